@@ -17,8 +17,8 @@ use gmo_coin_rs::private::*;
 /// ```
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    let http_client = Reqwest;
-    let private_api = PrivateAPI::<Reqwest> { http_client };
+    let http_client = Reqwest::new();
+    let private_api = PrivateAPI::new(http_client);
     let response = private_api.assets().await?;
 
     for asset in response.assets() {
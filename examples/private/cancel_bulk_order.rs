@@ -21,8 +21,8 @@ use gmo_coin_rs::symbol::Symbol;
 async fn main() -> Result<(), Error> {
     let symbols = vec![&Symbol::Btc, &Symbol::Eth];
 
-    let http_client = Reqwest;
-    let private_api = PrivateAPI::<Reqwest> { http_client };
+    let http_client = Reqwest::new();
+    let private_api = PrivateAPI::new(http_client);
     let response = private_api.cancel_bulk_order(&symbols).await?;
 
     // オプションを指定する場合。
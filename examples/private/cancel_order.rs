@@ -1,5 +1,6 @@
 use gmo_coin_rs::error::Error;
 use gmo_coin_rs::http_client::Reqwest;
+use gmo_coin_rs::id::OrderId;
 use gmo_coin_rs::private::*;
 
 /// 注文キャンセルAPIのExample
@@ -19,10 +20,10 @@ use gmo_coin_rs::private::*;
 /// ```
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    let order_id = std::env::var("GMO_COIN_ORDER_ID")?;
+    let order_id = OrderId::new(std::env::var("GMO_COIN_ORDER_ID")?);
 
-    let http_client = Reqwest;
-    let private_api = PrivateAPI::<Reqwest> { http_client };
+    let http_client = Reqwest::new();
+    let private_api = PrivateAPI::new(http_client);
     let response = private_api.cancel_order(&order_id).await?;
 
     println!("HTTPステータスコード: {}", response.http_status_code);
@@ -1,5 +1,6 @@
 use gmo_coin_rs::error::Error;
 use gmo_coin_rs::http_client::Reqwest;
+use gmo_coin_rs::id::OrderId;
 use gmo_coin_rs::private::*;
 
 /// 複数の注文キャンセルAPIのExample
@@ -20,11 +21,16 @@ use gmo_coin_rs::private::*;
 #[tokio::main]
 async fn main() -> Result<(), Error> {
     let order_ids_str = std::env::var("GMO_COIN_ORDER_IDS")?;
-    let order_ids = order_ids_str.split(",").collect::<Vec<&str>>();
+    let order_ids = order_ids_str
+        .split(",")
+        .map(OrderId::from)
+        .collect::<Vec<OrderId>>();
 
-    let http_client = Reqwest;
-    let private_api = PrivateAPI::<Reqwest> { http_client };
-    let response = private_api.cancel_orders(&order_ids).await?;
+    let http_client = Reqwest::new();
+    let private_api = PrivateAPI::new(http_client);
+    let response = private_api
+        .cancel_orders(&order_ids.iter().collect::<Vec<&OrderId>>())
+        .await?;
 
     println!("キャンセルに成功した注文:");
     for id in response.success() {
@@ -1,5 +1,7 @@
 use gmo_coin_rs::error::Error;
 use gmo_coin_rs::http_client::Reqwest;
+use gmo_coin_rs::id::PositionId;
+use gmo_coin_rs::price::Price;
 use gmo_coin_rs::private::*;
 
 /// ロスカットレート変更APIのExample
@@ -19,11 +21,11 @@ use gmo_coin_rs::private::*;
 /// ```
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    let position_id = std::env::var("GMO_COIN_POSITION_ID")?;
-    let losscut_price: i64 = std::env::var("GMO_COIN_LOSSCUT_PRICE")?.parse().unwrap();
+    let position_id = PositionId::new(std::env::var("GMO_COIN_POSITION_ID")?);
+    let losscut_price: Price = std::env::var("GMO_COIN_LOSSCUT_PRICE")?.parse().unwrap();
 
-    let http_client = Reqwest;
-    let private_api = PrivateAPI::<Reqwest> { http_client };
+    let http_client = Reqwest::new();
+    let private_api = PrivateAPI::new(http_client);
     let response = private_api
         .change_losscut_price(&position_id, losscut_price)
         .await?;
@@ -1,5 +1,7 @@
 use gmo_coin_rs::error::Error;
 use gmo_coin_rs::http_client::Reqwest;
+use gmo_coin_rs::id::OrderId;
+use gmo_coin_rs::price::Price;
 use gmo_coin_rs::private::*;
 
 /// 注文変更APIのExample
@@ -19,11 +21,11 @@ use gmo_coin_rs::private::*;
 /// ```
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    let order_id = std::env::var("GMO_COIN_ORDER_ID")?;
-    let price: i64 = std::env::var("GMO_COIN_CHANGE_PRICE")?.parse().unwrap();
+    let order_id = OrderId::new(std::env::var("GMO_COIN_ORDER_ID")?);
+    let price: Price = std::env::var("GMO_COIN_CHANGE_PRICE")?.parse().unwrap();
 
-    let http_client = Reqwest;
-    let private_api = PrivateAPI::<Reqwest> { http_client };
+    let http_client = Reqwest::new();
+    let private_api = PrivateAPI::new(http_client);
     let response = private_api.change_order(&order_id, price).await?;
 
     // ロスカットレートを指定する場合。
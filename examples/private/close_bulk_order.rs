@@ -3,6 +3,7 @@ use gmo_coin_rs::execution_type::ExecutionType;
 use gmo_coin_rs::http_client::Reqwest;
 use gmo_coin_rs::private::*;
 use gmo_coin_rs::side::Side;
+use gmo_coin_rs::size::Size;
 use gmo_coin_rs::symbol::Symbol;
 
 /// 決済注文APIのExample
@@ -21,11 +22,11 @@ use gmo_coin_rs::symbol::Symbol;
 /// ```
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    let size = 10.0; // !!! XrpJpyの最小サイズ !!!
+    let size: Size = "10.0".parse().unwrap(); // !!! XrpJpyの最小サイズ !!!
     let price = None; // 成行の場合は価格はなし。指値、逆指値の場合は価格を指定する。
 
-    let http_client = Reqwest;
-    let private_api = PrivateAPI::<Reqwest> { http_client };
+    let http_client = Reqwest::new();
+    let private_api = PrivateAPI::new(http_client);
     let response = private_api
         .close_bulk_order(
             &ExecutionType::Market,
@@ -1,8 +1,10 @@
 use gmo_coin_rs::error::Error;
 use gmo_coin_rs::execution_type::ExecutionType;
 use gmo_coin_rs::http_client::Reqwest;
+use gmo_coin_rs::id::PositionId;
 use gmo_coin_rs::private::*;
 use gmo_coin_rs::side::Side;
+use gmo_coin_rs::size::Size;
 use gmo_coin_rs::symbol::Symbol;
 
 /// 決済注文APIのExample
@@ -11,6 +13,7 @@ use gmo_coin_rs::symbol::Symbol;
 ///
 /// 実行前に環境変数`GMO_COIN_API_KEY`, `GMO_COIN_API_SECRET`にGMOコインのAPIキー、APIシークレットを設定します。
 /// また`GMO_COIN_POSITION_ID`で決済を行う建玉IDを指定します。
+/// 複数の建玉をまとめて決済する場合は`&[(&position_id1, size1), (&position_id2, size2)]`のように指定します。
 ///
 /// Private APIは実際に注文などが行われます。実行する際は十分気を付けてください。
 /// いかなる損害が発生しても当方は何ら責任を負いません。
@@ -22,20 +25,19 @@ use gmo_coin_rs::symbol::Symbol;
 /// ```
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    let position_id = std::env::var("GMO_COIN_POSITION_ID")?;
-    let size = 0.1; // !!! EthJpyの最小サイズ !!!
+    let position_id = PositionId::new(std::env::var("GMO_COIN_POSITION_ID")?);
+    let size: Size = "0.1".parse().unwrap(); // !!! EthJpyの最小サイズ !!!
     let price = None; // 成行の場合は価格はなし。指値、逆指値の場合は価格を指定する。
 
-    let http_client = Reqwest;
-    let private_api = PrivateAPI::<Reqwest> { http_client };
+    let http_client = Reqwest::new();
+    let private_api = PrivateAPI::new(http_client);
     let response = private_api
         .close_order(
             &ExecutionType::Market,
             &Symbol::EthJpy,
             &Side::Sell,
-            size,
             price,
-            &position_id,
+            &[(&position_id, size)],
         )
         .await?;
 
@@ -46,9 +48,8 @@ async fn main() -> Result<(), Error> {
     //         &ExecutionType::Market,
     //         &Symbol::EthJpy,
     //         &Side::Buy,
-    //         size,
     //         price,
-    //         &position_id,
+    //         &[(&position_id, size)],
     //         &TimeInForce::Fak,
     //     )
     //     .await?;
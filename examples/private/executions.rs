@@ -1,5 +1,6 @@
 use gmo_coin_rs::error::Error;
 use gmo_coin_rs::http_client::Reqwest;
+use gmo_coin_rs::id::OrderId;
 use gmo_coin_rs::private::*;
 
 /// 約定情報を取得するAPIのExample
@@ -19,14 +20,14 @@ use gmo_coin_rs::private::*;
 /// ```
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    let order_id = std::env::var("GMO_COIN_ORDER_ID")?;
+    let order_id = OrderId::new(std::env::var("GMO_COIN_ORDER_ID")?);
 
-    let http_client = Reqwest;
-    let private_api = PrivateAPI::<Reqwest> { http_client };
+    let http_client = Reqwest::new();
+    let private_api = PrivateAPI::new(http_client);
     let response = private_api.executions_with_order_id(&order_id).await?;
 
     // 約定IDを指定して約定情報を取得する場合。
-    // let execition_id = "1234...";
+    // let execution_id = ExecutionId::new("1234...");
     // let response = private_api.executions_with_execution_id(&execution_id).await?;
 
     for execution in response.executions() {
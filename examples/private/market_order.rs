@@ -3,6 +3,7 @@ use gmo_coin_rs::execution_type::ExecutionType;
 use gmo_coin_rs::http_client::Reqwest;
 use gmo_coin_rs::private::*;
 use gmo_coin_rs::side::Side;
+use gmo_coin_rs::size::Size;
 use gmo_coin_rs::symbol::Symbol;
 
 /// 新規成行注文APIのExample
@@ -21,14 +22,21 @@ use gmo_coin_rs::symbol::Symbol;
 /// ```
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    let size = 0.0001; // !!! 最小サイズ !!!
+    let size: Size = "0.0001".parse().unwrap(); // !!! 最小サイズ !!!
 
-    let http_client = Reqwest;
-    let private_api = PrivateAPI::<Reqwest> { http_client };
+    let http_client = Reqwest::new();
+    let private_api = PrivateAPI::new(http_client);
     let response = private_api
         .order(&ExecutionType::Market, &Symbol::Btc, &Side::Buy, size, None)
         .await?;
 
+    // 取引ルールに基づいて注文パラメータを事前に検証する場合。
+    // use gmo_coin_rs::order_validator::OrderValidator;
+    // use gmo_coin_rs::public::PublicAPI;
+    // let public_api = PublicAPI::new(Reqwest::new());
+    // let validator = OrderValidator::fetch(&public_api).await?;
+    // let private_api = PrivateAPI::new(Reqwest::new()).with_validator(validator);
+
     // 執行数量条件を指定する場合。
     // use gmo_coin_rs::time_in_force::TimeInForce;
     // let response = private_api.order_with_options(&ExecutionType::Market, &Symbol::Btc, &Side::Buy, size, None, &TimeInForce::Fak, None)
@@ -19,8 +19,8 @@ use gmo_coin_rs::symbol::Symbol;
 /// ```
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    let http_client = Reqwest;
-    let private_api = PrivateAPI::<Reqwest> { http_client };
+    let http_client = Reqwest::new();
+    let private_api = PrivateAPI::new(http_client);
     let response = private_api.open_positions(&Symbol::BtcJpy).await?;
 
     // 取得対象ページ、1ページ当たりの取得件数を指定する場合。
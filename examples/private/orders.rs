@@ -1,5 +1,6 @@
 use gmo_coin_rs::error::Error;
 use gmo_coin_rs::http_client::Reqwest;
+use gmo_coin_rs::id::OrderId;
 use gmo_coin_rs::private::*;
 
 /// 注文情報を取得するAPIのExample
@@ -20,11 +21,13 @@ use gmo_coin_rs::private::*;
 #[tokio::main]
 async fn main() -> Result<(), Error> {
     let order_id: String = std::env::var("GMO_COIN_ORDER_IDS")?;
-    let order_ids: Vec<&str> = order_id.split(',').collect();
+    let order_ids: Vec<OrderId> = order_id.split(',').map(OrderId::from).collect();
 
-    let http_client = Reqwest;
-    let private_api = PrivateAPI::<Reqwest> { http_client };
-    let response = private_api.orders(&order_ids).await?;
+    let http_client = Reqwest::new();
+    let private_api = PrivateAPI::new(http_client);
+    let response = private_api
+        .orders(&order_ids.iter().collect::<Vec<&OrderId>>())
+        .await?;
 
     for order in response.orders() {
         println!("親注文ID: {}", order.root_order_id);
@@ -0,0 +1,48 @@
+use futures::StreamExt;
+use gmo_coin_rs::error::Error;
+use gmo_coin_rs::http_client::Reqwest;
+use gmo_coin_rs::private::*;
+use gmo_coin_rs::private_ws::*;
+
+/// Private WebSocket APIで約定情報通知、注文情報通知を購読するExample
+///
+/// # Example
+///
+/// 実行前に環境変数`GMO_COIN_API_KEY`, `GMO_COIN_API_SECRET`にGMOコインのAPIキー、APIシークレットを設定します。
+///
+/// ```
+/// cargo build --examples
+/// cargo run --example private_ws
+/// ```
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let http_client = Reqwest::new();
+    let private_api = PrivateAPI::new(http_client);
+    let mut private_ws_api = PrivateWebSocketAPI::connect(&private_api).await?;
+    private_ws_api
+        .subscribe(&PrivateChannel::ExecutionEvents)
+        .await?;
+    private_ws_api
+        .subscribe(&PrivateChannel::OrderEvents)
+        .await?;
+
+    // イベントを受け取り続ける。アクセストークンの延長と再接続は自動で行われる。
+    let mut events = Box::pin(private_ws_api.into_stream());
+    while let Some(event) = events.next().await {
+        match event? {
+            PrivateEvent::Execution {
+                msg_type,
+                execution,
+            } => println!(
+                "約定: {} 注文ID: {} 価格: {} 数量: {}",
+                msg_type, execution.order_id, execution.price, execution.size
+            ),
+            PrivateEvent::Order { msg_type, order } => println!(
+                "注文: {} 注文ID: {} ステータス: {}",
+                msg_type, order.order_id, order.status
+            ),
+            _ => {}
+        }
+    }
+    Ok(())
+}
@@ -1,8 +1,10 @@
 use gmo_coin_rs::error::Error;
 use gmo_coin_rs::execution_type::ExecutionType;
 use gmo_coin_rs::http_client::Reqwest;
+use gmo_coin_rs::price::Price;
 use gmo_coin_rs::private::*;
 use gmo_coin_rs::side::Side;
+use gmo_coin_rs::size::Size;
 use gmo_coin_rs::symbol::Symbol;
 
 /// 新規逆指値注文APIのExample
@@ -22,11 +24,11 @@ use gmo_coin_rs::symbol::Symbol;
 /// ```
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    let price: i64 = std::env::var("GMO_COIN_STOP_PRICE")?.parse().unwrap();
-    let size = 0.0001; // !!! 最小サイズ !!!
+    let price: Price = std::env::var("GMO_COIN_STOP_PRICE")?.parse().unwrap();
+    let size: Size = "0.0001".parse().unwrap(); // !!! 最小サイズ !!!
 
-    let http_client = Reqwest;
-    let private_api = PrivateAPI::<Reqwest> { http_client };
+    let http_client = Reqwest::new();
+    let private_api = PrivateAPI::new(http_client);
     let response = private_api
         .order(
             &ExecutionType::Stop,
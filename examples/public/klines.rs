@@ -0,0 +1,43 @@
+use chrono::NaiveDate;
+use gmo_coin_rs::error::Error;
+use gmo_coin_rs::http_client::Reqwest;
+use gmo_coin_rs::kline_interval::KlineInterval;
+use gmo_coin_rs::public::*;
+use gmo_coin_rs::symbol::Symbol;
+
+/// KLine情報を取得するAPIのExample
+///
+/// # Example
+/// ```
+/// cargo build --examples
+/// cargo run --example klines
+/// ```
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let http_client = Reqwest::new();
+    let public_api = PublicAPI::new(http_client);
+    let date = NaiveDate::from_ymd_opt(2021, 4, 17).unwrap();
+    let response = public_api
+        .klines(&Symbol::Btc, &KlineInterval::Hour1, &date)
+        .await?;
+
+    // 期間を指定して複数日分を取得する場合。
+    // let from = NaiveDate::from_ymd_opt(2021, 4, 15).unwrap();
+    // let klines = public_api
+    //     .klines_range(&Symbol::Btc, &KlineInterval::Hour1, &from, &date)
+    //     .await?;
+
+    for kline in response.klines() {
+        println!("開始時刻: {}", kline.open_time);
+        println!("始値: {}", kline.open);
+        println!("高値: {}", kline.high);
+        println!("安値: {}", kline.low);
+        println!("終値: {}", kline.close);
+        println!("取引量: {}\n", kline.volume);
+    }
+
+    println!("HTTPステータスコード: {}", response.http_status_code);
+    println!("ステータスコード: {}", response.body.status);
+    println!("APIを呼び出した時間: {}", response.body.responsetime,);
+    Ok(())
+}
@@ -12,8 +12,8 @@ use gmo_coin_rs::symbol::Symbol;
 /// ```
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    let http_client = Reqwest;
-    let public_api = PublicAPI::<Reqwest> { http_client };
+    let http_client = Reqwest::new();
+    let public_api = PublicAPI::new(http_client);
     let response = public_api.orderbooks(&Symbol::Btc).await?;
 
     println!("ask:");
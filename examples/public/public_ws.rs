@@ -0,0 +1,38 @@
+use futures::StreamExt;
+use gmo_coin_rs::error::Error;
+use gmo_coin_rs::public_ws::*;
+use gmo_coin_rs::symbol::Symbol;
+
+/// Public WebSocket APIで最新レートを購読するExample
+///
+/// # Example
+/// ```
+/// cargo build --examples
+/// cargo run --example public_ws
+/// ```
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let mut public_ws_api = PublicWebSocketAPI::connect().await?;
+    public_ws_api
+        .subscribe(&Channel::Ticker, &Symbol::Btc)
+        .await?;
+
+    // 最新レートを5件受け取ったら終了する。
+    for _ in 0..5 {
+        let message = match public_ws_api.next().await {
+            Some(m) => m?,
+            None => break,
+        };
+        if let PublicMessage::Ticker(ticker) = message {
+            println!(
+                "ask: {}, bid: {}, timestamp: {}",
+                ticker.ask, ticker.bid, ticker.timestamp
+            );
+        }
+    }
+
+    public_ws_api
+        .unsubscribe(&Channel::Ticker, &Symbol::Btc)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use gmo_coin_rs::error::Error;
+use gmo_coin_rs::http_client::Reqwest;
+use gmo_coin_rs::public::*;
+
+/// 取引ルールを取得するAPIのExample
+///
+/// # Example
+/// ```
+/// cargo build --examples
+/// cargo run --example symbols
+/// ```
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let http_client = Reqwest::new();
+    let public_api = PublicAPI::new(http_client);
+    let response = public_api.symbols().await?;
+
+    for rule in response.symbols() {
+        println!("銘柄: {}", rule.symbol);
+        println!("最小注文数量: {}", rule.min_order_size);
+        println!("最大注文数量: {}", rule.max_order_size);
+        println!("最小注文単位: {}", rule.size_step);
+        println!("呼値: {}", rule.tick_size);
+        println!("Taker手数料: {}", rule.taker_fee);
+        println!("Maker手数料: {}\n", rule.maker_fee);
+    }
+
+    println!("HTTPステータスコード: {}", response.http_status_code);
+    println!("ステータスコード: {}", response.body.status);
+    println!("APIを呼び出した時間: {}", response.body.responsetime,);
+    Ok(())
+}
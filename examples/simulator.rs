@@ -0,0 +1,35 @@
+use gmo_coin_rs::credentials::Credentials;
+use gmo_coin_rs::error::Error;
+use gmo_coin_rs::price::Price;
+use gmo_coin_rs::simulator::Simulator;
+use gmo_coin_rs::symbol::Symbol;
+use std::time::Duration;
+
+/// 模擬取引所サーバーのExample
+///
+/// # Example
+///
+/// `http://127.0.0.1:8080`で待ち受け、BTC_JPYの価格を1秒ごとに動かします。Ctrl-Cで停止します。
+/// APIキー`simulator`, APIシークレット`simulator`で、接続先を`http://127.0.0.1:8080/public`, `http://127.0.0.1:8080/private`に向けたクライアントから呼び出せます。
+///
+/// ```
+/// cargo run --example simulator --features simulator
+/// ```
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let prices: Vec<Price> = (0..3600)
+        .map(|i| {
+            let price = 5_000_000 + ((i as f64 / 60.0).sin() * 100_000.0) as i64;
+            price.to_string().parse().unwrap()
+        })
+        .collect();
+    let simulator = Simulator::new(Credentials::new("simulator", "simulator"))
+        .with_price_path(&Symbol::BtcJpy, prices)
+        .with_tick_interval(Duration::from_secs(1))
+        .start("127.0.0.1:8080".parse().unwrap())
+        .await?;
+
+    println!("待ち受けているアドレス: {}", simulator.local_addr());
+    tokio::signal::ctrl_c().await.ok();
+    Ok(())
+}
@@ -0,0 +1,275 @@
+//! GMOコインのAPIが返すエラーコードを定義する。
+
+use serde::{Deserialize, Serialize};
+
+/// エラーコード
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ApiErrorCode {
+    /// 有効注文があり、注文可能数量を超えている
+    #[serde(rename = "ERR-200")]
+    ActiveOrdersExceedQuantity,
+
+    /// 取引余力が不足している
+    #[serde(rename = "ERR-201")]
+    InsufficientFunds,
+
+    /// 指定した建玉が存在しない
+    #[serde(rename = "ERR-254")]
+    PositionNotFound,
+
+    /// 有効注文の件数が上限に達している
+    #[serde(rename = "ERR-635")]
+    ActiveOrderLimitExceeded,
+
+    /// 建玉の件数が上限に達している
+    #[serde(rename = "ERR-759")]
+    PositionLimitExceeded,
+
+    /// 注文変更前と同じ価格が指定された
+    #[serde(rename = "ERR-760")]
+    SamePrice,
+
+    /// 注文価格が制限値幅を超えている
+    #[serde(rename = "ERR-761")]
+    PriceLimitExceeded,
+
+    /// API呼び出し回数の制限を超えた
+    #[serde(rename = "ERR-5003")]
+    TooManyRequests,
+
+    /// API-TIMESTAMPが遅すぎる
+    #[serde(rename = "ERR-5008")]
+    TimestampTooLate,
+
+    /// API-TIMESTAMPが早すぎる
+    #[serde(rename = "ERR-5009")]
+    TimestampTooEarly,
+
+    /// API-SIGNが不正
+    #[serde(rename = "ERR-5010")]
+    InvalidSignature,
+
+    /// API-KEYが設定されていない
+    #[serde(rename = "ERR-5011")]
+    ApiKeyNotSet,
+
+    /// API-KEYの認証に失敗した
+    #[serde(rename = "ERR-5012")]
+    AuthenticationFailed,
+
+    /// パラメーターが不正
+    #[serde(rename = "ERR-5106")]
+    InvalidParameter,
+
+    /// 注文数量の小数点以下の桁数が多すぎる
+    #[serde(rename = "ERR-5114")]
+    SizeDecimalPlacesExceeded,
+
+    /// 注文がすでに変更、取消、約定されている
+    #[serde(rename = "ERR-5122")]
+    OrderAlreadyChanged,
+
+    /// 指定した注文が存在しない
+    #[serde(rename = "ERR-5123")]
+    OrderNotFound,
+
+    /// 定時メンテナンス中
+    #[serde(rename = "ERR-5201")]
+    Maintenance,
+
+    /// 臨時メンテナンス中
+    #[serde(rename = "ERR-5202")]
+    EmergencyMaintenance,
+
+    /// APIのパスが不正
+    #[serde(rename = "ERR-5204")]
+    InvalidPath,
+}
+
+/// 有効注文があり、注文可能数量を超えている
+pub const ACTIVE_ORDERS_EXCEED_QUANTITY: &str = "ERR-200";
+
+/// 取引余力が不足している
+pub const INSUFFICIENT_FUNDS: &str = "ERR-201";
+
+/// 指定した建玉が存在しない
+pub const POSITION_NOT_FOUND: &str = "ERR-254";
+
+/// 有効注文の件数が上限に達している
+pub const ACTIVE_ORDER_LIMIT_EXCEEDED: &str = "ERR-635";
+
+/// 建玉の件数が上限に達している
+pub const POSITION_LIMIT_EXCEEDED: &str = "ERR-759";
+
+/// 注文変更前と同じ価格が指定された
+pub const SAME_PRICE: &str = "ERR-760";
+
+/// 注文価格が制限値幅を超えている
+pub const PRICE_LIMIT_EXCEEDED: &str = "ERR-761";
+
+/// API呼び出し回数の制限を超えた
+pub const TOO_MANY_REQUESTS: &str = "ERR-5003";
+
+/// API-TIMESTAMPが遅すぎる
+pub const TIMESTAMP_TOO_LATE: &str = "ERR-5008";
+
+/// API-TIMESTAMPが早すぎる
+pub const TIMESTAMP_TOO_EARLY: &str = "ERR-5009";
+
+/// API-SIGNが不正
+pub const INVALID_SIGNATURE: &str = "ERR-5010";
+
+/// API-KEYが設定されていない
+pub const API_KEY_NOT_SET: &str = "ERR-5011";
+
+/// API-KEYの認証に失敗した
+pub const AUTHENTICATION_FAILED: &str = "ERR-5012";
+
+/// パラメーターが不正
+pub const INVALID_PARAMETER: &str = "ERR-5106";
+
+/// 注文数量の小数点以下の桁数が多すぎる
+pub const SIZE_DECIMAL_PLACES_EXCEEDED: &str = "ERR-5114";
+
+/// 注文がすでに変更、取消、約定されている
+pub const ORDER_ALREADY_CHANGED: &str = "ERR-5122";
+
+/// 指定した注文が存在しない
+pub const ORDER_NOT_FOUND: &str = "ERR-5123";
+
+/// 定時メンテナンス中
+pub const MAINTENANCE: &str = "ERR-5201";
+
+/// 臨時メンテナンス中
+pub const EMERGENCY_MAINTENANCE: &str = "ERR-5202";
+
+/// APIのパスが不正
+pub const INVALID_PATH: &str = "ERR-5204";
+
+impl ApiErrorCode {
+    /// エラーコードを文字列に変換する。
+    pub fn to_string(&self) -> &str {
+        match self {
+            ApiErrorCode::ActiveOrdersExceedQuantity => ACTIVE_ORDERS_EXCEED_QUANTITY,
+            ApiErrorCode::InsufficientFunds => INSUFFICIENT_FUNDS,
+            ApiErrorCode::PositionNotFound => POSITION_NOT_FOUND,
+            ApiErrorCode::ActiveOrderLimitExceeded => ACTIVE_ORDER_LIMIT_EXCEEDED,
+            ApiErrorCode::PositionLimitExceeded => POSITION_LIMIT_EXCEEDED,
+            ApiErrorCode::SamePrice => SAME_PRICE,
+            ApiErrorCode::PriceLimitExceeded => PRICE_LIMIT_EXCEEDED,
+            ApiErrorCode::TooManyRequests => TOO_MANY_REQUESTS,
+            ApiErrorCode::TimestampTooLate => TIMESTAMP_TOO_LATE,
+            ApiErrorCode::TimestampTooEarly => TIMESTAMP_TOO_EARLY,
+            ApiErrorCode::InvalidSignature => INVALID_SIGNATURE,
+            ApiErrorCode::ApiKeyNotSet => API_KEY_NOT_SET,
+            ApiErrorCode::AuthenticationFailed => AUTHENTICATION_FAILED,
+            ApiErrorCode::InvalidParameter => INVALID_PARAMETER,
+            ApiErrorCode::SizeDecimalPlacesExceeded => SIZE_DECIMAL_PLACES_EXCEEDED,
+            ApiErrorCode::OrderAlreadyChanged => ORDER_ALREADY_CHANGED,
+            ApiErrorCode::OrderNotFound => ORDER_NOT_FOUND,
+            ApiErrorCode::Maintenance => MAINTENANCE,
+            ApiErrorCode::EmergencyMaintenance => EMERGENCY_MAINTENANCE,
+            ApiErrorCode::InvalidPath => INVALID_PATH,
+        }
+    }
+
+    /// 時間をおいて同じリクエストを送れば成功する可能性があるか？
+    /// 呼び出し回数の制限とメンテナンス中のエラーは、GMOコインがリクエストを処理せずに返す。
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ApiErrorCode::TooManyRequests
+                | ApiErrorCode::Maintenance
+                | ApiErrorCode::EmergencyMaintenance
+        )
+    }
+
+    /// メンテナンス中か？
+    pub fn is_maintenance(&self) -> bool {
+        matches!(
+            self,
+            ApiErrorCode::Maintenance | ApiErrorCode::EmergencyMaintenance
+        )
+    }
+
+    /// 取引余力が不足しているか？
+    pub fn is_insufficient_funds(&self) -> bool {
+        matches!(self, ApiErrorCode::InsufficientFunds)
+    }
+
+    /// 認証に失敗したか？ APIキー, APIシークレットや時刻の設定を見直す必要がある。
+    pub fn is_auth_failure(&self) -> bool {
+        matches!(
+            self,
+            ApiErrorCode::TimestampTooLate
+                | ApiErrorCode::TimestampTooEarly
+                | ApiErrorCode::InvalidSignature
+                | ApiErrorCode::ApiKeyNotSet
+                | ApiErrorCode::AuthenticationFailed
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto::MaybeUnknown;
+    use crate::error::Error;
+    use crate::response::ErrorResponse;
+
+    #[test]
+    fn test_deserialize() {
+        let code: MaybeUnknown<ApiErrorCode> = serde_json::from_str(r#""ERR-5003""#).unwrap();
+        assert_eq!(code, ApiErrorCode::TooManyRequests);
+        let code: MaybeUnknown<ApiErrorCode> = serde_json::from_str(r#""ERR-9999""#).unwrap();
+        assert_eq!(code, MaybeUnknown::Unknown("ERR-9999".to_string()));
+    }
+
+    #[test]
+    fn test_error_code_of_error() {
+        let error = |code: &str| {
+            Error::from(
+                serde_json::from_str::<ErrorResponse>(&format!(
+                    r#"{{"status":1,"messages":[{{"message_code":"{}","message_string":""}}]}}"#,
+                    code
+                ))
+                .unwrap(),
+            )
+        };
+        assert!(matches!(
+            error("ERR-5201"),
+            Error::APIError {
+                code: MaybeUnknown::Known(ApiErrorCode::Maintenance),
+                ..
+            }
+        ));
+        assert_eq!(
+            error("ERR-201").api_error_code(),
+            Some(ApiErrorCode::InsufficientFunds.into())
+        );
+        assert_eq!(
+            error("ERR-9999").api_error_code(),
+            Some(MaybeUnknown::Unknown("ERR-9999".to_string()))
+        );
+        assert!(Error::UnknownError.api_error_code().is_none());
+    }
+
+    #[test]
+    fn test_to_string() {
+        assert_eq!(ApiErrorCode::InsufficientFunds.to_string(), "ERR-201");
+        assert_eq!(ApiErrorCode::EmergencyMaintenance.to_string(), "ERR-5202");
+    }
+
+    #[test]
+    fn test_classification() {
+        assert!(ApiErrorCode::TooManyRequests.is_retryable());
+        assert!(ApiErrorCode::Maintenance.is_retryable());
+        assert!(!ApiErrorCode::InsufficientFunds.is_retryable());
+        assert!(!ApiErrorCode::InvalidParameter.is_retryable());
+        assert!(ApiErrorCode::EmergencyMaintenance.is_maintenance());
+        assert!(!ApiErrorCode::TooManyRequests.is_maintenance());
+        assert!(ApiErrorCode::InsufficientFunds.is_insufficient_funds());
+        assert!(ApiErrorCode::InvalidSignature.is_auth_failure());
+        assert!(!ApiErrorCode::OrderNotFound.is_auth_failure());
+    }
+}
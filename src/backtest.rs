@@ -0,0 +1,670 @@
+//! 過去の約定履歴やローソク足で注文を模擬約定させるバックテストを実装する。`backtest`フィーチャーを有効にすると使える。
+//! `PrivateAPI<Backtest>`, `PublicAPI<Backtest>`として使うので、本番と同じ注文APIで書いた戦略を何か月分もの過去データで動かせる。
+
+use crate::api_error_code::ApiErrorCode;
+use crate::client_config::ClientConfig;
+use crate::dto::Trade;
+use crate::error::Error;
+use crate::exchange::engine::{Engine, Quote};
+use crate::exchange::{
+    self, parse_query, query_symbol, timestamp, RouteResult, DEFAULT_MAKER_FEE_RATE,
+    DEFAULT_TAKER_FEE_RATE,
+};
+use crate::headers::Headers;
+use crate::http_client::HttpClient;
+use crate::order_type::OrderType;
+use crate::price::Price;
+use crate::public::klines::Kline;
+use crate::response::RawResponse;
+use crate::side::Side;
+use crate::size::Size;
+use crate::symbol::Symbol;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// 約定履歴をファイルから読み込む。ファイルは1行に1つの約定を約定履歴APIの`list`の要素と同じ形式で書いたJSON Lines形式。空行は読み飛ばす。
+///
+/// # Arguments
+///
+/// * `path` - ファイルのパス。
+///
+pub fn load_trades(path: impl AsRef<Path>) -> Result<Vec<Trade>, Error> {
+    read_json_lines(path)
+}
+
+/// ローソク足をファイルから読み込む。ファイルは1行に1本のローソク足をKLine情報の取得APIの`data`の要素と同じ形式で書いたJSON Lines形式。空行は読み飛ばす。
+///
+/// # Arguments
+///
+/// * `path` - ファイルのパス。
+///
+pub fn load_klines(path: impl AsRef<Path>) -> Result<Vec<Kline>, Error> {
+    read_json_lines(path)
+}
+
+fn read_json_lines<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<Vec<T>, Error> {
+    let file = File::open(path).map_err(Error::HistoryFileError)?;
+    let mut list = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(Error::HistoryFileError)?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        list.push(serde_json::from_str::<T>(&line)?);
+    }
+    Ok(list)
+}
+
+/// 過去データの1つの価格。
+struct Tick {
+    timestamp: DateTime<Utc>,
+    symbol: String,
+    price: Price,
+}
+
+/// ローソク足を始値、高値と安値、終値の4つの価格に分ける。陽線は安値を先に、陰線は高値を先につけたとみなす。
+/// ローソク足の期間は次のローソク足の開始時刻までとし、4つの価格を等間隔に並べる。
+fn kline_ticks(symbol: &str, klines: &[Kline]) -> Vec<Tick> {
+    let mut ticks = Vec::new();
+    let mut period = Duration::zero();
+    for (i, kline) in klines.iter().enumerate() {
+        if let Some(next) = klines.get(i + 1) {
+            period = next.open_time - kline.open_time;
+        }
+        let prices = if kline.close >= kline.open {
+            [kline.open, kline.low, kline.high, kline.close]
+        } else {
+            [kline.open, kline.high, kline.low, kline.close]
+        };
+        for (j, price) in prices.iter().enumerate() {
+            ticks.push(Tick {
+                timestamp: kline.open_time + period / 4 * j as i32,
+                symbol: symbol.to_string(),
+                price: *price,
+            });
+        }
+    }
+    ticks
+}
+
+/// ある時刻の口座の評価額。
+#[derive(Clone, Debug)]
+pub struct EquityPoint {
+    /// 時刻。
+    pub timestamp: DateTime<Utc>,
+
+    /// 建玉の評価損益を含む評価額(円)。
+    pub equity: i64,
+
+    /// それまでの評価額の最大値からの下落幅(円)。
+    pub drawdown: i64,
+}
+
+/// ロスカットされた建玉の決済。
+#[derive(Clone, Debug)]
+pub struct LosscutEvent {
+    /// 決済した時刻。
+    pub timestamp: DateTime<Utc>,
+
+    /// 銘柄名。
+    pub symbol: String,
+
+    /// 決済注文の売買区分。買い建玉のロスカットの場合は`Sell`。
+    pub side: Side,
+
+    /// 決済した数量。
+    pub size: Size,
+
+    /// 決済した価格。
+    pub price: Price,
+
+    /// 確定した損益(円)。
+    pub loss_gain: i64,
+}
+
+/// バックテストの結果。
+#[derive(Clone, Debug)]
+pub struct BacktestReport {
+    /// 開始時の円残高。
+    pub initial_balance: i64,
+
+    /// 終了時の建玉の評価損益を含む評価額。
+    pub final_equity: i64,
+
+    /// 価格が動くたびに記録した評価額の推移。
+    pub equity_curve: Vec<EquityPoint>,
+
+    /// 最大ドローダウン(円)。
+    pub max_drawdown: i64,
+
+    /// 最大ドローダウンのそれまでの評価額の最大値に対する割合。
+    pub max_drawdown_rate: f64,
+
+    /// 支払った手数料の合計。メイカー手数料で受け取った分は差し引く。
+    pub total_fees: i64,
+
+    /// 建玉の決済で確定した損益の合計。手数料は含まない。
+    pub realized_profit_loss: i64,
+
+    /// 終了時に残っている建玉の評価損益。
+    pub unrealized_profit_loss: i64,
+
+    /// 約定の数。
+    pub execution_count: usize,
+
+    /// ロスカット。
+    pub losscuts: Vec<LosscutEvent>,
+}
+
+/// バックテストの進み具合。
+struct State {
+    engine: Engine,
+
+    /// 次に反映する価格の位置。
+    index: usize,
+
+    /// 銘柄ごとの現在の価格、高値、安値。
+    prices: BTreeMap<String, (Price, Price, Price)>,
+
+    equity_curve: Vec<EquityPoint>,
+}
+
+/// 過去データで注文を模擬約定させるHTTPクライアント。`PublicAPI`, `PrivateAPI`に渡して使う。
+///
+/// 時計は過去データの時刻で進む。`step`を呼ぶたびに次の価格を反映し、その価格で指値/逆指値注文の約定とロスカットを判定する。
+/// 成行注文はその時点の価格ですぐに約定する。指値注文は価格が指値に達したときに指値で約定し、逆指値注文は価格が逆指値に達したときにその価格で約定する。
+/// 執行数量条件は本番と同じく、FAKは約定しなかった残りを失効させ、FOKは全量約定できない場合に、SOKは即時約定する価格の場合にそれぞれ失効させる。
+/// Public APIは取引所ステータス、最新レート、板情報を過去データから返す。署名は検証しないので、APIキーとAPIシークレットは何でもよい。
+pub struct Backtest {
+    config: ClientConfig,
+    ticks: Vec<Tick>,
+    initial_balance: i64,
+    state: Mutex<State>,
+}
+
+impl Default for Backtest {
+    fn default() -> Self {
+        Backtest::new()
+    }
+}
+
+impl Backtest {
+    /// 過去データのないバックテストを作成する。円残高は100万円、レバレッジは2倍、手数料率はメイカー-0.01%、テイカー0.05%。
+    pub fn new() -> Backtest {
+        let initial_balance = 1_000_000;
+        let mut engine = Engine::new(initial_balance, 2);
+        engine.set_fee_rates(DEFAULT_MAKER_FEE_RATE, DEFAULT_TAKER_FEE_RATE);
+        Backtest {
+            config: ClientConfig::default(),
+            ticks: Vec::new(),
+            initial_balance,
+            state: Mutex::new(State {
+                engine,
+                index: 0,
+                prices: BTreeMap::new(),
+                equity_curve: Vec::new(),
+            }),
+        }
+    }
+
+    /// 約定履歴を過去データに加える。約定ごとに価格が動く。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `trades` - 約定履歴。順番は問わない。
+    ///
+    pub fn with_trades(mut self, symbol: &Symbol, trades: Vec<Trade>) -> Backtest {
+        self.ticks.extend(trades.into_iter().map(|t| Tick {
+            timestamp: t.timestamp,
+            symbol: symbol.to_string().to_owned(),
+            price: t.price,
+        }));
+        self.ticks.sort_by_key(|t| t.timestamp);
+        self
+    }
+
+    /// ローソク足を過去データに加える。1本のローソク足で始値、高値と安値、終値の順に4回価格が動く。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `klines` - 開始時刻の順に並んだローソク足。
+    ///
+    pub fn with_klines(mut self, symbol: &Symbol, klines: Vec<Kline>) -> Backtest {
+        self.ticks.extend(kline_ticks(symbol.to_string(), &klines));
+        self.ticks.sort_by_key(|t| t.timestamp);
+        self
+    }
+
+    /// Public API, Private APIのベースURLを指定する。`PublicAPI`, `PrivateAPI`に渡す設定と同じものを指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - 設定。
+    ///
+    pub fn with_config(mut self, config: ClientConfig) -> Backtest {
+        self.config = config;
+        self
+    }
+
+    /// 開始時の円残高を指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `balance` - 円残高。
+    ///
+    pub fn with_balance(mut self, balance: i64) -> Backtest {
+        self.initial_balance = balance;
+        self.state.lock().unwrap().engine.set_balance(balance);
+        self
+    }
+
+    /// レバレッジを指定する。証拠金とロスカットレートの初期値の計算に使う。
+    ///
+    /// # Arguments
+    ///
+    /// * `leverage` - レバレッジ。
+    ///
+    pub fn with_leverage(self, leverage: i64) -> Backtest {
+        self.state.lock().unwrap().engine.set_leverage(leverage);
+        self
+    }
+
+    /// 約定代金に対する手数料率を指定する。負の手数料率は手数料を受け取ることを表す。
+    ///
+    /// # Arguments
+    ///
+    /// * `maker_fee_rate` - 価格が動いて約定した指値注文の手数料率。例: `-0.0001`
+    /// * `taker_fee_rate` - 成行注文、逆指値注文、即時約定した指値注文の手数料率。例: `0.0005`
+    ///
+    pub fn with_fee_rates(self, maker_fee_rate: f64, taker_fee_rate: f64) -> Backtest {
+        self.state
+            .lock()
+            .unwrap()
+            .engine
+            .set_fee_rates(maker_fee_rate, taker_fee_rate);
+        self
+    }
+
+    /// 価格が1回動くあいだに1つの指値注文が約定できる数量を指定する。指定しない場合は全量約定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `liquidity` - 約定できる数量。
+    ///
+    pub fn with_liquidity(self, liquidity: Size) -> Backtest {
+        self.state
+            .lock()
+            .unwrap()
+            .engine
+            .set_liquidity(Some(liquidity));
+        self
+    }
+
+    /// 過去データの時計の現在時刻を取得する。まだ`step`を呼んでいない場合は`None`を返す。
+    pub fn now(&self) -> Option<DateTime<Utc>> {
+        let state = self.state.lock().unwrap();
+        state.index.checked_sub(1).map(|i| self.ticks[i].timestamp)
+    }
+
+    /// 過去データを最後まで反映したか？
+    pub fn is_finished(&self) -> bool {
+        self.state.lock().unwrap().index >= self.ticks.len()
+    }
+
+    /// 次の価格を反映し、ロスカットと注文の約定を判定する。過去データを最後まで反映していた場合は何もせずに`false`を返す。
+    pub fn step(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let tick = match self.ticks.get(state.index) {
+            Some(t) => t,
+            None => return false,
+        };
+        state.index += 1;
+        let entry = state
+            .prices
+            .entry(tick.symbol.clone())
+            .or_insert((tick.price, tick.price, tick.price));
+        *entry = (tick.price, entry.1.max(tick.price), entry.2.min(tick.price));
+        state.engine.set_clock(tick.timestamp);
+        state.engine.set_quote(&tick.symbol, Quote::at(tick.price));
+        state.engine.update();
+        let equity = state.engine.margin().actual_profit_loss;
+        let peak = state
+            .equity_curve
+            .iter()
+            .map(|p| p.equity)
+            .fold(self.initial_balance, i64::max);
+        state.equity_curve.push(EquityPoint {
+            timestamp: tick.timestamp,
+            equity,
+            drawdown: (peak - equity).max(0),
+        });
+        true
+    }
+
+    /// 指定した時刻までの価格をすべて反映する。過去データが残っている場合は`true`を返す。
+    ///
+    /// # Arguments
+    ///
+    /// * `until` - 時刻。この時刻ちょうどの価格も反映する。
+    ///
+    pub fn advance_until(&self, until: DateTime<Utc>) -> bool {
+        loop {
+            let next = self
+                .ticks
+                .get(self.state.lock().unwrap().index)
+                .map(|t| t.timestamp);
+            match next {
+                Some(t) if t <= until => {
+                    self.step();
+                }
+                Some(_) => return true,
+                None => return false,
+            }
+        }
+    }
+
+    /// ここまでの結果を集計する。
+    pub fn report(&self) -> BacktestReport {
+        let state = self.state.lock().unwrap();
+        let engine = &state.engine;
+        let executions = engine.executions();
+        let losscuts = executions
+            .iter()
+            .filter(|e| {
+                matches!(engine.order(e.order_id), Some(o) if o.order_type == OrderType::Losscut)
+            })
+            .map(|e| LosscutEvent {
+                timestamp: e.timestamp,
+                symbol: e.symbol.clone(),
+                side: e.side,
+                size: e.size,
+                price: e.price,
+                loss_gain: e.loss_gain,
+            })
+            .collect();
+        let margin = engine.margin();
+        let mut peak = self.initial_balance;
+        let mut max_drawdown = 0;
+        let mut max_drawdown_rate = 0.0;
+        for point in &state.equity_curve {
+            peak = peak.max(point.equity);
+            if point.drawdown > max_drawdown {
+                max_drawdown = point.drawdown;
+            }
+            if peak > 0 {
+                max_drawdown_rate =
+                    f64::max(max_drawdown_rate, point.drawdown as f64 / peak as f64);
+            }
+        }
+        BacktestReport {
+            initial_balance: self.initial_balance,
+            final_equity: margin.actual_profit_loss,
+            equity_curve: state.equity_curve.clone(),
+            max_drawdown,
+            max_drawdown_rate,
+            total_fees: executions.iter().map(|e| e.fee).sum(),
+            realized_profit_loss: executions.iter().map(|e| e.loss_gain).sum(),
+            unrealized_profit_loss: margin.profit_loss,
+            execution_count: executions.len(),
+            losscuts,
+        }
+    }
+
+    /// リクエストをパスで振り分け、GMOコインと同じ形式のレスポンスを返す。
+    fn respond(&self, method: &str, url: &str, parameters: Option<&Value>) -> RawResponse {
+        let (url, query) = match url.find('?') {
+            Some(i) => (&url[..i], Some(&url[i + 1..])),
+            None => (url, None),
+        };
+        let query = parse_query(query);
+        let mut state = self.state.lock().unwrap();
+        let result = if let Some(path) = url.strip_prefix(self.config.private_endpoint()) {
+            let body = parameters.map(|p| p.to_string()).unwrap_or_default();
+            exchange::private_route(&mut state.engine, method, path, &query, &body)
+        } else if let Some(path) = url.strip_prefix(self.config.public_endpoint()) {
+            public_route(&state, method, path, &query)
+        } else {
+            Err(ApiErrorCode::InvalidPath)
+        };
+        let (http_status_code, body_text) = exchange::to_response(result);
+        RawResponse {
+            http_status_code,
+            body_text,
+        }
+    }
+}
+
+/// Public APIを処理する。
+fn public_route(
+    state: &State,
+    method: &str,
+    path: &str,
+    query: &HashMap<String, String>,
+) -> RouteResult {
+    match (method, path) {
+        ("GET", "/v1/status") => Ok(Some(json!({"status": "OPEN"}))),
+        ("GET", "/v1/ticker") => {
+            let symbol = query.get("symbol");
+            let tickers: Vec<Value> = state
+                .prices
+                .iter()
+                .filter(|(s, _)| symbol.is_none() || symbol == Some(*s))
+                .map(|(symbol, (price, high, low))| {
+                    json!({
+                        "ask": price.to_string(),
+                        "bid": price.to_string(),
+                        "high": high.to_string(),
+                        "last": price.to_string(),
+                        "low": low.to_string(),
+                        "symbol": symbol,
+                        "timestamp": timestamp(state.engine.now()),
+                        "volume": state.engine.volume(symbol).to_string(),
+                    })
+                })
+                .collect();
+            Ok(Some(json!(tickers)))
+        }
+        ("GET", "/v1/orderbooks") => {
+            let symbol = query_symbol(query)?;
+            let (price, _, _) = state
+                .prices
+                .get(symbol)
+                .ok_or(ApiErrorCode::InvalidParameter)?;
+            Ok(Some(json!({
+                "asks": [{"price": price.to_string(), "size": "1"}],
+                "bids": [{"price": price.to_string(), "size": "1"}],
+                "symbol": symbol,
+            })))
+        }
+        _ => Err(ApiErrorCode::InvalidPath),
+    }
+}
+
+#[async_trait]
+impl HttpClient for Backtest {
+    async fn get(&self, url: String, _headers: &Headers) -> Result<RawResponse, Error> {
+        Ok(self.respond("GET", &url, None))
+    }
+
+    async fn post(
+        &self,
+        url: String,
+        _headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        Ok(self.respond("POST", &url, Some(parameters)))
+    }
+
+    async fn put(
+        &self,
+        url: String,
+        _headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        Ok(self.respond("PUT", &url, Some(parameters)))
+    }
+
+    async fn delete(
+        &self,
+        url: String,
+        _headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        Ok(self.respond("DELETE", &url, Some(parameters)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::tests::fake_credentials;
+    use crate::execution_type::ExecutionType;
+    use crate::price::{price, price_from_i64};
+    use crate::private::PrivateAPI;
+    use crate::public::PublicAPI;
+    use crate::size::size;
+    use crate::time_in_force::TimeInForce;
+    use std::io::Write;
+
+    const KLINES: &str = r#"{"openTime":"1618588800000","open":"1000000","high":"1010000","low":"990000","close":"1005000","volume":"1"}
+{"openTime":"1618588860000","open":"1005000","high":"1006000","low":"900000","close":"950000","volume":"1"}
+
+{"openTime":"1618588920000","open":"950000","high":"1100000","low":"940000","close":"1100000","volume":"1"}
+"#;
+
+    const TRADES: &str = r#"{"price":"1000100","side":"SELL","size":"0.01","timestamp":"2018-03-30T12:34:56.789Z"}
+{"price":"999900","side":"BUY","size":"0.02","timestamp":"2018-03-30T12:34:55.789Z"}
+"#;
+
+    fn write_temp_file(name: &str, text: &str) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("gmo-coin-rs-{}-{}.jsonl", name, std::process::id()));
+        File::create(&path)
+            .unwrap()
+            .write_all(text.as_bytes())
+            .unwrap();
+        path
+    }
+
+    fn klines() -> Vec<Kline> {
+        let path = write_temp_file("klines", KLINES);
+        let klines = load_klines(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        klines
+    }
+
+    #[test]
+    fn test_kline_ticks() {
+        let ticks = kline_ticks("BTC_JPY", &klines());
+        let prices: Vec<Price> = ticks.iter().map(|t| t.price).collect();
+        let expected: Vec<Price> = [
+            1000000, 990000, 1010000, 1005000, 1005000, 1006000, 900000, 950000,
+        ]
+        .iter()
+        .map(|p| price_from_i64(*p))
+        .collect();
+        assert_eq!(prices[..8], expected[..]);
+        assert_eq!((ticks[1].timestamp - ticks[0].timestamp).num_seconds(), 15);
+        assert_eq!(ticks.len(), 12);
+    }
+
+    #[tokio::test]
+    async fn test_trades() {
+        let path = write_temp_file("trades", TRADES);
+        let trades = load_trades(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        let backtest = Backtest::new().with_trades(&Symbol::Btc, trades);
+        assert!(backtest.now().is_none());
+        let until = "2018-03-30T12:34:56Z".parse().unwrap();
+        assert!(backtest.advance_until(until));
+        assert_eq!(backtest.now().unwrap().timestamp(), 1522413295);
+        let public_api = PublicAPI::new(backtest);
+        let ticker = public_api.ticker(&Symbol::Btc).await.unwrap();
+        assert_eq!(ticker.last().unwrap(), price("999900"));
+        assert!(public_api.http_client.step());
+        assert!(!public_api.http_client.advance_until(until));
+        assert!(public_api.http_client.is_finished());
+    }
+
+    #[tokio::test]
+    async fn test_orders_and_report() {
+        let backtest = Backtest::new()
+            .with_klines(&Symbol::BtcJpy, klines())
+            .with_fee_rates(0.0, 0.001);
+        let private_api = PrivateAPI::new(backtest).with_credentials(fake_credentials());
+        assert!(private_api.http_client.step());
+        assert_eq!(
+            private_api.http_client.now().unwrap().timestamp_millis(),
+            1618588800000
+        );
+
+        private_api
+            .order(
+                &ExecutionType::Market,
+                &Symbol::BtcJpy,
+                &Side::Buy,
+                size("1"),
+                None,
+            )
+            .await
+            .unwrap();
+        let limit = private_api
+            .order_with_options(
+                &ExecutionType::Limit,
+                &Symbol::BtcJpy,
+                &Side::Sell,
+                size("0.1"),
+                Some(price("900000")),
+                &TimeInForce::Sok,
+                None,
+            )
+            .await
+            .unwrap();
+        let orders = private_api.orders(&[limit.order_id()]).await.unwrap();
+        assert_eq!(orders.orders()[0].cancel_type, "EXPIRED_SOK");
+
+        private_api
+            .order(
+                &ExecutionType::Stop,
+                &Symbol::BtcJpy,
+                &Side::Buy,
+                size("0.1"),
+                Some(price("1008000")),
+            )
+            .await
+            .unwrap();
+        let positions = private_api.open_positions(&Symbol::BtcJpy).await.unwrap();
+        let position_id = positions.open_positions()[0].position_id.clone();
+        private_api
+            .change_losscut_price(&position_id, price("960000"))
+            .await
+            .unwrap();
+        while private_api.http_client.step() {}
+        assert!(private_api.http_client.is_finished());
+
+        let report = private_api.http_client.report();
+        assert_eq!(report.losscuts.len(), 1);
+        assert_eq!(report.losscuts[0].price, price("900000"));
+        assert_eq!(report.losscuts[0].loss_gain, -100_000);
+        assert_eq!(report.execution_count, 3);
+        assert_eq!(report.total_fees, 1_000 + 101 + 900);
+        assert_eq!(report.realized_profit_loss, -100_000);
+        assert_eq!(report.unrealized_profit_loss, 9_000);
+        assert_eq!(report.final_equity, 1_000_000 - 100_000 - 2_001 + 9_000);
+        assert!(report.max_drawdown >= 100_000);
+        assert_eq!(report.equity_curve.len(), 12);
+
+        let public_api = PublicAPI::new(private_api.http_client);
+        let ticker = public_api.ticker(&Symbol::BtcJpy).await.unwrap();
+        assert_eq!(ticker.ask().unwrap(), price("1100000"));
+    }
+}
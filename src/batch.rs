@@ -0,0 +1,86 @@
+//! 一度に指定できるIDの件数に上限があるAPIのために、IDの配列を上限以下の塊に分けて並行に呼び出す。
+
+use crate::error::Error;
+use futures::{stream, Future, StreamExt, TryStreamExt};
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::hash::Hash;
+
+/// IDの配列に重複がないか検証する。
+///
+/// # Arguments
+///
+/// * `ids` - IDの配列。
+///
+pub(crate) fn check_duplicate_ids<I: Eq + Hash + Display>(ids: &[&I]) -> Result<(), Error> {
+    let mut seen = HashSet::new();
+    for id in ids {
+        if !seen.insert(*id) {
+            return Err(Error::DuplicateIdError(id.to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// IDの配列を`max_ids`件ずつに分け、最大`concurrency`個を並行に`call`で呼び出す。
+/// 結果は塊の順番に並べて返す。どれかの呼び出しがエラーになったらそのエラーを返す。
+/// IDの配列が空の場合も1回呼び出すので、結果は必ず1件以上になる。
+///
+/// # Arguments
+///
+/// * `ids` - IDの配列。
+/// * `max_ids` - 1回の呼び出しで指定できるIDの最大件数。
+/// * `concurrency` - 並行に呼び出す最大数。
+/// * `call` - IDの塊を受け取ってAPIを呼び出す関数。
+///
+pub(crate) async fn call_in_chunks<'a, I, R, F, Fut>(
+    ids: &'a [&'a I],
+    max_ids: usize,
+    concurrency: usize,
+    call: F,
+) -> Result<Vec<R>, Error>
+where
+    I: Eq + Hash + Display,
+    F: Fn(&'a [&'a I]) -> Fut,
+    Fut: Future<Output = Result<R, Error>>,
+{
+    check_duplicate_ids(ids)?;
+    if ids.is_empty() {
+        return Ok(vec![call(ids).await?]);
+    }
+    stream::iter(ids.chunks(max_ids))
+        .map(call)
+        .buffered(concurrency.max(1))
+        .try_collect()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::id::OrderId;
+
+    #[tokio::test]
+    async fn test_call_in_chunks() {
+        let ids: Vec<OrderId> = (1..=25).map(|i| OrderId::new(i.to_string())).collect();
+        let refs: Vec<&OrderId> = ids.iter().collect();
+        let sizes = call_in_chunks(&refs, 10, 2, |chunk| async move { Ok(chunk.len()) })
+            .await
+            .unwrap();
+        assert_eq!(sizes, vec![10, 10, 5]);
+
+        let sizes = call_in_chunks(
+            &[],
+            10,
+            2,
+            |chunk: &[&OrderId]| async move { Ok(chunk.len()) },
+        )
+        .await
+        .unwrap();
+        assert_eq!(sizes, vec![0]);
+
+        let refs = vec![&ids[0], &ids[1], &ids[0]];
+        let result = call_in_chunks(&refs, 10, 2, |chunk| async move { Ok(chunk.len()) }).await;
+        assert!(matches!(result, Err(Error::DuplicateIdError(id)) if id == "1"));
+    }
+}
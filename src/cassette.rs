@@ -0,0 +1,553 @@
+//! リクエストとレスポンスをカセットファイルに記録するHTTPクライアントと、カセットファイルから再生するHTTPクライアントを実装する。
+//! カセットファイルは1行に1つのやり取りをJSONで書いたJSON Lines形式。
+
+use crate::error::Error;
+use crate::headers::Headers;
+use crate::http_client::HttpClient;
+use crate::response::RawResponse;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// カセットファイルに書くときに値を伏せるヘッダー。
+const REDACTED_HEADERS: [&str; 2] = ["API-KEY", "API-SIGN"];
+
+/// 伏せた値。
+const REDACTED_VALUE: &str = "REDACTED";
+
+/// アクセストークンを扱うAPIのパス。リクエストとレスポンスのアクセストークンを伏せる。
+const WS_AUTH_API_PATH: &str = "/v1/ws-auth";
+
+/// 1回のリクエストとレスポンスのやり取り。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// HTTPメソッド。
+    pub method: String,
+
+    /// URLのパス。例: `/public/v1/ticker`
+    pub path: String,
+
+    /// クエリパラメーター。
+    #[serde(default)]
+    pub query: BTreeMap<String, String>,
+
+    /// リクエストヘッダー。APIキーと署名は伏せる。
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+
+    /// リクエストのボディ。GETリクエストの場合は`None`。
+    #[serde(default)]
+    pub body: Option<Value>,
+
+    /// HTTPステータスコード。
+    pub status: u16,
+
+    /// レスポンスのボディ。
+    pub response: String,
+}
+
+impl Interaction {
+    /// リクエストの内容からやり取りを作る。レスポンスは空にしておく。
+    fn from_request(
+        method: &str,
+        url: &str,
+        headers: &Headers,
+        body: Option<&Value>,
+    ) -> Result<Interaction, Error> {
+        let url = reqwest::Url::parse(url)?;
+        let headers = headers
+            .into_iter()
+            .map(|(key, value)| {
+                if REDACTED_HEADERS.iter().any(|h| h.eq_ignore_ascii_case(key)) {
+                    (key.clone(), REDACTED_VALUE.to_string())
+                } else {
+                    (key.clone(), value.clone())
+                }
+            })
+            .collect();
+        let path = url.path().to_string();
+        let mut body = body.cloned();
+        if path.ends_with(WS_AUTH_API_PATH) {
+            if let Some(token) = body.as_mut().and_then(|b| b.get_mut("token")) {
+                *token = Value::String(REDACTED_VALUE.to_string());
+            }
+        }
+        Ok(Interaction {
+            method: method.to_string(),
+            path,
+            query: url.query_pairs().into_owned().collect(),
+            headers,
+            body,
+            status: 0,
+            response: String::new(),
+        })
+    }
+
+    /// レスポンスを記録する。アクセストークン取得APIのレスポンスの場合はアクセストークンを伏せる。
+    fn set_response(&mut self, response: &RawResponse) {
+        self.status = response.http_status_code;
+        self.response = response.body_text.clone();
+        if !self.path.ends_with(WS_AUTH_API_PATH) {
+            return;
+        }
+        if let Ok(mut value) = serde_json::from_str::<Value>(&response.body_text) {
+            if let Some(token) = value.get_mut("data").filter(|d| d.is_string()) {
+                *token = Value::String(REDACTED_VALUE.to_string());
+                self.response = value.to_string();
+            }
+        }
+    }
+
+    /// メソッド、パス、クエリパラメーター、ボディが一致するか？ ヘッダーはタイムスタンプや署名が毎回変わるので比べない。
+    fn matches(&self, other: &Interaction) -> bool {
+        self.method == other.method
+            && self.path == other.path
+            && self.query == other.query
+            && self.body == other.body
+    }
+}
+
+/// リクエストとレスポンスをカセットファイルに追記するHTTPクライアント。任意の`HttpClient`を包んで使う。
+/// 記録に失敗してもレスポンスはそのまま返す。失敗は`take_record_errors`で取得できる。
+pub struct RecordingClient<T: HttpClient + std::marker::Sync + std::marker::Send> {
+    inner: T,
+    file: Mutex<File>,
+    record_errors: Mutex<Vec<Error>>,
+}
+
+impl<T: HttpClient + std::marker::Sync + std::marker::Send> RecordingClient<T> {
+    /// 記録するHTTPクライアントを作成する。カセットファイルが既にある場合は追記する。
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - 実際にリクエストを送るHTTPクライアント。
+    /// * `path` - カセットファイルのパス。
+    ///
+    pub fn new(inner: T, path: impl AsRef<Path>) -> Result<RecordingClient<T>, Error> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(Error::CassetteFileError)?;
+        Ok(RecordingClient {
+            inner,
+            file: Mutex::new(file),
+            record_errors: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// 記録に失敗したときのエラーを取り出す。取り出したエラーは消える。
+    /// 注文APIなどは記録に失敗しても注文が通っているので、記録の失敗でレスポンスを捨てないようにエラーを別に保持している。
+    pub fn take_record_errors(&self) -> Vec<Error> {
+        std::mem::take(&mut *self.record_errors.lock().unwrap())
+    }
+
+    /// やり取りをカセットファイルに1行追記する。失敗した場合はエラーを保持する。
+    fn record(&self, interaction: Result<Interaction, Error>, result: &Result<RawResponse, Error>) {
+        let response = match result {
+            Ok(r) => r,
+            Err(_) => return,
+        };
+        if let Err(e) = self.write(interaction, response) {
+            self.record_errors.lock().unwrap().push(e);
+        }
+    }
+
+    fn write(
+        &self,
+        interaction: Result<Interaction, Error>,
+        response: &RawResponse,
+    ) -> Result<(), Error> {
+        let mut interaction = interaction?;
+        interaction.set_response(response);
+        let line = serde_json::to_string(&interaction)?;
+        let mut file = self.file.lock().unwrap();
+        writeln!(file, "{}", line).map_err(Error::CassetteFileError)
+    }
+}
+
+#[async_trait]
+impl<T: HttpClient + std::marker::Sync + std::marker::Send> HttpClient for RecordingClient<T> {
+    async fn get(&self, url: String, headers: &Headers) -> Result<RawResponse, Error> {
+        let interaction = Interaction::from_request("GET", &url, headers, None);
+        let result = self.inner.get(url, headers).await;
+        self.record(interaction, &result);
+        result
+    }
+
+    async fn post(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        let interaction = Interaction::from_request("POST", &url, headers, Some(parameters));
+        let result = self.inner.post(url, headers, parameters).await;
+        self.record(interaction, &result);
+        result
+    }
+
+    async fn put(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        let interaction = Interaction::from_request("PUT", &url, headers, Some(parameters));
+        let result = self.inner.put(url, headers, parameters).await;
+        self.record(interaction, &result);
+        result
+    }
+
+    async fn delete(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        let interaction = Interaction::from_request("DELETE", &url, headers, Some(parameters));
+        let result = self.inner.delete(url, headers, parameters).await;
+        self.record(interaction, &result);
+        result
+    }
+}
+
+/// カセットファイルに記録したレスポンスを返すHTTPクライアント。ネットワークにはアクセスしない。
+/// メソッド、パス、クエリパラメーター、ボディが一致するやり取りを記録した順に返す。
+/// 同じリクエストを記録した回数より多く送った場合は、最後に記録したレスポンスを返し続ける。
+pub struct ReplayClient {
+    interactions: Vec<Interaction>,
+    used: Mutex<Vec<bool>>,
+}
+
+impl ReplayClient {
+    /// 再生するやり取りを指定して作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `interactions` - 再生するやり取り。
+    ///
+    pub fn new(interactions: Vec<Interaction>) -> ReplayClient {
+        let used = vec![false; interactions.len()];
+        ReplayClient {
+            interactions,
+            used: Mutex::new(used),
+        }
+    }
+
+    /// カセットファイルを読み込んで作成する。空行は読み飛ばす。
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - カセットファイルのパス。
+    ///
+    pub fn from_file(path: impl AsRef<Path>) -> Result<ReplayClient, Error> {
+        let file = File::open(path).map_err(Error::CassetteFileError)?;
+        let mut interactions = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(Error::CassetteFileError)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            interactions.push(serde_json::from_str::<Interaction>(&line)?);
+        }
+        Ok(ReplayClient::new(interactions))
+    }
+
+    /// リクエストに一致するやり取りのレスポンスを返す。
+    fn replay(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &Headers,
+        body: Option<&Value>,
+    ) -> Result<RawResponse, Error> {
+        let request = Interaction::from_request(method, url, headers, body)?;
+        let mut used = self.used.lock().unwrap();
+        let mut last_match = None;
+        for (i, interaction) in self.interactions.iter().enumerate() {
+            if !interaction.matches(&request) {
+                continue;
+            }
+            if !used[i] {
+                used[i] = true;
+                return Ok(to_raw_response(interaction));
+            }
+            last_match = Some(interaction);
+        }
+        match last_match {
+            Some(interaction) => Ok(to_raw_response(interaction)),
+            None => Err(Error::CassetteMismatchError(format!("{} {}", method, url))),
+        }
+    }
+}
+
+fn to_raw_response(interaction: &Interaction) -> RawResponse {
+    RawResponse {
+        http_status_code: interaction.status,
+        body_text: interaction.response.clone(),
+    }
+}
+
+#[async_trait]
+impl HttpClient for ReplayClient {
+    async fn get(&self, url: String, headers: &Headers) -> Result<RawResponse, Error> {
+        self.replay("GET", &url, headers, None)
+    }
+
+    async fn post(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        self.replay("POST", &url, headers, Some(parameters))
+    }
+
+    async fn put(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        self.replay("PUT", &url, headers, Some(parameters))
+    }
+
+    async fn delete(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        self.replay("DELETE", &url, headers, Some(parameters))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client_config::ClientConfig;
+    use crate::credentials::tests::fake_credentials;
+    use crate::http_client::tests::InmemClient;
+    use crate::id::OrderId;
+    use crate::price::price;
+    use crate::private::PrivateAPI;
+    use crate::public::PublicAPI;
+    use crate::symbol::Symbol;
+
+    const TICKER_RESPONSE_SAMPLE: &str = r#"{"status":0,"data":[{"ask":"750760","bid":"750600","high":"762302","last":"756662","low":"704874","symbol":"BTC","timestamp":"2018-03-30T12:34:56.789Z","volume":"194785.8484"}],"responsetime":"2019-03-19T02:15:06.014Z"}"#;
+
+    const CANCEL_ORDER_RESPONSE_SAMPLE: &str =
+        r#"{"status":0,"responsetime":"2019-03-19T01:07:24.557Z"}"#;
+
+    fn cassette_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "gmo-coin-rs-cassette-{}-{}.jsonl",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let path = cassette_path("record");
+        let _ = std::fs::remove_file(&path);
+
+        let public_api = PublicAPI::new(
+            RecordingClient::new(
+                InmemClient {
+                    http_status_code: 200,
+                    body_text: TICKER_RESPONSE_SAMPLE.to_string(),
+                    return_error: false,
+                },
+                &path,
+            )
+            .unwrap(),
+        );
+        public_api.ticker(&Symbol::Btc).await.unwrap();
+
+        let private_api = PrivateAPI::new(
+            RecordingClient::new(
+                InmemClient {
+                    http_status_code: 200,
+                    body_text: CANCEL_ORDER_RESPONSE_SAMPLE.to_string(),
+                    return_error: false,
+                },
+                &path,
+            )
+            .unwrap(),
+        )
+        .with_credentials(fake_credentials());
+        private_api
+            .cancel_order(&OrderId::new("200"))
+            .await
+            .unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let interactions: Vec<Interaction> = text
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(interactions.len(), 2);
+        assert_eq!(interactions[0].method, "GET");
+        assert_eq!(interactions[0].path, "/public/v1/ticker");
+        assert_eq!(interactions[0].query["symbol"], "BTC");
+        assert_eq!(interactions[1].method, "POST");
+        assert_eq!(interactions[1].path, "/private/v1/cancelOrder");
+        assert_eq!(interactions[1].headers["API-SIGN"], REDACTED_VALUE);
+        assert_eq!(interactions[1].headers["API-KEY"], REDACTED_VALUE);
+
+        let replay = ReplayClient::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let public_api = PublicAPI::new(replay);
+        let resp = public_api.ticker(&Symbol::Btc).await.unwrap();
+        assert_eq!(resp.ask().unwrap(), price("750760"));
+        let resp = public_api.ticker(&Symbol::Eth).await;
+        assert!(matches!(resp, Err(Error::CassetteMismatchError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_replay_matches_normalized_parameters() {
+        let interactions = vec![
+            Interaction {
+                method: "POST".to_string(),
+                path: "/private/v1/cancelOrders".to_string(),
+                query: BTreeMap::new(),
+                headers: BTreeMap::new(),
+                body: Some(serde_json::json!({"orderIds": [1, 2]})),
+                status: 200,
+                response: "first".to_string(),
+            },
+            Interaction {
+                method: "GET".to_string(),
+                path: "/private/v1/activeOrders".to_string(),
+                query: vec![
+                    ("symbol".to_string(), "BTC".to_string()),
+                    ("page".to_string(), "1".to_string()),
+                ]
+                .into_iter()
+                .collect(),
+                headers: BTreeMap::new(),
+                body: None,
+                status: 200,
+                response: "second".to_string(),
+            },
+        ];
+        let client = ReplayClient::new(interactions);
+        let headers = Headers::create_empty_headers();
+        let config = ClientConfig::default();
+        let resp = client
+            .get(
+                format!(
+                    "{}/v1/activeOrders?page=1&symbol=BTC",
+                    config.private_endpoint()
+                ),
+                &headers,
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.body_text, "second");
+
+        let resp = client
+            .post(
+                format!("{}/v1/cancelOrders", config.private_endpoint()),
+                &headers,
+                &serde_json::json!({"orderIds": [1, 2]}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.body_text, "first");
+
+        let resp = client
+            .post(
+                format!("{}/v1/cancelOrders", config.private_endpoint()),
+                &headers,
+                &serde_json::json!({"orderIds": [1, 3]}),
+            )
+            .await;
+        assert!(matches!(resp, Err(Error::CassetteMismatchError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_redact_ws_auth_token() {
+        let path = cassette_path("ws-auth");
+        let _ = std::fs::remove_file(&path);
+        let client = RecordingClient::new(
+            InmemClient {
+                http_status_code: 200,
+                body_text: r#"{"status":0,"data":"secret-token","responsetime":"2019-03-19T02:15:06.102Z"}"#
+                    .to_string(),
+                return_error: false,
+            },
+            &path,
+        )
+        .unwrap();
+        let url = format!("{}/v1/ws-auth", ClientConfig::default().private_endpoint());
+        let headers = Headers::create_empty_headers();
+        let resp = client
+            .post(url.clone(), &headers, &serde_json::json!({}))
+            .await
+            .unwrap();
+        assert!(resp.body_text.contains("secret-token"));
+        client
+            .put(
+                url.clone(),
+                &headers,
+                &serde_json::json!({"token": "secret-token"}),
+            )
+            .await
+            .unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(!text.contains("secret-token"));
+        assert!(client.take_record_errors().is_empty());
+
+        // 伏せたアクセストークンでも再生できる。
+        let replay = ReplayClient::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let resp = replay
+            .put(
+                url,
+                &headers,
+                &serde_json::json!({"token": "another-token"}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.http_status_code, 200);
+    }
+
+    #[tokio::test]
+    async fn test_record_error_keeps_response() {
+        let path = cassette_path("read-only");
+        std::fs::write(&path, "").unwrap();
+        // 読み取り専用で開いたファイルには書き込めない。
+        let client = RecordingClient {
+            inner: InmemClient {
+                http_status_code: 200,
+                body_text: CANCEL_ORDER_RESPONSE_SAMPLE.to_string(),
+                return_error: false,
+            },
+            file: Mutex::new(File::open(&path).unwrap()),
+            record_errors: Mutex::new(Vec::new()),
+        };
+        let resp = client
+            .post(
+                format!("{}/v1/order", ClientConfig::default().private_endpoint()),
+                &Headers::create_empty_headers(),
+                &serde_json::json!({}),
+            )
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(resp.body_text, CANCEL_ORDER_RESPONSE_SAMPLE);
+        let errors = client.take_record_errors();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0], Error::CassetteFileError(_)));
+        assert!(client.take_record_errors().is_empty());
+    }
+}
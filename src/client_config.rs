@@ -0,0 +1,213 @@
+//! 接続先のURLやタイムアウトなど、クライアントの設定を実装する。
+
+use crate::end_point::*;
+use std::time::Duration;
+
+/// `PublicAPI`, `PrivateAPI`, WebSocket APIの接続先とタイムアウトの設定。
+/// デフォルトではGMOコインのAPIに接続し、タイムアウトしない。
+#[derive(Clone, Debug)]
+pub struct ClientConfig {
+    /// Public APIのベースURL。
+    public_endpoint: String,
+
+    /// Private APIのベースURL。
+    private_endpoint: String,
+
+    /// Public WebSocket APIのベースURL。
+    public_web_socket_endpoint: String,
+
+    /// Private WebSocket APIのベースURL。
+    private_web_socket_endpoint: String,
+
+    /// REST APIのリクエストのタイムアウト。`None`の場合はタイムアウトしない。
+    timeout: Option<Duration>,
+
+    /// レスポンスのうち構造体が知らないフィールドを`RestResponse::unknown_fields`に詰めるか？
+    collect_unknown_fields: bool,
+
+    /// IDの件数に上限があるAPIで、IDを分けて並行に呼び出すときの最大並行数。
+    batch_concurrency: usize,
+}
+
+/// IDを分けて並行に呼び出すときの最大並行数のデフォルト値。
+const DEFAULT_BATCH_CONCURRENCY: usize = 2;
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        ClientConfig {
+            public_endpoint: PUBLIC_ENDPOINT.to_string(),
+            private_endpoint: PRIVATE_ENDPOINT.to_string(),
+            public_web_socket_endpoint: PUBLIC_WEB_SOCKET_ENDPOINT.to_string(),
+            private_web_socket_endpoint: PRIVATE_WEB_SOCKET_ENDPOINT.to_string(),
+            timeout: None,
+            collect_unknown_fields: false,
+            batch_concurrency: DEFAULT_BATCH_CONCURRENCY,
+        }
+    }
+}
+
+impl ClientConfig {
+    /// Public APIのベースURLを指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - ベースURL。例: `http://localhost:8080/public`
+    ///
+    pub fn with_public_endpoint(mut self, url: impl Into<String>) -> ClientConfig {
+        self.public_endpoint = url.into();
+        self
+    }
+
+    /// Private APIのベースURLを指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - ベースURL。例: `http://localhost:8080/private`
+    ///
+    pub fn with_private_endpoint(mut self, url: impl Into<String>) -> ClientConfig {
+        self.private_endpoint = url.into();
+        self
+    }
+
+    /// Public WebSocket APIのベースURLを指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - ベースURL。例: `ws://localhost:8080/ws/public`
+    ///
+    pub fn with_public_web_socket_endpoint(mut self, url: impl Into<String>) -> ClientConfig {
+        self.public_web_socket_endpoint = url.into();
+        self
+    }
+
+    /// Private WebSocket APIのベースURLを指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - ベースURL。例: `ws://localhost:8080/ws/private`
+    ///
+    pub fn with_private_web_socket_endpoint(mut self, url: impl Into<String>) -> ClientConfig {
+        self.private_web_socket_endpoint = url.into();
+        self
+    }
+
+    /// REST APIのリクエストのタイムアウトを指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - レスポンスが返ってくるまで待つ時間。
+    ///
+    pub fn with_timeout(mut self, timeout: Duration) -> ClientConfig {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// レスポンスのうち構造体が知らないフィールドを`RestResponse::unknown_fields`に詰めるかを指定する。
+    /// GMOコインがレスポンスにフィールドを追加したことに気づくために使う。
+    /// 知らないフィールドは指定しなくても無視されるので、バインディングの成否は変わらない。
+    /// 構造体が必要とするフィールドがない場合や型が違う場合は、指定しても`Error::ResponseParseError`を返す。
+    ///
+    /// # Arguments
+    ///
+    /// * `collect_unknown_fields` - 詰める場合は`true`。
+    ///
+    pub fn with_collect_unknown_fields(mut self, collect_unknown_fields: bool) -> ClientConfig {
+        self.collect_unknown_fields = collect_unknown_fields;
+        self
+    }
+
+    /// 注文情報取得APIや複数の注文キャンセルAPIに上限を超える件数のIDを渡したときに、
+    /// IDを分けて並行に呼び出す最大数を指定する。1未満の値は1とみなす。
+    ///
+    /// # Arguments
+    ///
+    /// * `batch_concurrency` - 最大並行数。
+    ///
+    pub fn with_batch_concurrency(mut self, batch_concurrency: usize) -> ClientConfig {
+        self.batch_concurrency = batch_concurrency.max(1);
+        self
+    }
+
+    /// Public APIのベースURLを取得する。
+    pub fn public_endpoint(&self) -> &str {
+        &self.public_endpoint
+    }
+
+    /// Private APIのベースURLを取得する。
+    pub fn private_endpoint(&self) -> &str {
+        &self.private_endpoint
+    }
+
+    /// Public WebSocket APIのベースURLを取得する。
+    pub fn public_web_socket_endpoint(&self) -> &str {
+        &self.public_web_socket_endpoint
+    }
+
+    /// Private WebSocket APIのベースURLを取得する。
+    pub fn private_web_socket_endpoint(&self) -> &str {
+        &self.private_web_socket_endpoint
+    }
+
+    /// REST APIのリクエストのタイムアウトを取得する。
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// レスポンスのうち構造体が知らないフィールドを`RestResponse::unknown_fields`に詰めるか？
+    pub fn collect_unknown_fields(&self) -> bool {
+        self.collect_unknown_fields
+    }
+
+    /// IDを分けて並行に呼び出すときの最大並行数を取得する。
+    pub fn batch_concurrency(&self) -> usize {
+        self.batch_concurrency
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default() {
+        let config = ClientConfig::default();
+        assert_eq!(config.public_endpoint(), "https://api.coin.z.com/public");
+        assert_eq!(config.private_endpoint(), "https://api.coin.z.com/private");
+        assert_eq!(
+            config.public_web_socket_endpoint(),
+            "wss://api.coin.z.com/ws/public"
+        );
+        assert_eq!(
+            config.private_web_socket_endpoint(),
+            "wss://api.coin.z.com/ws/private"
+        );
+        assert_eq!(config.timeout(), None);
+        assert!(!config.collect_unknown_fields());
+        assert_eq!(config.batch_concurrency(), 2);
+    }
+
+    #[test]
+    fn test_override() {
+        let config = ClientConfig::default()
+            .with_public_endpoint("http://localhost:8080/public")
+            .with_private_endpoint("http://localhost:8080/private")
+            .with_public_web_socket_endpoint("ws://localhost:8080/ws/public")
+            .with_private_web_socket_endpoint("ws://localhost:8080/ws/private")
+            .with_timeout(Duration::from_secs(5))
+            .with_collect_unknown_fields(true)
+            .with_batch_concurrency(0);
+        assert_eq!(config.public_endpoint(), "http://localhost:8080/public");
+        assert_eq!(config.private_endpoint(), "http://localhost:8080/private");
+        assert_eq!(
+            config.public_web_socket_endpoint(),
+            "ws://localhost:8080/ws/public"
+        );
+        assert_eq!(
+            config.private_web_socket_endpoint(),
+            "ws://localhost:8080/ws/private"
+        );
+        assert_eq!(config.timeout(), Some(Duration::from_secs(5)));
+        assert!(config.collect_unknown_fields());
+        assert_eq!(config.batch_concurrency(), 1);
+    }
+}
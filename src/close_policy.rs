@@ -0,0 +1,277 @@
+//! 一部決済で決済する建玉を選ぶ方針を定義する。
+//! `PrivateAPI::close_order_by_policy`に渡すと、建玉一覧から方針に従って建玉を選んで決済注文を出す。
+
+use crate::dto::Position;
+use crate::error::Error;
+use crate::id::PositionId;
+use crate::price::price_to_f64;
+use crate::side::Side;
+use crate::size::{normalize_size, size_exceeds, Size};
+use std::cmp::Ordering;
+
+/// 決済する建玉を選ぶ方針。`compare`で先に決済する建玉が前に来るように並べる。
+pub trait ClosePolicy: Send + Sync {
+    /// 2つの建玉を比べる。`a`を先に決済する場合は`Ordering::Less`を返す。
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - 建玉。
+    /// * `b` - 建玉。
+    ///
+    fn compare(&self, a: &Position, b: &Position) -> Ordering;
+}
+
+/// ライブラリが用意している方針。
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StandardClosePolicy {
+    /// 古い建玉から決済する。
+    Fifo,
+
+    /// 新しい建玉から決済する。
+    Lifo,
+
+    /// 評価損の大きい建玉から決済する。
+    LargestLossFirst,
+
+    /// ロスカットレートが現在のレートに近い建玉から決済する。
+    /// 同じ銘柄、同じ売買区分の建玉であれば、買いはロスカットレートが高いほど、売りは低いほど近い。
+    NearestLosscutFirst,
+
+    /// 評価損益の大きい建玉から決済する。
+    HighestLossGainFirst,
+}
+
+/// ロスカットレートの低い順に比べる。
+fn compare_losscut_price(a: &Position, b: &Position) -> Ordering {
+    price_to_f64(a.losscut_price)
+        .partial_cmp(&price_to_f64(b.losscut_price))
+        .unwrap_or(Ordering::Equal)
+}
+
+impl ClosePolicy for StandardClosePolicy {
+    fn compare(&self, a: &Position, b: &Position) -> Ordering {
+        match self {
+            StandardClosePolicy::Fifo => a.timestamp.cmp(&b.timestamp),
+            StandardClosePolicy::Lifo => b.timestamp.cmp(&a.timestamp),
+            StandardClosePolicy::LargestLossFirst => a.loss_gain.cmp(&b.loss_gain),
+            StandardClosePolicy::NearestLosscutFirst => match a.side.known() {
+                Some(Side::Sell) => compare_losscut_price(a, b),
+                _ => compare_losscut_price(b, a),
+            },
+            StandardClosePolicy::HighestLossGainFirst => b.loss_gain.cmp(&a.loss_gain),
+        }
+    }
+}
+
+/// 建玉一覧から方針に従って決済する建玉と決済数量を選ぶ。
+/// 決済注文の売買区分と反対の建玉だけを対象にし、発注中数量を除いた数量だけを決済する。
+///
+/// # Arguments
+///
+/// * `positions` - 建玉一覧。
+/// * `symbol` - 銘柄名。
+/// * `side` - 決済注文の売買区分。
+/// * `size` - 決済する合計数量。
+/// * `policy` - 建玉を選ぶ方針。
+///
+pub fn select_positions<'a>(
+    positions: &'a [Position],
+    symbol: &str,
+    side: &Side,
+    size: Size,
+    policy: &dyn ClosePolicy,
+) -> Result<Vec<(&'a PositionId, Size)>, Error> {
+    let mut candidates: Vec<&Position> = positions
+        .iter()
+        .filter(|p| p.symbol == symbol)
+        .filter(|p| matches!(p.side.known(), Some(s) if s != *side))
+        .filter(|p| size_exceeds(p.size - p.orderd_size, Size::default()))
+        .collect();
+    candidates.sort_by(|a, b| policy.compare(a, b));
+
+    let mut remaining = size;
+    let mut selected = Vec::new();
+    for position in candidates {
+        if !size_exceeds(remaining, Size::default()) {
+            break;
+        }
+        let closable = position.size - position.orderd_size;
+        let close_size = if size_exceeds(remaining, closable) {
+            closable
+        } else {
+            remaining
+        };
+        selected.push((&position.position_id, normalize_size(close_size)));
+        remaining -= close_size;
+    }
+    if size_exceeds(remaining, Size::default()) {
+        return Err(Error::InsufficientPositionSizeError(
+            size,
+            normalize_size(size - remaining),
+        ));
+    }
+    Ok(selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::Credentials;
+    use crate::execution_type::ExecutionType;
+    use crate::mock::tests::open_positions_mock;
+    use crate::private::PrivateAPI;
+    use crate::size::size;
+    use crate::symbol::Symbol;
+
+    const POSITIONS: &str = r#"[
+        {"positionId":1,"symbol":"BTC_JPY","side":"BUY","size":"0.3","orderdSize":"0.1","price":"900000","lossGain":"-3000","leverage":"4","losscutPrice":"700000","timestamp":"2019-03-19T01:00:00.000Z"},
+        {"positionId":2,"symbol":"BTC_JPY","side":"BUY","size":"0.2","orderdSize":"0","price":"880000","lossGain":"1000","leverage":"4","losscutPrice":"750000","timestamp":"2019-03-19T02:00:00.000Z"},
+        {"positionId":3,"symbol":"BTC_JPY","side":"BUY","size":"0.1","orderdSize":"0","price":"870000","lossGain":"-5000","leverage":"4","losscutPrice":"720000","timestamp":"2019-03-19T03:00:00.000Z"},
+        {"positionId":4,"symbol":"BTC_JPY","side":"SELL","size":"0.5","orderdSize":"0","price":"870000","lossGain":"2000","leverage":"4","losscutPrice":"990000","timestamp":"2019-03-19T00:00:00.000Z"},
+        {"positionId":5,"symbol":"BTC_JPY","side":"BUY","size":"0.1","orderdSize":"0.1","price":"870000","lossGain":"0","leverage":"4","losscutPrice":"760000","timestamp":"2019-03-18T00:00:00.000Z"}
+    ]"#;
+
+    fn positions() -> Vec<Position> {
+        serde_json::from_str(POSITIONS).unwrap()
+    }
+
+    fn select(policy: StandardClosePolicy, close_size: &str) -> Result<Vec<(String, Size)>, Error> {
+        let positions = positions();
+        let selected = select_positions(
+            &positions,
+            "BTC_JPY",
+            &Side::Sell,
+            size(close_size),
+            &policy,
+        )?;
+        Ok(selected
+            .into_iter()
+            .map(|(id, s)| (id.to_string(), s))
+            .collect())
+    }
+
+    #[test]
+    fn test_select_positions() {
+        let ids = |policy| -> Vec<String> {
+            select(policy, "0.35")
+                .unwrap()
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect()
+        };
+        assert_eq!(ids(StandardClosePolicy::Fifo), vec!["1", "2"]);
+        assert_eq!(ids(StandardClosePolicy::Lifo), vec!["3", "2", "1"]);
+        assert_eq!(
+            ids(StandardClosePolicy::LargestLossFirst),
+            vec!["3", "1", "2"]
+        );
+        assert_eq!(
+            ids(StandardClosePolicy::NearestLosscutFirst),
+            vec!["2", "3", "1"]
+        );
+        assert_eq!(
+            ids(StandardClosePolicy::HighestLossGainFirst),
+            vec!["2", "1"]
+        );
+
+        let selected = select(StandardClosePolicy::Fifo, "0.35").unwrap();
+        assert_eq!(selected[0].1, size("0.2"));
+        assert_eq!(selected[1].1, size("0.15"));
+
+        assert!(matches!(
+            select(StandardClosePolicy::Fifo, "0.6"),
+            Err(Error::InsufficientPositionSizeError(_, available)) if available == size("0.5")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_close_order_by_policy() {
+        let mock = open_positions_mock(POSITIONS).on(
+            "POST",
+            "/v1/closeOrder",
+            200,
+            r#"{"status":0,"data":"637000","responsetime":"2019-03-19T01:07:24.557Z"}"#,
+        );
+        let private_api = PrivateAPI::new(mock).with_credentials(Credentials::new("key", "secret"));
+        let response = private_api
+            .close_order_by_policy(
+                &ExecutionType::Market,
+                &Symbol::BtcJpy,
+                &Side::Sell,
+                size("0.25"),
+                None,
+                &StandardClosePolicy::LargestLossFirst,
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.len(), 1);
+        assert_eq!(response[0].order_id(), "637000");
+        // 取得した建玉一覧をそのまま検証に使うので、建玉一覧は1回(空のページまでの2ページ)しか取得しない。
+        assert_eq!(
+            private_api
+                .http_client
+                .calls_to("GET", "/v1/openPositions")
+                .len(),
+            2
+        );
+
+        let calls = private_api.http_client.calls_to("POST", "/v1/closeOrder");
+        assert_eq!(calls.len(), 1);
+        let settle_position = &calls[0].body.as_ref().unwrap()["settlePosition"];
+        assert_eq!(settle_position[0]["positionId"], 3);
+        assert_eq!(settle_position[0]["size"], "0.1");
+        assert_eq!(settle_position[1]["positionId"], 1);
+        assert_eq!(settle_position[1]["size"], "0.15");
+    }
+
+    #[tokio::test]
+    async fn test_close_order_by_policy_in_chunks() {
+        let list = (1..=12)
+            .map(|i| {
+                format!(
+                    r#"{{"positionId":{},"symbol":"BTC_JPY","side":"BUY","size":"0.1","orderdSize":"0","price":"900000","lossGain":"0","leverage":"4","losscutPrice":"700000","timestamp":"2019-03-19T{:02}:00:00.000Z"}}"#,
+                    i, i
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        let list: &'static str = Box::leak(format!("[{}]", list).into_boxed_str());
+        let mock = open_positions_mock(list).on(
+            "POST",
+            "/v1/closeOrder",
+            200,
+            r#"{"status":0,"data":"637000","responsetime":"2019-03-19T01:07:24.557Z"}"#,
+        );
+        let private_api = PrivateAPI::new(mock).with_credentials(Credentials::new("key", "secret"));
+        let responses = private_api
+            .close_order_by_policy(
+                &ExecutionType::Market,
+                &Symbol::BtcJpy,
+                &Side::Sell,
+                size("1.15"),
+                None,
+                &StandardClosePolicy::Fifo,
+            )
+            .await
+            .unwrap();
+        assert_eq!(responses.len(), 2);
+
+        let calls = private_api.http_client.calls_to("POST", "/v1/closeOrder");
+        assert_eq!(calls.len(), 2);
+        let first = calls[0].body.as_ref().unwrap()["settlePosition"]
+            .as_array()
+            .unwrap()
+            .clone();
+        let second = calls[1].body.as_ref().unwrap()["settlePosition"]
+            .as_array()
+            .unwrap()
+            .clone();
+        assert_eq!(first.len(), 10);
+        assert_eq!(first[0]["positionId"], 1);
+        assert_eq!(first[9]["positionId"], 10);
+        assert_eq!(second.len(), 2);
+        assert_eq!(second[0]["positionId"], 11);
+        assert_eq!(second[1]["positionId"], 12);
+        assert_eq!(second[1]["size"], "0.05");
+    }
+}
@@ -0,0 +1,258 @@
+//! Private APIの認証に使うAPIキー, APIシークレットと、それらを提供する仕組みを実装する。
+
+use crate::error::Error;
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+/// APIキーを読み取る環境変数名のデフォルト値。
+pub const DEFAULT_API_KEY_ENVNAME: &str = "GMO_COIN_API_KEY";
+
+/// APIシークレットを読み取る環境変数名のデフォルト値。
+pub const DEFAULT_API_SECRET_ENVNAME: &str = "GMO_COIN_API_SECRET";
+
+/// APIキーとAPIシークレットを格納する構造体。
+/// APIシークレットはドロップ時にメモリ上から消去され、`Debug`出力にも含まれない。
+#[derive(Clone, Deserialize)]
+pub struct Credentials {
+    /// APIキー。
+    api_key: String,
+
+    /// APIシークレット。
+    api_secret: Zeroizing<String>,
+}
+
+impl Credentials {
+    /// APIキーとAPIシークレットから作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `api_key` - APIキー。
+    /// * `api_secret` - APIシークレット。
+    ///
+    pub fn new(api_key: impl Into<String>, api_secret: impl Into<String>) -> Credentials {
+        Credentials {
+            api_key: api_key.into(),
+            api_secret: Zeroizing::new(api_secret.into()),
+        }
+    }
+
+    /// APIキーを取得する。
+    pub fn api_key(&self) -> &str {
+        &self.api_key
+    }
+
+    /// APIシークレットを取得する。
+    pub(crate) fn api_secret(&self) -> &str {
+        &self.api_secret
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("api_key", &self.api_key)
+            .field("api_secret", &"***")
+            .finish()
+    }
+}
+
+/// Private APIを呼び出すたびに`Credentials`を提供するトレイト。
+pub trait CredentialsProvider: Send + Sync {
+    /// `Credentials`を取得する。
+    fn credentials(&self) -> Result<Credentials, Error>;
+}
+
+/// 環境変数から`Credentials`を読み取る。
+pub struct EnvCredentialsProvider {
+    api_key_envname: String,
+    api_secret_envname: String,
+}
+
+impl EnvCredentialsProvider {
+    /// 環境変数`GMO_COIN_API_KEY`, `GMO_COIN_API_SECRET`から読み取るように作成する。
+    pub fn new() -> EnvCredentialsProvider {
+        EnvCredentialsProvider::with_names(DEFAULT_API_KEY_ENVNAME, DEFAULT_API_SECRET_ENVNAME)
+    }
+
+    /// 読み取る環境変数名を指定して作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `api_key_envname` - APIキーを読み取る環境変数名。
+    /// * `api_secret_envname` - APIシークレットを読み取る環境変数名。
+    ///
+    pub fn with_names(api_key_envname: &str, api_secret_envname: &str) -> EnvCredentialsProvider {
+        EnvCredentialsProvider {
+            api_key_envname: api_key_envname.to_string(),
+            api_secret_envname: api_secret_envname.to_string(),
+        }
+    }
+}
+
+impl Default for EnvCredentialsProvider {
+    fn default() -> Self {
+        EnvCredentialsProvider::new()
+    }
+}
+
+impl CredentialsProvider for EnvCredentialsProvider {
+    fn credentials(&self) -> Result<Credentials, Error> {
+        let api_key = std::env::var(&self.api_key_envname)?;
+        let api_secret = Zeroizing::new(std::env::var(&self.api_secret_envname)?);
+        Ok(Credentials {
+            api_key,
+            api_secret,
+        })
+    }
+}
+
+/// 作成時に渡した`Credentials`をそのまま提供する。
+pub struct StaticCredentialsProvider {
+    credentials: Credentials,
+}
+
+impl StaticCredentialsProvider {
+    /// 提供する`Credentials`を指定して作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `credentials` - 提供する`Credentials`。
+    ///
+    pub fn new(credentials: Credentials) -> StaticCredentialsProvider {
+        StaticCredentialsProvider { credentials }
+    }
+}
+
+impl CredentialsProvider for StaticCredentialsProvider {
+    fn credentials(&self) -> Result<Credentials, Error> {
+        Ok(self.credentials.clone())
+    }
+}
+
+/// JSONファイルから`Credentials`を読み取る。
+/// ファイルは`{"api_key": "...", "api_secret": "..."}`の形式で書く。呼び出すたびに読み直すので、ファイルを書き換えればキーを差し替えられる。
+pub struct FileCredentialsProvider {
+    path: PathBuf,
+}
+
+impl FileCredentialsProvider {
+    /// 読み取るファイルのパスを指定して作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - JSONファイルのパス。
+    ///
+    pub fn new(path: impl AsRef<Path>) -> FileCredentialsProvider {
+        FileCredentialsProvider {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl CredentialsProvider for FileCredentialsProvider {
+    fn credentials(&self) -> Result<Credentials, Error> {
+        let text = Zeroizing::new(
+            std::fs::read_to_string(&self.path).map_err(Error::CredentialsFileError)?,
+        );
+        Ok(serde_json::from_str::<Credentials>(&text)?)
+    }
+}
+
+/// 関数を呼び出して`Credentials`を取得する。シークレット管理サービスなどから読み取る場合に使う。
+pub struct CallbackCredentialsProvider<F>
+where
+    F: Fn() -> Result<Credentials, Error> + Send + Sync,
+{
+    callback: F,
+}
+
+impl<F> CallbackCredentialsProvider<F>
+where
+    F: Fn() -> Result<Credentials, Error> + Send + Sync,
+{
+    /// `Credentials`を返す関数を指定して作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - `Credentials`を返す関数。
+    ///
+    pub fn new(callback: F) -> CallbackCredentialsProvider<F> {
+        CallbackCredentialsProvider { callback }
+    }
+}
+
+impl<F> CredentialsProvider for CallbackCredentialsProvider<F>
+where
+    F: Fn() -> Result<Credentials, Error> + Send + Sync,
+{
+    fn credentials(&self) -> Result<Credentials, Error> {
+        (self.callback)()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// 単体テスト用のAPIキー, APIシークレット。
+    pub(crate) fn fake_credentials() -> Credentials {
+        Credentials::new("fake", "fake")
+    }
+
+    #[test]
+    fn test_debug_does_not_leak_secret() {
+        let credentials = Credentials::new("key", "very-secret");
+        let debug = format!("{:?}", credentials);
+        assert!(debug.contains("key"));
+        assert!(!debug.contains("very-secret"));
+    }
+
+    #[test]
+    fn test_env_credentials_provider() {
+        std::env::set_var("GMO_COIN_RS_TEST_KEY", "env-key");
+        std::env::set_var("GMO_COIN_RS_TEST_SECRET", "env-secret");
+        let provider =
+            EnvCredentialsProvider::with_names("GMO_COIN_RS_TEST_KEY", "GMO_COIN_RS_TEST_SECRET");
+        let credentials = provider.credentials().unwrap();
+        assert_eq!(credentials.api_key(), "env-key");
+        assert_eq!(credentials.api_secret(), "env-secret");
+
+        let provider =
+            EnvCredentialsProvider::with_names("GMO_COIN_RS_NOT_SET", "GMO_COIN_RS_NOT_SET");
+        assert!(matches!(provider.credentials(), Err(Error::EnvVarError(_))));
+    }
+
+    #[test]
+    fn test_static_and_callback_credentials_provider() {
+        let provider = StaticCredentialsProvider::new(Credentials::new("static-key", "secret"));
+        assert_eq!(provider.credentials().unwrap().api_key(), "static-key");
+
+        let provider =
+            CallbackCredentialsProvider::new(|| Ok(Credentials::new("callback-key", "secret")));
+        assert_eq!(provider.credentials().unwrap().api_key(), "callback-key");
+    }
+
+    #[test]
+    fn test_file_credentials_provider() {
+        let path = std::env::temp_dir().join(format!(
+            "gmo-coin-rs-credentials-{}.json",
+            std::process::id()
+        ));
+        let mut file = std::fs::File::create(&path).unwrap();
+        file.write_all(br#"{"api_key": "file-key", "api_secret": "file-secret"}"#)
+            .unwrap();
+        let provider = FileCredentialsProvider::new(&path);
+        let credentials = provider.credentials().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(credentials.api_key(), "file-key");
+        assert_eq!(credentials.api_secret(), "file-secret");
+
+        assert!(matches!(
+            provider.credentials(),
+            Err(Error::CredentialsFileError(_))
+        ));
+    }
+}
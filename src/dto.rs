@@ -1,66 +1,124 @@
 //! 各種DTOを定義する。
 //! ここで定義したDTOはGMOコインからのレスポンスを構造体にバインディングするのに用いる。
 
+use crate::api_error_code::ApiErrorCode;
+use crate::execution_type::ExecutionType;
+use crate::id::{ExecutionId, OrderId, PositionId};
 use crate::json::*;
+use crate::order_status::OrderStatus;
+use crate::order_type::OrderType;
+use crate::price::Price;
+use crate::settle_type::SettleType;
+use crate::side::Side;
+use crate::size::Size;
+use crate::time_in_force::TimeInForce;
 use chrono::{DateTime, Utc};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// GMOコインから返ってくる区分値を格納する列挙型。
+/// ライブラリが知らない値が返ってきてもデシリアライズに失敗しないように、その場合は`Unknown`に元の文字列を格納する。
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MaybeUnknown<T> {
+    /// ライブラリが知っている値。
+    Known(T),
+
+    /// ライブラリが知らない値。
+    Unknown(String),
+}
+
+impl<T: Copy> MaybeUnknown<T> {
+    /// ライブラリが知っている値であれば取得する。
+    pub fn known(&self) -> Option<T> {
+        match self {
+            MaybeUnknown::Known(v) => Some(*v),
+            MaybeUnknown::Unknown(_) => None,
+        }
+    }
+}
+
+/// GMOコインでの表記("BUY"など)で表示する。
+impl<T: Serialize> fmt::Display for MaybeUnknown<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::String(s)) => write!(f, "{}", s),
+            _ => Err(fmt::Error),
+        }
+    }
+}
+
+impl<T> From<T> for MaybeUnknown<T> {
+    fn from(v: T) -> Self {
+        MaybeUnknown::Known(v)
+    }
+}
+
+impl<T: PartialEq> PartialEq<T> for MaybeUnknown<T> {
+    fn eq(&self, other: &T) -> bool {
+        match self {
+            MaybeUnknown::Known(v) => v == other,
+            MaybeUnknown::Unknown(_) => false,
+        }
+    }
+}
 
 /// 注文情報を格納する構造体。
 #[derive(Deserialize)]
 pub struct Order {
     /// 親注文ID。
-    #[serde(deserialize_with = "id_to_str", rename = "rootOrderId")]
-    pub root_order_id: String,
+    #[serde(rename = "rootOrderId")]
+    pub root_order_id: OrderId,
 
     /// 注文ID。
-    #[serde(deserialize_with = "id_to_str", rename = "orderId")]
-    pub order_id: String,
+    #[serde(rename = "orderId")]
+    pub order_id: OrderId,
 
     /// 銘柄名。
     pub symbol: String,
 
-    /// 売買区分。"BUY" or "SELL"。
-    pub side: String,
+    /// 売買区分。
+    pub side: MaybeUnknown<Side>,
 
-    /// 取引区分。"NORMAL" or "LOSSCUT"。
+    /// 取引区分。
     #[serde(rename = "orderType")]
-    pub order_type: String,
+    pub order_type: MaybeUnknown<OrderType>,
 
-    /// 注文タイプ。"MARKET", "LIMIT", or "STOP"。
+    /// 注文タイプ。
     #[serde(rename = "executionType")]
-    pub execution_type: String,
+    pub execution_type: MaybeUnknown<ExecutionType>,
 
-    /// 決済区分。"OPEN" or "CLOSE"。
+    /// 決済区分。
     #[serde(rename = "settleType")]
-    pub settle_type: String,
+    pub settle_type: MaybeUnknown<SettleType>,
 
     /// 発注数量。
-    #[serde(deserialize_with = "str_to_f64")]
-    pub size: f64,
+    #[serde(deserialize_with = "str_to_size")]
+    pub size: Size,
 
     /// 約定数量。
-    #[serde(deserialize_with = "str_to_f64", rename = "executedSize")]
-    pub executed_size: f64,
+    #[serde(deserialize_with = "str_to_size", rename = "executedSize")]
+    pub executed_size: Size,
 
     /// 注文価格。MARKET注文の場合は"0"。
-    #[serde(deserialize_with = "str_to_i64")]
-    pub price: i64,
+    #[serde(deserialize_with = "str_to_price")]
+    pub price: Price,
 
     /// ロスカットレート。現物取引や未設定の場合は"0"。
-    #[serde(deserialize_with = "str_to_i64", rename = "losscutPrice")]
-    pub losscut_price: i64,
+    #[serde(deserialize_with = "str_to_price", rename = "losscutPrice")]
+    pub losscut_price: Price,
 
-    /// 注文ステータス。"WAITING", "ORDERED", "MODIFYING", "CANCELLING", "CANCELED", "EXECUTED", or "EXPIRED"
-    pub status: String,
+    /// 注文ステータス。
+    pub status: MaybeUnknown<OrderStatus>,
 
     /// 取消区分。"NONE", "USER", "POSITION_LOSSCUT", "INSUFFICIENT_BALANCE", "INSUFFICIENT_MARGIN", "ACCOUNT_LOSSCUT", "EXPIRED_FAK", "EXPIRED_FOK", or "EXPIRED_SOK"。
     /// GMOコインではstatusが "CANCELLING", "CANCELED" または "EXPIRED" の場合のみ返ってくるが、実装として難しいので無い場合は"NONE"という値を持つ。
     #[serde(default = "get_string_default_value", rename = "cancelType")]
     pub cancel_type: String,
 
-    /// 執行数量条件。"FAK", "FAS", or "FOK"。 Post-only の場合は "SOK"。
+    /// 執行数量条件。
     #[serde(rename = "timeInForce")]
-    pub time_in_force: String,
+    pub time_in_force: MaybeUnknown<TimeInForce>,
 
     /// 注文日時。
     #[serde(deserialize_with = "gmo_timestamp_to_chrono_timestamp")]
@@ -71,15 +129,15 @@ pub struct Order {
 #[derive(Deserialize)]
 pub struct Trade {
     /// 約定価格。
-    #[serde(deserialize_with = "str_to_i64")]
-    pub price: i64,
+    #[serde(deserialize_with = "str_to_price")]
+    pub price: Price,
 
-    /// 売買区分。"BUY" or "SELL"。
-    pub side: String,
+    /// 売買区分。
+    pub side: MaybeUnknown<Side>,
 
     /// 約定数量。
-    #[serde(deserialize_with = "str_to_f64")]
-    pub size: f64,
+    #[serde(deserialize_with = "str_to_size")]
+    pub size: Size,
 
     /// 約定日時。
     #[serde(deserialize_with = "gmo_timestamp_to_chrono_timestamp")]
@@ -90,30 +148,30 @@ pub struct Trade {
 #[derive(Deserialize)]
 pub struct Execution {
     /// 約定ID。
-    #[serde(deserialize_with = "id_to_str", rename = "executionId")]
-    pub execution_id: String,
+    #[serde(rename = "executionId")]
+    pub execution_id: ExecutionId,
 
     /// 注文ID。
-    #[serde(deserialize_with = "id_to_str", rename = "orderId")]
-    pub order_id: String,
+    #[serde(rename = "orderId")]
+    pub order_id: OrderId,
 
     /// 銘柄名。
     pub symbol: String,
 
-    /// 売買区分。"BUY" or "SELL"。
-    pub side: String,
+    /// 売買区分。
+    pub side: MaybeUnknown<Side>,
 
-    /// 決済区分。"OPEN" or "CLOSE"。
+    /// 決済区分。
     #[serde(rename = "settleType")]
-    pub settle_type: String,
+    pub settle_type: MaybeUnknown<SettleType>,
 
     /// 約定数量。
-    #[serde(deserialize_with = "str_to_f64")]
-    pub size: f64,
+    #[serde(deserialize_with = "str_to_size")]
+    pub size: Size,
 
     /// 約定レート。
-    #[serde(deserialize_with = "str_to_i64")]
-    pub price: i64,
+    #[serde(deserialize_with = "str_to_price")]
+    pub price: Price,
 
     /// 決済損益。
     #[serde(deserialize_with = "str_to_i64", rename = "lossGain")]
@@ -132,26 +190,26 @@ pub struct Execution {
 #[derive(Deserialize)]
 pub struct Position {
     /// 建玉ID。
-    #[serde(deserialize_with = "id_to_str", rename = "positionId")]
-    pub position_id: String,
+    #[serde(rename = "positionId")]
+    pub position_id: PositionId,
 
     /// 銘柄名。
     pub symbol: String,
 
-    /// 売買区分。"BUY" or "SELL"。
-    pub side: String,
+    /// 売買区分。
+    pub side: MaybeUnknown<Side>,
 
     /// 建玉数量。
-    #[serde(deserialize_with = "str_to_f64")]
-    pub size: f64,
+    #[serde(deserialize_with = "str_to_size")]
+    pub size: Size,
 
     /// 発注中数量。
-    #[serde(deserialize_with = "str_to_f64", rename = "orderdSize")]
-    pub orderd_size: f64,
+    #[serde(deserialize_with = "str_to_size", rename = "orderdSize")]
+    pub orderd_size: Size,
 
     /// 建玉レート。
-    #[serde(deserialize_with = "str_to_i64")]
-    pub price: i64,
+    #[serde(deserialize_with = "str_to_price")]
+    pub price: Price,
 
     /// 評価損益。
     #[serde(deserialize_with = "str_to_i64", rename = "lossGain")]
@@ -162,8 +220,8 @@ pub struct Position {
     pub leverage: f64,
 
     /// ロスカットレート。
-    #[serde(deserialize_with = "str_to_f64", rename = "losscutPrice")]
-    pub losscut_price: f64,
+    #[serde(deserialize_with = "str_to_price", rename = "losscutPrice")]
+    pub losscut_price: Price,
 
     /// 注文日時。
     #[serde(deserialize_with = "gmo_timestamp_to_chrono_timestamp")]
@@ -174,23 +232,23 @@ pub struct Position {
 #[derive(Deserialize)]
 pub struct Summary {
     /// 平均建玉レート。
-    #[serde(deserialize_with = "id_to_str", rename = "averagePositionRate")]
-    pub average_position_rate: String,
+    #[serde(deserialize_with = "str_to_price", rename = "averagePositionRate")]
+    pub average_position_rate: Price,
 
     /// 評価損益。
     #[serde(deserialize_with = "str_to_i64", rename = "positionLossGain")]
     pub position_loss_gain: i64,
 
-    /// 売買区分。"BUY" or "SELL"。
-    pub side: String,
+    /// 売買区分。
+    pub side: MaybeUnknown<Side>,
 
     /// 発注中数量。
-    #[serde(deserialize_with = "str_to_f64", rename = "sumOrderQuantity")]
-    pub sum_order_quantity: f64,
+    #[serde(deserialize_with = "str_to_size", rename = "sumOrderQuantity")]
+    pub sum_order_quantity: Size,
 
     /// 建玉数量。
-    #[serde(deserialize_with = "str_to_f64", rename = "sumPositionQuantity")]
-    pub sum_position_quantity: f64,
+    #[serde(deserialize_with = "str_to_size", rename = "sumPositionQuantity")]
+    pub sum_position_quantity: Size,
 
     /// 銘柄名。"BTC_JPY", "ETH_JPY", "BCH_JPY", "LTC_JPY", or "XPR_JPY"。
     pub symbol: String,
@@ -212,14 +270,14 @@ pub struct Pagination {
 #[derive(Deserialize)]
 pub struct CancelFailedOrder {
     /// エラーコード。
-    pub message_code: String,
+    pub message_code: MaybeUnknown<ApiErrorCode>,
 
     /// エラーメッセージ。
     pub message_string: String,
 
     /// 注文Id。
-    #[serde(deserialize_with = "id_to_str", rename = "orderId")]
-    pub order_id: String,
+    #[serde(rename = "orderId")]
+    pub order_id: OrderId,
 }
 
 /// 取得対象ページのデフォルト値。APIを呼び出すとき、数値が指定されない場合はこの値を用いる。
@@ -245,3 +303,26 @@ pub(crate) fn get_pagination_default_value() -> Pagination {
         count: 0,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maybe_unknown() {
+        let side: MaybeUnknown<Side> = serde_json::from_str(r#""SELL""#).unwrap();
+        assert_eq!(side, Side::Sell);
+        assert_eq!(side.known(), Some(Side::Sell));
+
+        let status: MaybeUnknown<OrderStatus> = serde_json::from_str(r#""NEW_STATUS""#).unwrap();
+        assert_eq!(status, MaybeUnknown::Unknown("NEW_STATUS".to_string()));
+        assert_eq!(status.known(), None);
+
+        assert_eq!(
+            serde_json::to_string(&MaybeUnknown::Known(TimeInForce::Sok)).unwrap(),
+            r#""SOK""#
+        );
+        assert_eq!(format!("{}", MaybeUnknown::Known(Side::Buy)), "BUY");
+        assert_eq!(format!("{}", status), "NEW_STATUS");
+    }
+}
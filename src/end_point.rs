@@ -3,11 +3,11 @@
 /// Public APIのエンドポイント。
 pub(crate) const PUBLIC_ENDPOINT: &str = "https://api.coin.z.com/public";
 
-// Public WebSocket APIのエンドポイント。
-// pub(crate) const PUBLIC_WEB_SOCKET_ENDPOINT: &str = "wss://api.coin.z.com/ws/public";
+/// Public WebSocket APIのエンドポイント。
+pub(crate) const PUBLIC_WEB_SOCKET_ENDPOINT: &str = "wss://api.coin.z.com/ws/public";
 
 /// Private APIのエンドポイント。
 pub(crate) const PRIVATE_ENDPOINT: &str = "https://api.coin.z.com/private";
 
-// Public WebSocket APIのエンドポイント。
-// pub(crate) const PRIVATE_WEB_SOCKET_ENDPOINT: &str = "wss://api.coin.z.com/ws/private";
+/// Private WebSocket APIのエンドポイント。
+pub(crate) const PRIVATE_WEB_SOCKET_ENDPOINT: &str = "wss://api.coin.z.com/ws/private";
@@ -1,6 +1,11 @@
 //! ライブラリ内で異常が発生したときに投げるエラーを定義する。
 
+use crate::api_error_code::ApiErrorCode;
+use crate::dto::MaybeUnknown;
+use crate::id::PositionId;
+use crate::price::Price;
 use crate::response::ErrorResponse;
+use crate::size::Size;
 use thiserror::Error;
 
 /// 異常が発生したときに投げるエラー。
@@ -12,28 +17,145 @@ pub enum Error {
     #[error("HTTPレスポンスのボディをserde_jsonで構造体にバインディングしてるとこで異常があった")]
     SerdeJsonError(serde_json::Error),
 
+    #[error("{path}のレスポンス(HTTPステータスコード{http_status_code})を構造体にもエラーレスポンスにもバインディングできなかった")]
+    ResponseParseError {
+        /// HTTPステータスコード。
+        http_status_code: u16,
+
+        /// レスポンスのボディ。
+        body_text: String,
+
+        /// 呼び出したAPIのパス。
+        path: String,
+
+        /// 構造体にバインディングしたときのエラー。
+        body_error: serde_json::Error,
+
+        /// エラーレスポンスにバインディングしたときのエラー。
+        error_response_error: serde_json::Error,
+    },
+
     #[error("URLを作るとこで異常が起きた")]
     UrlParseError(url::ParseError),
 
+    #[error("タイムアウトまでにレスポンスが返ってこなかった")]
+    TimeoutError(),
+
     #[error("空のレスポンスが返ってこないはずの箇所で空のレスポンスが返ってきた")]
     EmptyResponseError(),
 
-    #[error("GMOコインのAPIからエラーレスポンスが返ってきた")]
-    APIError(ErrorResponse),
+    /// `code`はエラーレスポンスの最初のメッセージのエラーコード。メッセージがない場合は空文字列の`Unknown`になる。
+    #[error("GMOコインのAPIからエラーレスポンス{code}が返ってきた")]
+    APIError {
+        code: MaybeUnknown<ApiErrorCode>,
+        response: ErrorResponse,
+    },
 
     #[error("環境変数を読み取れなかった")]
     EnvVarError(std::env::VarError),
 
+    #[error("APIキー, APIシークレットを書いたファイルを読み取れなかった")]
+    CredentialsFileError(std::io::Error),
+
+    #[error("カセットファイルを読み書きできなかった")]
+    CassetteFileError(std::io::Error),
+
+    #[error("カセットファイルにリクエスト{0}と一致するやり取りがない")]
+    CassetteMismatchError(String),
+
+    #[error("過去データのファイルを読み取れなかった")]
+    HistoryFileError(std::io::Error),
+
+    #[error("モックにリクエスト{0}と一致するレスポンスが登録されていない")]
+    MockRouteNotFoundError(String),
+
+    #[error("模擬取引所サーバーを起動できなかった")]
+    SimulatorServerError(Box<dyn std::error::Error + Send + Sync>),
+
     #[error("IDを文字列から数値に変換できなかった")]
     IdToNumberError(String),
 
     #[error("指値/逆指値注文で価格が指定されていない")]
     PriceNotSpecifiedError(),
 
+    #[error("IDの配列で{0}が重複して指定されている")]
+    DuplicateIdError(String),
+
+    #[error("決済注文で決済する建玉が指定されていない")]
+    PositionNotSpecifiedError(),
+
+    #[error("決済注文で建玉{0}が重複して指定されている")]
+    DuplicatePositionError(PositionId),
+
+    #[error("決済注文で指定された建玉{0}が見つからない")]
+    PositionNotFoundError(PositionId),
+
+    #[error("建玉{0}の銘柄{1}が決済注文の銘柄と一致しない")]
+    PositionSymbolMismatchError(PositionId, String),
+
+    #[error("建玉{0}の売買区分{1}が決済注文の売買区分と反対になっていない")]
+    PositionSideMismatchError(PositionId, String),
+
+    #[error("建玉{0}の決済数量{1}が決済できる数量{2}を超えている")]
+    PositionSizeExceededError(PositionId, Size, Size),
+
+    #[error("決済数量{0}が決済できる建玉の合計数量{1}を超えている")]
+    InsufficientPositionSizeError(Size, Size),
+
+    #[error("注文方法{0}では執行数量条件{1}を指定できない")]
+    TimeInForceNotAllowedError(String, String),
+
+    #[error("注文方法{0}ではロスカットレートを指定できない")]
+    LosscutPriceNotAllowedError(String),
+
+    #[error("銘柄{0}の取引ルールが見つからない")]
+    SymbolRuleNotFoundError(String),
+
+    #[error("注文数量{0}が最小注文数量{1}より小さい")]
+    OrderSizeTooSmallError(Size, Size),
+
+    #[error("注文数量{0}が最大注文数量{1}より大きい")]
+    OrderSizeTooLargeError(Size, Size),
+
+    #[error("注文数量{0}が最小注文単位{1}の倍数になっていない")]
+    OrderSizeStepError(Size, Size),
+
+    #[error("注文価格{0}が呼値{1}の倍数になっていない")]
+    OrderPriceTickError(Price, Price),
+
+    #[error("WebSocketの通信で異常が起きた")]
+    WebSocketError(tokio_tungstenite::tungstenite::Error),
+
+    #[error("GMOコインのWebSocket APIからエラーメッセージが返ってきた")]
+    WebSocketAPIError(String),
+
     #[error("デバッグ用")]
     UnknownError,
 }
 
+impl Error {
+    /// GMOコインのAPIからエラーレスポンスが返ってきた場合に、そのエラーコードを取得する。
+    /// ライブラリが知らないエラーコードの場合は`MaybeUnknown::Unknown`に元の文字列を格納して返す。
+    /// それ以外のエラーの場合は`None`を返す。
+    pub fn api_error_code(&self) -> Option<MaybeUnknown<ApiErrorCode>> {
+        match self {
+            Error::APIError { code, .. } => Some(code.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl From<ErrorResponse> for Error {
+    fn from(response: ErrorResponse) -> Self {
+        Error::APIError {
+            code: response
+                .code()
+                .unwrap_or_else(|| MaybeUnknown::Unknown(String::new())),
+            response,
+        }
+    }
+}
+
 impl From<reqwest::Error> for Error {
     fn from(e: reqwest::Error) -> Self {
         Error::ReqwestError(e)
@@ -57,3 +179,9 @@ impl From<std::env::VarError> for Error {
         Error::EnvVarError(e)
     }
 }
+
+impl From<tokio_tungstenite::tungstenite::Error> for Error {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        Error::WebSocketError(e)
+    }
+}
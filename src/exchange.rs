@@ -0,0 +1,716 @@
+//! 模擬取引所を実装する。GMOコインのPrivate APIと同じ形式のリクエストを約定エンジンで処理し、同じ形式のレスポンスを返す。
+//! 模擬取引所サーバー(`simulator`)、ペーパートレード用のHTTPクライアント(`paper`)、バックテスト(`backtest`)が使う。
+
+pub(crate) mod engine;
+
+use crate::api_error_code::ApiErrorCode;
+use crate::execution_type::ExecutionType;
+use crate::price::Price;
+use crate::settle_type::SettleType;
+use crate::side::Side;
+use crate::size::Size;
+use crate::time_in_force::TimeInForce;
+use chrono::{DateTime, SecondsFormat, Utc};
+use engine::{Engine, NewOrder, SimExecution, SimOrder, SimPosition};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// 注文一覧系APIのデフォルトのページ番号。
+const DEFAULT_PAGE: usize = 1;
+
+/// 注文一覧系APIのデフォルトの取得件数。
+const DEFAULT_COUNT: usize = 100;
+
+/// デフォルトのメイカー手数料率。GMOコインの取引所(現物)と同じく、約定代金の0.01%を受け取る。
+#[cfg(any(test, feature = "paper", feature = "backtest"))]
+pub(crate) const DEFAULT_MAKER_FEE_RATE: f64 = -0.0001;
+
+/// デフォルトのテイカー手数料率。GMOコインの取引所(現物)と同じく、約定代金の0.05%を支払う。
+#[cfg(any(test, feature = "paper", feature = "backtest"))]
+pub(crate) const DEFAULT_TAKER_FEE_RATE: f64 = 0.0005;
+
+/// APIの処理結果。成功した場合はレスポンスの`data`の部分を返す。
+pub(crate) type RouteResult = Result<Option<Value>, ApiErrorCode>;
+
+/// APIの処理結果をGMOコインと同じ形式のHTTPステータスコードとボディに変換する。
+pub(crate) fn to_response(result: RouteResult) -> (u16, String) {
+    let responsetime = timestamp(Utc::now());
+    let (http_status_code, body) = match result {
+        Ok(Some(data)) => (
+            200,
+            json!({"status": 0, "data": data, "responsetime": responsetime}),
+        ),
+        Ok(None) => (200, json!({"status": 0, "responsetime": responsetime})),
+        Err(code) => (
+            if code == ApiErrorCode::InvalidPath {
+                404
+            } else {
+                200
+            },
+            json!({
+                "status": 1,
+                "messages": [error_message_json(code)],
+                "responsetime": responsetime,
+            }),
+        ),
+    };
+    (http_status_code, body.to_string())
+}
+
+/// URLのクエリ文字列を読み取る。
+pub(crate) fn parse_query(query: Option<&str>) -> HashMap<String, String> {
+    url::form_urlencoded::parse(query.unwrap_or("").as_bytes())
+        .into_owned()
+        .collect()
+}
+
+/// Private APIを処理する。
+pub(crate) fn private_route(
+    engine: &mut Engine,
+    method: &str,
+    path: &str,
+    query: &HashMap<String, String>,
+    body: &str,
+) -> RouteResult {
+    match method {
+        "GET" => private_get_route(engine, path, query),
+        "POST" => {
+            let params: Value =
+                serde_json::from_str(body).map_err(|_| ApiErrorCode::InvalidParameter)?;
+            private_post_route(engine, path, &params)
+        }
+        _ => Err(ApiErrorCode::InvalidPath),
+    }
+}
+
+/// Private APIのうちGETリクエストを処理する。
+fn private_get_route(engine: &Engine, path: &str, query: &HashMap<String, String>) -> RouteResult {
+    match path {
+        "/v1/account/margin" => {
+            let margin = engine.margin();
+            Ok(Some(json!({
+                "actualProfitLoss": margin.actual_profit_loss.to_string(),
+                "availableAmount": margin.available_amount.to_string(),
+                "margin": margin.margin.to_string(),
+                "profitLoss": margin.profit_loss.to_string(),
+            })))
+        }
+        "/v1/account/assets" => Ok(Some(json!([{
+            "amount": engine.balance().to_string(),
+            "available": engine.margin().available_amount.to_string(),
+            "conversionRate": "1",
+            "symbol": "JPY",
+        }]))),
+        "/v1/activeOrders" => {
+            let symbol = query_symbol(query)?;
+            let orders: Vec<Value> = engine
+                .orders()
+                .rev()
+                .filter(|o| o.is_active() && o.symbol == symbol)
+                .map(order_json)
+                .collect();
+            Ok(Some(paginate(orders, query)?))
+        }
+        "/v1/orders" => {
+            let ids = query_ids(query, "orderId")?;
+            let orders: Vec<Value> = ids
+                .iter()
+                .filter_map(|id| engine.order(*id))
+                .map(order_json)
+                .collect();
+            Ok(Some(json!({ "list": orders })))
+        }
+        "/v1/executions" => {
+            let executions: Vec<Value> = match query_ids(query, "orderId") {
+                Ok(ids) => engine
+                    .executions()
+                    .iter()
+                    .filter(|e| ids.contains(&e.order_id))
+                    .map(execution_json)
+                    .collect(),
+                Err(_) => {
+                    let ids = query_ids(query, "executionId")?;
+                    engine
+                        .executions()
+                        .iter()
+                        .filter(|e| ids.contains(&e.execution_id))
+                        .map(execution_json)
+                        .collect()
+                }
+            };
+            Ok(Some(json!({ "list": executions })))
+        }
+        "/v1/latestExecutions" => {
+            let symbol = query_symbol(query)?;
+            let executions: Vec<Value> = engine
+                .executions()
+                .iter()
+                .rev()
+                .filter(|e| e.symbol == symbol)
+                .map(execution_json)
+                .collect();
+            Ok(Some(paginate(executions, query)?))
+        }
+        "/v1/openPositions" => {
+            let symbol = query_symbol(query)?;
+            let positions: Vec<Value> = engine
+                .positions()
+                .filter(|p| p.symbol == symbol)
+                .map(|p| position_json(engine, p))
+                .collect();
+            Ok(Some(paginate(positions, query)?))
+        }
+        "/v1/positionSummary" => Ok(Some(json!({
+            "list": position_summary_json(engine, query.get("symbol"))
+        }))),
+        _ => Err(ApiErrorCode::InvalidPath),
+    }
+}
+
+/// Private APIのうちPOSTリクエストを処理する。
+fn private_post_route(engine: &mut Engine, path: &str, params: &Value) -> RouteResult {
+    match path {
+        "/v1/order" => {
+            let order_id = engine.place_order(new_order(params, true)?)?;
+            Ok(Some(json!(order_id.to_string())))
+        }
+        "/v1/changeOrder" => {
+            engine.change_order(
+                param_id(params, "orderId")?,
+                param_price(params, "price")?,
+                param_opt_price(params, "losscutPrice")?,
+            )?;
+            Ok(None)
+        }
+        "/v1/cancelOrder" => {
+            engine.cancel_order(param_id(params, "orderId")?)?;
+            Ok(None)
+        }
+        "/v1/cancelOrders" => {
+            let ids = param(params, "orderIds")?
+                .as_array()
+                .ok_or(ApiErrorCode::InvalidParameter)?;
+            let mut success = Vec::new();
+            let mut failed = Vec::new();
+            for id in ids {
+                let order_id = value_to_id(id)?;
+                match engine.cancel_order(order_id) {
+                    Ok(()) => success.push(order_id),
+                    Err(code) => {
+                        let mut message = error_message_json(code);
+                        message["orderId"] = json!(order_id);
+                        failed.push(message);
+                    }
+                }
+            }
+            Ok(Some(json!({"failed": failed, "success": success})))
+        }
+        "/v1/cancelBulkOrder" => {
+            let symbols: Vec<String> = param_enum(params, "symbols")?;
+            let side = param_opt_enum::<Side>(params, "side")?;
+            let settle_type = param_opt_enum::<SettleType>(params, "settleType")?;
+            let order_ids = engine.cancel_bulk_order(&symbols, side, settle_type);
+            Ok(Some(json!(order_ids)))
+        }
+        "/v1/closeOrder" => {
+            let settle_positions = param(params, "settlePosition")?
+                .as_array()
+                .ok_or(ApiErrorCode::InvalidParameter)?
+                .iter()
+                .map(|p| Ok((param_id(p, "positionId")?, param_size(p, "size")?)))
+                .collect::<Result<Vec<_>, ApiErrorCode>>()?;
+            let mut order = new_order(params, false)?;
+            order.size = settle_positions.iter().map(|(_, s)| *s).sum();
+            let order_id = engine.place_close_order(order, settle_positions)?;
+            Ok(Some(json!(order_id.to_string())))
+        }
+        "/v1/closeBulkOrder" => {
+            let order_id = engine.place_close_bulk_order(new_order(params, true)?)?;
+            Ok(Some(json!(order_id.to_string())))
+        }
+        "/v1/changeLosscutPrice" => {
+            engine.change_losscut_price(
+                param_id(params, "positionId")?,
+                param_price(params, "losscutPrice")?,
+            )?;
+            Ok(None)
+        }
+        _ => Err(ApiErrorCode::InvalidPath),
+    }
+}
+
+/// 新規注文、決済注文のパラメーターを読み取る。
+fn new_order(params: &Value, with_size: bool) -> Result<NewOrder, ApiErrorCode> {
+    let execution_type: ExecutionType = param_enum(params, "executionType")?;
+    let time_in_force = match param_opt_enum::<TimeInForce>(params, "timeInForce")? {
+        Some(t) => t,
+        None if execution_type == ExecutionType::Limit => TimeInForce::Fas,
+        None => TimeInForce::Fak,
+    };
+    Ok(NewOrder {
+        symbol: param_enum(params, "symbol")?,
+        side: param_enum(params, "side")?,
+        execution_type,
+        size: if with_size {
+            param_size(params, "size")?
+        } else {
+            Size::default()
+        },
+        price: param_opt_price(params, "price")?,
+        losscut_price: param_opt_price(params, "losscutPrice")?,
+        time_in_force,
+    })
+}
+
+fn param<'a>(params: &'a Value, key: &str) -> Result<&'a Value, ApiErrorCode> {
+    params.get(key).ok_or(ApiErrorCode::InvalidParameter)
+}
+
+fn param_enum<T: DeserializeOwned>(params: &Value, key: &str) -> Result<T, ApiErrorCode> {
+    serde_json::from_value(param(params, key)?.clone()).map_err(|_| ApiErrorCode::InvalidParameter)
+}
+
+fn param_opt_enum<T: DeserializeOwned>(
+    params: &Value,
+    key: &str,
+) -> Result<Option<T>, ApiErrorCode> {
+    match params.get(key) {
+        Some(_) => param_enum(params, key).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// 文字列でも数値でも受け付ける。
+fn param_price(params: &Value, key: &str) -> Result<Price, ApiErrorCode> {
+    match param(params, key)? {
+        Value::String(s) => s.parse().ok(),
+        Value::Number(n) => n.to_string().parse().ok(),
+        _ => None,
+    }
+    .ok_or(ApiErrorCode::InvalidParameter)
+}
+
+fn param_opt_price(params: &Value, key: &str) -> Result<Option<Price>, ApiErrorCode> {
+    match params.get(key) {
+        Some(_) => param_price(params, key).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn param_size(params: &Value, key: &str) -> Result<Size, ApiErrorCode> {
+    match param(params, key)? {
+        Value::String(s) => s.parse().ok(),
+        Value::Number(n) => n.to_string().parse().ok(),
+        _ => None,
+    }
+    .ok_or(ApiErrorCode::InvalidParameter)
+}
+
+fn param_id(params: &Value, key: &str) -> Result<u64, ApiErrorCode> {
+    value_to_id(param(params, key)?)
+}
+
+/// 文字列でも数値でもIDとして受け付ける。
+fn value_to_id(value: &Value) -> Result<u64, ApiErrorCode> {
+    match value {
+        Value::String(s) => s.parse().ok(),
+        Value::Number(n) => n.as_u64(),
+        _ => None,
+    }
+    .ok_or(ApiErrorCode::InvalidParameter)
+}
+
+pub(crate) fn query_symbol(query: &HashMap<String, String>) -> Result<&str, ApiErrorCode> {
+    query
+        .get("symbol")
+        .map(|s| s.as_str())
+        .ok_or(ApiErrorCode::InvalidParameter)
+}
+
+/// カンマ区切りのIDを読み取る。
+fn query_ids(query: &HashMap<String, String>, key: &str) -> Result<Vec<u64>, ApiErrorCode> {
+    query
+        .get(key)
+        .ok_or(ApiErrorCode::InvalidParameter)?
+        .split(',')
+        .map(|id| {
+            id.trim()
+                .parse()
+                .map_err(|_| ApiErrorCode::InvalidParameter)
+        })
+        .collect()
+}
+
+/// `page`, `count`クエリパラメーターに従って一覧を切り出す。
+fn paginate(list: Vec<Value>, query: &HashMap<String, String>) -> Result<Value, ApiErrorCode> {
+    let parse = |key: &str, default: usize| match query.get(key) {
+        Some(v) => v
+            .parse::<usize>()
+            .map_err(|_| ApiErrorCode::InvalidParameter),
+        None => Ok(default),
+    };
+    let page = parse("page", DEFAULT_PAGE)?.max(1);
+    let count = parse("count", DEFAULT_COUNT)?;
+    let list: Vec<Value> = list
+        .into_iter()
+        .skip((page - 1) * count)
+        .take(count)
+        .collect();
+    Ok(json!({
+        "pagination": {"currentPage": page, "count": count},
+        "list": list,
+    }))
+}
+
+/// GMOコインのタイムスタンプの形式に変換する。
+pub(crate) fn timestamp(t: DateTime<Utc>) -> String {
+    t.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn error_message_json(code: ApiErrorCode) -> Value {
+    json!({
+        "message_code": code.to_string(),
+        "message_string": error_message(code),
+    })
+}
+
+/// エラーコードに対応するメッセージ。
+fn error_message(code: ApiErrorCode) -> &'static str {
+    match code {
+        ApiErrorCode::InsufficientFunds => "Insufficient funds.",
+        ApiErrorCode::PositionNotFound => "The specified position does not exist.",
+        ApiErrorCode::SamePrice => "The same price as before the change.",
+        ApiErrorCode::TimestampTooLate => "The API-TIMESTAMP is too late.",
+        ApiErrorCode::TimestampTooEarly => "The API-TIMESTAMP is too early.",
+        ApiErrorCode::InvalidSignature => "Invalid signature.",
+        ApiErrorCode::ApiKeyNotSet => "The API-KEY is not set.",
+        ApiErrorCode::AuthenticationFailed => "Authentication failed.",
+        ApiErrorCode::OrderAlreadyChanged => {
+            "The order has already been changed, canceled or executed."
+        }
+        ApiErrorCode::OrderNotFound => "The specified order does not exist.",
+        ApiErrorCode::InvalidPath => "Invalid request path.",
+        _ => "Invalid request parameter.",
+    }
+}
+
+fn order_json(order: &SimOrder) -> Value {
+    let mut value = json!({
+        "rootOrderId": order.order_id,
+        "orderId": order.order_id,
+        "symbol": order.symbol,
+        "side": order.side,
+        "orderType": order.order_type,
+        "executionType": order.execution_type,
+        "settleType": order.settle_type,
+        "size": order.size.to_string(),
+        "executedSize": order.executed_size.to_string(),
+        "price": order.price.unwrap_or_default().to_string(),
+        "losscutPrice": order.losscut_price.unwrap_or_default().to_string(),
+        "status": order.status,
+        "timeInForce": order.time_in_force,
+        "timestamp": timestamp(order.timestamp),
+    });
+    if let Some(cancel_type) = order.cancel_type {
+        value["cancelType"] = json!(cancel_type);
+    }
+    value
+}
+
+fn execution_json(execution: &SimExecution) -> Value {
+    json!({
+        "executionId": execution.execution_id,
+        "orderId": execution.order_id,
+        "symbol": execution.symbol,
+        "side": execution.side,
+        "settleType": execution.settle_type,
+        "size": execution.size.to_string(),
+        "price": execution.price.to_string(),
+        "lossGain": execution.loss_gain.to_string(),
+        "fee": execution.fee.to_string(),
+        "timestamp": timestamp(execution.timestamp),
+    })
+}
+
+fn position_json(engine: &Engine, position: &SimPosition) -> Value {
+    json!({
+        "positionId": position.position_id,
+        "symbol": position.symbol,
+        "side": position.side,
+        "size": position.size.to_string(),
+        "orderdSize": position.orderd_size.to_string(),
+        "price": position.price.to_string(),
+        "lossGain": engine.loss_gain(position).to_string(),
+        "leverage": engine.leverage().to_string(),
+        "losscutPrice": position.losscut_price.to_string(),
+        "timestamp": timestamp(position.timestamp),
+    })
+}
+
+/// 銘柄と売買区分ごとに建玉をまとめる。
+fn position_summary_json(engine: &Engine, symbol: Option<&String>) -> Vec<Value> {
+    let mut groups: BTreeMap<(String, &str), Vec<&SimPosition>> = BTreeMap::new();
+    for position in engine
+        .positions()
+        .filter(|p| symbol.is_none() || symbol == Some(&p.symbol))
+    {
+        groups
+            .entry((position.symbol.clone(), position.side.to_string()))
+            .or_default()
+            .push(position);
+    }
+    groups
+        .into_iter()
+        .map(|((symbol, side), positions)| {
+            let size: Size = positions.iter().map(|p| p.size).sum();
+            let orderd_size: Size = positions.iter().map(|p| p.orderd_size).sum();
+            let amount: Price = positions.iter().map(|p| p.price * p.size).sum();
+            let loss_gain: i64 = positions.iter().map(|p| engine.loss_gain(p)).sum();
+            json!({
+                "averagePositionRate": (amount / size).to_string(),
+                "positionLossGain": loss_gain.to_string(),
+                "side": side,
+                "sumOrderQuantity": orderd_size.to_string(),
+                "sumPositionQuantity": size.to_string(),
+                "symbol": symbol,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price::price;
+    use crate::size::size;
+    use engine::Quote;
+
+    const SYMBOL: &str = "BTC_JPY";
+
+    fn engine() -> Engine {
+        let mut engine = Engine::new(1_000_000, 2);
+        engine.set_liquidity(Some(size("0.5")));
+        engine.set_quote(SYMBOL, Quote::at(price("1000000")));
+        engine
+    }
+
+    fn move_to(engine: &mut Engine, p: &str) {
+        engine.set_quote(SYMBOL, Quote::at(price(p)));
+        engine.update();
+    }
+
+    fn get(engine: &mut Engine, path: &str, query: &str) -> Value {
+        private_route(engine, "GET", path, &parse_query(Some(query)), "")
+            .unwrap()
+            .unwrap()
+    }
+
+    fn post(engine: &mut Engine, path: &str, params: Value) -> RouteResult {
+        private_route(engine, "POST", path, &HashMap::new(), &params.to_string())
+    }
+
+    /// 文字列で返ってくる数値を比べられるようにf64に変換する。
+    fn num(value: &Value) -> f64 {
+        value.as_str().unwrap().parse().unwrap()
+    }
+
+    fn order(engine: &mut Engine, order_id: &str) -> Value {
+        get(engine, "/v1/orders", &format!("orderId={}", order_id))["list"][0].clone()
+    }
+
+    #[test]
+    fn test_route_errors() {
+        let mut engine = engine();
+        let query = HashMap::new();
+        assert_eq!(
+            private_route(&mut engine, "GET", "/v1/unknown", &query, ""),
+            Err(ApiErrorCode::InvalidPath)
+        );
+        assert_eq!(
+            private_route(&mut engine, "PUT", "/v1/order", &query, "{}"),
+            Err(ApiErrorCode::InvalidPath)
+        );
+        assert_eq!(
+            private_route(&mut engine, "POST", "/v1/order", &query, "not json"),
+            Err(ApiErrorCode::InvalidParameter)
+        );
+        assert_eq!(
+            private_route(&mut engine, "GET", "/v1/activeOrders", &query, ""),
+            Err(ApiErrorCode::InvalidParameter)
+        );
+        assert_eq!(
+            post(
+                &mut engine,
+                "/v1/order",
+                json!({"symbol": SYMBOL, "side": "BUY", "executionType": "MARKET"})
+            ),
+            Err(ApiErrorCode::InvalidParameter)
+        );
+
+        let (status, body) = to_response(Err(ApiErrorCode::InvalidPath));
+        assert_eq!(status, 404);
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["messages"][0]["message_code"], "ERR-5204");
+
+        let (status, body) = to_response(Err(ApiErrorCode::OrderNotFound));
+        assert_eq!(status, 200);
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["status"], 1);
+        assert_eq!(body["messages"][0]["message_code"], "ERR-5123");
+    }
+
+    #[test]
+    fn test_change_and_cancel_partially_filled_order() {
+        let mut engine = engine();
+        let order_id = post(
+            &mut engine,
+            "/v1/order",
+            json!({"symbol": SYMBOL, "side": "BUY", "executionType": "LIMIT", "size": "1.2", "price": "985000"}),
+        )
+        .unwrap()
+        .unwrap();
+        let order_id = order_id.as_str().unwrap().to_string();
+        assert_eq!(order(&mut engine, &order_id)["timeInForce"], "FAS");
+
+        move_to(&mut engine, "980000");
+        let partially_filled = order(&mut engine, &order_id);
+        assert_eq!(partially_filled["status"], "ORDERED");
+        assert_eq!(num(&partially_filled["executedSize"]), 0.5);
+
+        // 一部約定した注文の残りの価格を変更する。
+        assert_eq!(
+            post(
+                &mut engine,
+                "/v1/changeOrder",
+                json!({"orderId": order_id, "price": "985000"})
+            ),
+            Err(ApiErrorCode::SamePrice)
+        );
+        assert_eq!(
+            post(
+                &mut engine,
+                "/v1/changeOrder",
+                json!({"orderId": order_id, "price": "970000"})
+            ),
+            Ok(None)
+        );
+        let changed = order(&mut engine, &order_id);
+        assert_eq!(num(&changed["price"]), 970000.0);
+        assert_eq!(num(&changed["executedSize"]), 0.5);
+
+        // 一部約定した注文を取り消しても、約定した分の建玉は残る。
+        assert_eq!(
+            post(&mut engine, "/v1/cancelOrder", json!({"orderId": order_id})),
+            Ok(None)
+        );
+        let canceled = order(&mut engine, &order_id);
+        assert_eq!(canceled["status"], "CANCELED");
+        assert_eq!(canceled["cancelType"], "USER");
+        assert_eq!(num(&canceled["executedSize"]), 0.5);
+        assert_eq!(
+            post(&mut engine, "/v1/cancelOrder", json!({"orderId": order_id})),
+            Err(ApiErrorCode::OrderAlreadyChanged)
+        );
+        assert_eq!(
+            post(
+                &mut engine,
+                "/v1/changeOrder",
+                json!({"orderId": order_id, "price": "960000"})
+            ),
+            Err(ApiErrorCode::OrderAlreadyChanged)
+        );
+
+        let executions = get(
+            &mut engine,
+            "/v1/executions",
+            &format!("orderId={}", order_id),
+        );
+        assert_eq!(executions["list"].as_array().unwrap().len(), 1);
+        assert_eq!(num(&executions["list"][0]["price"]), 985000.0);
+        let positions = get(&mut engine, "/v1/openPositions", "symbol=BTC_JPY");
+        assert_eq!(num(&positions["list"][0]["size"]), 0.5);
+        let active = get(&mut engine, "/v1/activeOrders", "symbol=BTC_JPY");
+        assert!(active["list"].as_array().unwrap().is_empty());
+
+        let result = post(
+            &mut engine,
+            "/v1/cancelOrders",
+            json!({"orderIds": [order_id, "999"]}),
+        )
+        .unwrap()
+        .unwrap();
+        assert!(result["success"].as_array().unwrap().is_empty());
+        assert_eq!(result["failed"][0]["message_code"], "ERR-5122");
+        assert_eq!(result["failed"][1]["orderId"], 999);
+        assert_eq!(result["failed"][1]["message_code"], "ERR-5123");
+    }
+
+    #[test]
+    fn test_losscut() {
+        let mut engine = engine();
+        post(
+            &mut engine,
+            "/v1/order",
+            json!({"symbol": SYMBOL, "side": "BUY", "executionType": "MARKET", "size": "1"}),
+        )
+        .unwrap();
+        let positions = get(&mut engine, "/v1/openPositions", "symbol=BTC_JPY");
+        let position = &positions["list"][0];
+        assert_eq!(num(&position["losscutPrice"]), 500000.0);
+        let position_id = position["positionId"].as_u64().unwrap();
+
+        let close_id = post(
+            &mut engine,
+            "/v1/closeOrder",
+            json!({
+                "symbol": SYMBOL,
+                "side": "SELL",
+                "executionType": "LIMIT",
+                "price": "2000000",
+                "settlePosition": [{"positionId": position_id, "size": "1"}],
+            }),
+        )
+        .unwrap()
+        .unwrap();
+        let close_id = close_id.as_str().unwrap().to_string();
+        let positions = get(&mut engine, "/v1/openPositions", "symbol=BTC_JPY");
+        assert_eq!(num(&positions["list"][0]["orderdSize"]), 1.0);
+
+        assert_eq!(
+            post(
+                &mut engine,
+                "/v1/changeLosscutPrice",
+                json!({"positionId": position_id, "losscutPrice": "600000"})
+            ),
+            Ok(None)
+        );
+        assert_eq!(
+            post(
+                &mut engine,
+                "/v1/changeLosscutPrice",
+                json!({"positionId": 999, "losscutPrice": "600000"})
+            ),
+            Err(ApiErrorCode::PositionNotFound)
+        );
+
+        move_to(&mut engine, "610000");
+        let positions = get(&mut engine, "/v1/openPositions", "symbol=BTC_JPY");
+        assert_eq!(positions["list"].as_array().unwrap().len(), 1);
+
+        // 変更したロスカットレートに達すると、決済注文を取り消して成行で決済する。
+        move_to(&mut engine, "590000");
+        let positions = get(&mut engine, "/v1/openPositions", "symbol=BTC_JPY");
+        assert!(positions["list"].as_array().unwrap().is_empty());
+        let close = order(&mut engine, &close_id);
+        assert_eq!(close["status"], "CANCELED");
+        assert_eq!(close["cancelType"], "POSITION_LOSSCUTTED");
+        let executions = get(&mut engine, "/v1/latestExecutions", "symbol=BTC_JPY");
+        let losscut = &executions["list"][0];
+        assert_eq!(losscut["settleType"], "CLOSE");
+        assert_eq!(num(&losscut["price"]), 590000.0);
+        assert_eq!(num(&losscut["lossGain"]), -410000.0);
+        let assets = get(&mut engine, "/v1/account/assets", "");
+        assert_eq!(num(&assets[0]["amount"]), 590000.0);
+    }
+}
@@ -0,0 +1,1084 @@
+//! 模擬取引所の約定エンジンを実装する。
+//! 気配が更新されるたびに指値/逆指値注文の約定とロスカットを判定する。
+
+use crate::api_error_code::ApiErrorCode;
+use crate::execution_type::ExecutionType;
+use crate::order_status::OrderStatus;
+use crate::order_type::OrderType;
+use crate::price::{price_from_i64, price_to_f64, Price};
+use crate::settle_type::SettleType;
+use crate::side::Side;
+use crate::size::{size_to_f64, Size};
+use crate::time_in_force::TimeInForce;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+/// ユーザーが取り消した注文の取消区分。
+pub(crate) const CANCEL_TYPE_USER: &str = "USER";
+
+/// 建玉がロスカットされたために取り消された決済注文の取消区分。
+pub(crate) const CANCEL_TYPE_POSITION_LOSSCUTTED: &str = "POSITION_LOSSCUTTED";
+
+/// FAKで約定しなかった残りの取消区分。
+pub(crate) const CANCEL_TYPE_EXPIRED_FAK: &str = "EXPIRED_FAK";
+
+/// FOKで全量約定できなかった注文の取消区分。
+pub(crate) const CANCEL_TYPE_EXPIRED_FOK: &str = "EXPIRED_FOK";
+
+/// SOKで即時約定する価格だった注文の取消区分。
+pub(crate) const CANCEL_TYPE_EXPIRED_SOK: &str = "EXPIRED_SOK";
+
+/// 数量がゼロか？ `Size`がf64の場合に引き算で残る誤差はゼロとみなす。
+fn is_zero(size: Size) -> bool {
+    size_to_f64(size).abs() < 1e-9
+}
+
+/// 小さい方の数量を返す。
+fn min_size(a: Size, b: Size) -> Size {
+    if a < b {
+        a
+    } else {
+        b
+    }
+}
+
+/// 価格と数量から金額を計算する。
+fn amount(price: Price, size: Size) -> f64 {
+    price_to_f64(price) * size_to_f64(size)
+}
+
+/// 反対の売買区分。
+fn opposite(side: Side) -> Side {
+    match side {
+        Side::Buy => Side::Sell,
+        Side::Sell => Side::Buy,
+    }
+}
+
+/// 指値注文が`best`の価格で約定するか？
+fn crosses(side: Side, best: Price, limit: Price) -> bool {
+    match side {
+        Side::Buy => best <= limit,
+        Side::Sell => best >= limit,
+    }
+}
+
+/// 逆指値注文が`best`の価格で発動するか？
+fn triggered(side: Side, best: Price, stop: Price) -> bool {
+    match side {
+        Side::Buy => best >= stop,
+        Side::Sell => best <= stop,
+    }
+}
+
+/// 板の1つの価格帯。
+#[derive(Clone)]
+pub(crate) struct Level {
+    pub(crate) price: Price,
+    pub(crate) size: Size,
+}
+
+/// 銘柄の気配。
+#[derive(Clone)]
+pub(crate) struct Quote {
+    /// 買い気配。
+    pub(crate) bid: Price,
+
+    /// 売り気配。
+    pub(crate) ask: Price,
+
+    /// 買い板。良い価格から順に並ぶ。空の場合は`bid`でいくらでも約定する。
+    pub(crate) bids: Vec<Level>,
+
+    /// 売り板。良い価格から順に並ぶ。空の場合は`ask`でいくらでも約定する。
+    pub(crate) asks: Vec<Level>,
+}
+
+impl Quote {
+    #[cfg(any(test, feature = "simulator", feature = "backtest"))]
+    /// 売り気配と買い気配が同じで、板の厚みに制限がない気配を作る。
+    pub(crate) fn at(price: Price) -> Quote {
+        Quote {
+            bid: price,
+            ask: price,
+            bids: Vec::new(),
+            asks: Vec::new(),
+        }
+    }
+}
+
+/// 新規注文、決済注文のパラメーター。
+pub(crate) struct NewOrder {
+    pub(crate) symbol: String,
+    pub(crate) side: Side,
+    pub(crate) execution_type: ExecutionType,
+    pub(crate) size: Size,
+    pub(crate) price: Option<Price>,
+    pub(crate) losscut_price: Option<Price>,
+    pub(crate) time_in_force: TimeInForce,
+}
+
+/// 模擬取引所の注文。
+pub(crate) struct SimOrder {
+    pub(crate) order_id: u64,
+    pub(crate) symbol: String,
+    pub(crate) side: Side,
+    pub(crate) order_type: OrderType,
+    pub(crate) execution_type: ExecutionType,
+    pub(crate) settle_type: SettleType,
+    pub(crate) size: Size,
+    pub(crate) executed_size: Size,
+    pub(crate) price: Option<Price>,
+    pub(crate) losscut_price: Option<Price>,
+    pub(crate) status: OrderStatus,
+    pub(crate) cancel_type: Option<&'static str>,
+    pub(crate) time_in_force: TimeInForce,
+    pub(crate) timestamp: DateTime<Utc>,
+
+    /// 決済注文の場合、決済する建玉IDとまだ約定していない数量。
+    settle_positions: Vec<(u64, Size)>,
+}
+
+impl SimOrder {
+    /// 約定、取消、失効していない注文か？
+    pub(crate) fn is_active(&self) -> bool {
+        matches!(self.status, OrderStatus::Waiting | OrderStatus::Ordered)
+    }
+
+    fn remaining(&self) -> Size {
+        self.size - self.executed_size
+    }
+}
+
+/// 模擬取引所の約定。
+pub(crate) struct SimExecution {
+    pub(crate) execution_id: u64,
+    pub(crate) order_id: u64,
+    pub(crate) symbol: String,
+    pub(crate) side: Side,
+    pub(crate) settle_type: SettleType,
+    pub(crate) size: Size,
+    pub(crate) price: Price,
+    pub(crate) loss_gain: i64,
+    pub(crate) fee: i64,
+    pub(crate) timestamp: DateTime<Utc>,
+}
+
+/// 模擬取引所の建玉。
+pub(crate) struct SimPosition {
+    pub(crate) position_id: u64,
+    pub(crate) symbol: String,
+    pub(crate) side: Side,
+    pub(crate) size: Size,
+    pub(crate) orderd_size: Size,
+    pub(crate) price: Price,
+    pub(crate) losscut_price: Price,
+    pub(crate) timestamp: DateTime<Utc>,
+}
+
+/// 余力情報。
+pub(crate) struct MarginSummary {
+    pub(crate) actual_profit_loss: i64,
+    pub(crate) available_amount: i64,
+    pub(crate) margin: i64,
+    pub(crate) profit_loss: i64,
+}
+
+/// 注文、約定、建玉と口座の円残高を持つ約定エンジン。
+/// すべての注文を証拠金取引として扱う。
+pub(crate) struct Engine {
+    quotes: BTreeMap<String, Quote>,
+    volumes: BTreeMap<String, Size>,
+    orders: BTreeMap<u64, SimOrder>,
+    executions: Vec<SimExecution>,
+    positions: BTreeMap<u64, SimPosition>,
+    next_id: u64,
+
+    /// 確定した損益と手数料を含む円残高。
+    balance: i64,
+
+    /// レバレッジ。
+    leverage: i64,
+
+    /// 気配が1回更新されるあいだに1つの指値注文が約定できる数量。`None`の場合は板の厚みだけで決まる。
+    liquidity: Option<Size>,
+
+    /// 板に並んでいた指値注文が約定したときの手数料率。負の場合は手数料を受け取る。
+    maker_fee_rate: f64,
+
+    /// 成行注文や即時約定した指値注文の手数料率。
+    taker_fee_rate: f64,
+
+    /// 注文や約定の時刻に使う時計。`None`の場合は現在時刻を使う。
+    clock: Option<DateTime<Utc>>,
+}
+
+impl Engine {
+    pub(crate) fn new(balance: i64, leverage: i64) -> Engine {
+        Engine {
+            quotes: BTreeMap::new(),
+            volumes: BTreeMap::new(),
+            orders: BTreeMap::new(),
+            executions: Vec::new(),
+            positions: BTreeMap::new(),
+            next_id: 1,
+            balance,
+            leverage,
+            liquidity: None,
+            maker_fee_rate: 0.0,
+            taker_fee_rate: 0.0,
+            clock: None,
+        }
+    }
+
+    pub(crate) fn set_balance(&mut self, balance: i64) {
+        self.balance = balance;
+    }
+
+    pub(crate) fn set_leverage(&mut self, leverage: i64) {
+        self.leverage = leverage;
+    }
+
+    #[cfg(any(test, feature = "simulator", feature = "backtest"))]
+    pub(crate) fn set_liquidity(&mut self, liquidity: Option<Size>) {
+        self.liquidity = liquidity;
+    }
+
+    #[cfg(any(test, feature = "paper", feature = "backtest"))]
+    pub(crate) fn set_fee_rates(&mut self, maker_fee_rate: f64, taker_fee_rate: f64) {
+        self.maker_fee_rate = maker_fee_rate;
+        self.taker_fee_rate = taker_fee_rate;
+    }
+
+    #[cfg(any(test, feature = "backtest"))]
+    /// 注文や約定の時刻を指定した時刻に合わせる。過去データで動かす場合に使う。
+    pub(crate) fn set_clock(&mut self, now: DateTime<Utc>) {
+        self.clock = Some(now);
+    }
+
+    /// 注文や約定の時刻に使う現在時刻。
+    pub(crate) fn now(&self) -> DateTime<Utc> {
+        self.clock.unwrap_or_else(Utc::now)
+    }
+
+    /// 銘柄の気配を更新する。約定の判定は`update`を呼んだときに行う。
+    pub(crate) fn set_quote(&mut self, symbol: &str, quote: Quote) {
+        self.quotes.insert(symbol.to_string(), quote);
+    }
+
+    #[cfg(any(test, feature = "paper"))]
+    pub(crate) fn quote(&self, symbol: &str) -> Option<&Quote> {
+        self.quotes.get(symbol)
+    }
+
+    #[cfg(any(test, feature = "simulator", feature = "backtest"))]
+    /// これまでの取引量。
+    pub(crate) fn volume(&self, symbol: &str) -> Size {
+        self.volumes.get(symbol).copied().unwrap_or_default()
+    }
+
+    #[cfg(any(test, feature = "paper"))]
+    /// 有効な注文か建玉がある銘柄。
+    pub(crate) fn symbols_in_use(&self) -> std::collections::BTreeSet<String> {
+        self.orders()
+            .filter(|o| o.is_active())
+            .map(|o| o.symbol.clone())
+            .chain(self.positions().map(|p| p.symbol.clone()))
+            .collect()
+    }
+
+    pub(crate) fn balance(&self) -> i64 {
+        self.balance
+    }
+
+    pub(crate) fn leverage(&self) -> i64 {
+        self.leverage
+    }
+
+    pub(crate) fn order(&self, order_id: u64) -> Option<&SimOrder> {
+        self.orders.get(&order_id)
+    }
+
+    pub(crate) fn orders(&self) -> impl DoubleEndedIterator<Item = &SimOrder> {
+        self.orders.values()
+    }
+
+    pub(crate) fn executions(&self) -> &[SimExecution] {
+        &self.executions
+    }
+
+    pub(crate) fn positions(&self) -> impl Iterator<Item = &SimPosition> {
+        self.positions.values()
+    }
+
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    /// `side`の注文がすぐに約定する価格。買いは売り気配、売りは買い気配。
+    fn best_price(&self, symbol: &str, side: Side) -> Option<Price> {
+        self.quotes.get(symbol).map(|q| match side {
+            Side::Buy => q.ask,
+            Side::Sell => q.bid,
+        })
+    }
+
+    /// 建玉の評価損益。建玉を今すぐ決済する価格で評価する。
+    pub(crate) fn loss_gain(&self, position: &SimPosition) -> i64 {
+        let market = self
+            .best_price(&position.symbol, opposite(position.side))
+            .unwrap_or(position.price);
+        let diff = match position.side {
+            Side::Buy => market - position.price,
+            Side::Sell => position.price - market,
+        };
+        amount(diff, position.size).round() as i64
+    }
+
+    /// 余力情報を計算する。有効な新規注文の証拠金も取引余力から差し引く。
+    pub(crate) fn margin(&self) -> MarginSummary {
+        let leverage = self.leverage as f64;
+        let profit_loss: i64 = self.positions().map(|p| self.loss_gain(p)).sum();
+        let margin: f64 = self
+            .positions()
+            .map(|p| amount(p.price, p.size) / leverage)
+            .sum();
+        let reserved: f64 = self
+            .orders()
+            .filter(|o| o.is_active() && o.settle_type == SettleType::Open)
+            .map(|o| amount(self.order_price(o), o.remaining()) / leverage)
+            .sum();
+        let actual_profit_loss = self.balance + profit_loss;
+        MarginSummary {
+            actual_profit_loss,
+            available_amount: actual_profit_loss - (margin + reserved).round() as i64,
+            margin: margin.round() as i64,
+            profit_loss,
+        }
+    }
+
+    /// 証拠金の計算に使う注文の価格。成行注文の場合は今すぐ約定する価格。
+    fn order_price(&self, order: &SimOrder) -> Price {
+        order
+            .price
+            .or_else(|| self.best_price(&order.symbol, order.side))
+            .unwrap_or_default()
+    }
+
+    /// 注文のパラメーターを検証する。
+    fn validate(&self, order: &NewOrder) -> Result<(), ApiErrorCode> {
+        if !self.quotes.contains_key(&order.symbol) || size_to_f64(order.size) <= 0.0 {
+            return Err(ApiErrorCode::InvalidParameter);
+        }
+        match (order.execution_type, order.price) {
+            (ExecutionType::Market, _) => Ok(()),
+            (_, Some(p)) if p > Price::default() => Ok(()),
+            _ => Err(ApiErrorCode::InvalidParameter),
+        }
+    }
+
+    /// 新規注文を受け付ける。今の気配で約定する場合はすぐに約定させる。
+    pub(crate) fn place_order(&mut self, order: NewOrder) -> Result<u64, ApiErrorCode> {
+        self.validate(&order)?;
+        let price = order
+            .price
+            .or_else(|| self.best_price(&order.symbol, order.side))
+            .unwrap_or_default();
+        let required = amount(price, order.size) / self.leverage as f64;
+        if required > self.margin().available_amount as f64 {
+            return Err(ApiErrorCode::InsufficientFunds);
+        }
+        Ok(self.insert_order(order, SettleType::Open, OrderType::Normal, Vec::new()))
+    }
+
+    /// 建玉を指定した決済注文を受け付ける。
+    pub(crate) fn place_close_order(
+        &mut self,
+        order: NewOrder,
+        settle_positions: Vec<(u64, Size)>,
+    ) -> Result<u64, ApiErrorCode> {
+        self.validate(&order)?;
+        let mut total = Size::default();
+        for (position_id, size) in &settle_positions {
+            let position = self
+                .positions
+                .get(position_id)
+                .ok_or(ApiErrorCode::PositionNotFound)?;
+            if position.symbol != order.symbol
+                || position.side == order.side
+                || *size > position.size - position.orderd_size
+            {
+                return Err(ApiErrorCode::InvalidParameter);
+            }
+            total += *size;
+        }
+        if settle_positions.is_empty() || total != order.size {
+            return Err(ApiErrorCode::InvalidParameter);
+        }
+        Ok(self.insert_order(
+            order,
+            SettleType::Close,
+            OrderType::Normal,
+            settle_positions,
+        ))
+    }
+
+    /// 建玉を指定しない一括決済注文を受け付ける。古い建玉から順に決済する。
+    pub(crate) fn place_close_bulk_order(&mut self, order: NewOrder) -> Result<u64, ApiErrorCode> {
+        self.validate(&order)?;
+        let mut left = order.size;
+        let mut settle_positions = Vec::new();
+        for position in self.positions.values() {
+            if is_zero(left) {
+                break;
+            }
+            if position.symbol != order.symbol || position.side == order.side {
+                continue;
+            }
+            let free = position.size - position.orderd_size;
+            if is_zero(free) {
+                continue;
+            }
+            let take = min_size(free, left);
+            settle_positions.push((position.position_id, take));
+            left -= take;
+        }
+        if !is_zero(left) {
+            return Err(ApiErrorCode::InvalidParameter);
+        }
+        self.place_close_order(order, settle_positions)
+    }
+
+    fn insert_order(
+        &mut self,
+        order: NewOrder,
+        settle_type: SettleType,
+        order_type: OrderType,
+        settle_positions: Vec<(u64, Size)>,
+    ) -> u64 {
+        for (position_id, size) in &settle_positions {
+            if let Some(p) = self.positions.get_mut(position_id) {
+                p.orderd_size += *size;
+            }
+        }
+        let order_id = self.next_id();
+        let now = self.now();
+        let status = match order.execution_type {
+            ExecutionType::Stop => OrderStatus::Waiting,
+            _ => OrderStatus::Ordered,
+        };
+        self.orders.insert(
+            order_id,
+            SimOrder {
+                order_id,
+                symbol: order.symbol,
+                side: order.side,
+                order_type,
+                execution_type: order.execution_type,
+                settle_type,
+                size: order.size,
+                executed_size: Size::default(),
+                price: order.price,
+                losscut_price: order.losscut_price,
+                status,
+                cancel_type: None,
+                time_in_force: order.time_in_force,
+                timestamp: now,
+                settle_positions,
+            },
+        );
+        self.execute_on_arrival(order_id);
+        order_id
+    }
+
+    /// 受け付けたばかりの注文を執行数量条件に従って約定させる。
+    fn execute_on_arrival(&mut self, order_id: u64) {
+        let order = &self.orders[&order_id];
+        let fills = self.fills(order);
+        let fillable: Size = fills.iter().map(|(s, _)| *s).sum();
+        let fills_all = !fills.is_empty() && is_zero(order.remaining() - fillable);
+        match (order.execution_type, order.time_in_force) {
+            (ExecutionType::Limit, TimeInForce::Sok) if !fills.is_empty() => {
+                self.expire(order_id, CANCEL_TYPE_EXPIRED_SOK)
+            }
+            (ExecutionType::Limit, TimeInForce::Fok) if !fills_all => {
+                self.expire(order_id, CANCEL_TYPE_EXPIRED_FOK)
+            }
+            (ExecutionType::Limit, TimeInForce::Fak) => {
+                self.execute(order_id, true);
+                self.expire(order_id, CANCEL_TYPE_EXPIRED_FAK);
+            }
+            _ => self.execute(order_id, true),
+        }
+    }
+
+    /// 有効な注文が今の気配で約定する数量と価格の組。
+    /// 成行注文と発動した逆指値注文は板を順に、指値注文は指値より良い価格の板を順に約定させる。
+    /// 板がない場合は、成行注文は気配で、指値注文は指値でそれぞれ約定する。指値注文は`liquidity`までしか約定しない。
+    fn fills(&self, order: &SimOrder) -> Vec<(Size, Price)> {
+        let quote = match self.quotes.get(&order.symbol) {
+            Some(q) if order.is_active() => q,
+            _ => return Vec::new(),
+        };
+        let (best, levels) = match order.side {
+            Side::Buy => (quote.ask, &quote.asks),
+            Side::Sell => (quote.bid, &quote.bids),
+        };
+        let limit = match (order.execution_type, order.price) {
+            (ExecutionType::Market, _) => None,
+            (ExecutionType::Limit, Some(p)) if crosses(order.side, best, p) => Some(p),
+            (ExecutionType::Stop, Some(p)) if triggered(order.side, best, p) => None,
+            _ => return Vec::new(),
+        };
+        let mut left = order.remaining();
+        if let (ExecutionType::Limit, Some(liquidity)) = (order.execution_type, self.liquidity) {
+            left = min_size(left, liquidity);
+        }
+        if levels.is_empty() {
+            return vec![(left, limit.unwrap_or(best))];
+        }
+        let mut fills = Vec::new();
+        for level in levels {
+            if is_zero(left) || matches!(limit, Some(p) if !crosses(order.side, level.price, p)) {
+                break;
+            }
+            let take = min_size(level.size, left);
+            if is_zero(take) {
+                continue;
+            }
+            fills.push((take, level.price));
+            left -= take;
+        }
+        fills
+    }
+
+    /// 有効な注文を今の気配で約定させる。成行注文と逆指値注文は約定しなかった残りを失効させる。
+    ///
+    /// # Arguments
+    ///
+    /// * `arriving` - 受け付けたばかりか価格を変更したばかりの注文か？ 指値注文の手数料率の判定に使う。
+    ///
+    fn execute(&mut self, order_id: u64, arriving: bool) {
+        let order = match self.orders.get(&order_id) {
+            Some(o) => o,
+            None => return,
+        };
+        let fills = self.fills(order);
+        if fills.is_empty() {
+            return;
+        }
+        let execution_type = order.execution_type;
+        let side = order.side;
+        let symbol = order.symbol.clone();
+        let taker = arriving || execution_type != ExecutionType::Limit;
+        for (size, price) in fills {
+            self.consume(&symbol, side, price, size);
+            self.fill(order_id, size, price, taker);
+        }
+        if execution_type != ExecutionType::Limit {
+            self.expire(order_id, CANCEL_TYPE_EXPIRED_FAK);
+        }
+    }
+
+    /// 約定した分だけ板を減らす。次に気配が更新されるまで同じ板で約定しないようにする。
+    fn consume(&mut self, symbol: &str, side: Side, price: Price, size: Size) {
+        let quote = match self.quotes.get_mut(symbol) {
+            Some(q) => q,
+            None => return,
+        };
+        let levels = match side {
+            Side::Buy => &mut quote.asks,
+            Side::Sell => &mut quote.bids,
+        };
+        if let Some(level) = levels.iter_mut().find(|l| l.price == price) {
+            level.size -= size;
+        }
+    }
+
+    /// 注文を約定させ、建玉と残高に反映する。
+    fn fill(&mut self, order_id: u64, size: Size, price: Price, taker: bool) {
+        let leverage = price_from_i64(self.leverage);
+        let fee_rate = if taker {
+            self.taker_fee_rate
+        } else {
+            self.maker_fee_rate
+        };
+        let fee = (amount(price, size) * fee_rate).round() as i64;
+        let execution_id = self.next_id();
+        let now = self.now();
+        let order = self.orders.get_mut(&order_id).unwrap();
+        let mut loss_gain = 0;
+        if order.settle_type == SettleType::Open {
+            let losscut_price = order.losscut_price.unwrap_or(match order.side {
+                Side::Buy => price - price / leverage,
+                Side::Sell => price + price / leverage,
+            });
+            self.positions.insert(
+                execution_id,
+                SimPosition {
+                    position_id: execution_id,
+                    symbol: order.symbol.clone(),
+                    side: order.side,
+                    size,
+                    orderd_size: Size::default(),
+                    price,
+                    losscut_price,
+                    timestamp: now,
+                },
+            );
+        } else {
+            let mut left = size;
+            for (position_id, remaining) in order.settle_positions.iter_mut() {
+                if is_zero(left) {
+                    break;
+                }
+                let take = min_size(*remaining, left);
+                if is_zero(take) {
+                    continue;
+                }
+                let position = match self.positions.get_mut(position_id) {
+                    Some(p) => p,
+                    None => continue,
+                };
+                let diff = match position.side {
+                    Side::Buy => price - position.price,
+                    Side::Sell => position.price - price,
+                };
+                loss_gain += amount(diff, take).round() as i64;
+                position.size -= take;
+                position.orderd_size -= take;
+                *remaining -= take;
+                left -= take;
+                if is_zero(position.size) {
+                    self.positions.remove(position_id);
+                }
+            }
+        }
+        self.balance += loss_gain - fee;
+        order.executed_size += size;
+        if is_zero(order.remaining()) {
+            order.status = OrderStatus::Executed;
+        } else {
+            order.status = OrderStatus::Ordered;
+        }
+        self.executions.push(SimExecution {
+            execution_id,
+            order_id,
+            symbol: order.symbol.clone(),
+            side: order.side,
+            settle_type: order.settle_type,
+            size,
+            price,
+            loss_gain,
+            fee,
+            timestamp: now,
+        });
+        *self.volumes.entry(order.symbol.clone()).or_default() += size;
+    }
+
+    /// 有効な注文を取り消し、決済注文で拘束していた建玉の数量を戻す。
+    fn close_out(&mut self, order_id: u64, status: OrderStatus, cancel_type: &'static str) {
+        let order = match self.orders.get_mut(&order_id) {
+            Some(o) if o.is_active() => o,
+            _ => return,
+        };
+        order.status = status;
+        order.cancel_type = Some(cancel_type);
+        for (position_id, remaining) in order.settle_positions.iter_mut() {
+            if let Some(p) = self.positions.get_mut(position_id) {
+                p.orderd_size -= *remaining;
+            }
+            *remaining = Size::default();
+        }
+    }
+
+    fn expire(&mut self, order_id: u64, cancel_type: &'static str) {
+        self.close_out(order_id, OrderStatus::Expired, cancel_type);
+    }
+
+    /// 注文価格を変更する。変更後の価格で約定する場合はすぐに約定させる。
+    pub(crate) fn change_order(
+        &mut self,
+        order_id: u64,
+        price: Price,
+        losscut_price: Option<Price>,
+    ) -> Result<(), ApiErrorCode> {
+        let order = self
+            .orders
+            .get_mut(&order_id)
+            .ok_or(ApiErrorCode::OrderNotFound)?;
+        if !order.is_active() || order.execution_type == ExecutionType::Market {
+            return Err(ApiErrorCode::OrderAlreadyChanged);
+        }
+        if order.price == Some(price)
+            && (losscut_price.is_none() || order.losscut_price == losscut_price)
+        {
+            return Err(ApiErrorCode::SamePrice);
+        }
+        order.price = Some(price);
+        if losscut_price.is_some() {
+            order.losscut_price = losscut_price;
+        }
+        self.execute(order_id, true);
+        Ok(())
+    }
+
+    /// 注文を取り消す。
+    pub(crate) fn cancel_order(&mut self, order_id: u64) -> Result<(), ApiErrorCode> {
+        match self.orders.get(&order_id) {
+            None => Err(ApiErrorCode::OrderNotFound),
+            Some(o) if !o.is_active() => Err(ApiErrorCode::OrderAlreadyChanged),
+            Some(_) => {
+                self.close_out(order_id, OrderStatus::Canceled, CANCEL_TYPE_USER);
+                Ok(())
+            }
+        }
+    }
+
+    /// 条件に一致する有効な注文をすべて取り消し、取り消した注文IDを返す。
+    pub(crate) fn cancel_bulk_order(
+        &mut self,
+        symbols: &[String],
+        side: Option<Side>,
+        settle_type: Option<SettleType>,
+    ) -> Vec<u64> {
+        let order_ids: Vec<u64> = self
+            .orders()
+            .filter(|o| {
+                o.is_active()
+                    && symbols.contains(&o.symbol)
+                    && (side.is_none() || side == Some(o.side))
+                    && (settle_type.is_none() || settle_type == Some(o.settle_type))
+            })
+            .map(|o| o.order_id)
+            .collect();
+        for order_id in &order_ids {
+            self.close_out(*order_id, OrderStatus::Canceled, CANCEL_TYPE_USER);
+        }
+        order_ids
+    }
+
+    /// 建玉のロスカットレートを変更する。
+    pub(crate) fn change_losscut_price(
+        &mut self,
+        position_id: u64,
+        losscut_price: Price,
+    ) -> Result<(), ApiErrorCode> {
+        let position = self
+            .positions
+            .get_mut(&position_id)
+            .ok_or(ApiErrorCode::PositionNotFound)?;
+        position.losscut_price = losscut_price;
+        Ok(())
+    }
+
+    /// 今の気配でロスカットと板に並んでいる注文の約定を判定する。気配を更新したあとに呼ぶ。
+    pub(crate) fn update(&mut self) {
+        self.losscut();
+        let order_ids: Vec<u64> = self
+            .orders()
+            .filter(|o| o.is_active())
+            .map(|o| o.order_id)
+            .collect();
+        for order_id in order_ids {
+            self.execute(order_id, false);
+        }
+    }
+
+    /// 決済する価格がロスカットレートに達した建玉を成行で決済する。
+    fn losscut(&mut self) {
+        let targets: Vec<u64> = self
+            .positions()
+            .filter(|p| {
+                let market = self
+                    .best_price(&p.symbol, opposite(p.side))
+                    .unwrap_or(p.price);
+                match p.side {
+                    Side::Buy => market <= p.losscut_price,
+                    Side::Sell => market >= p.losscut_price,
+                }
+            })
+            .map(|p| p.position_id)
+            .collect();
+        for position_id in targets {
+            let closing: Vec<u64> = self
+                .orders()
+                .filter(|o| {
+                    o.is_active() && o.settle_positions.iter().any(|(id, _)| *id == position_id)
+                })
+                .map(|o| o.order_id)
+                .collect();
+            for order_id in closing {
+                self.close_out(
+                    order_id,
+                    OrderStatus::Canceled,
+                    CANCEL_TYPE_POSITION_LOSSCUTTED,
+                );
+            }
+            let position = &self.positions[&position_id];
+            let order = NewOrder {
+                symbol: position.symbol.clone(),
+                side: opposite(position.side),
+                execution_type: ExecutionType::Market,
+                size: position.size,
+                price: None,
+                losscut_price: None,
+                time_in_force: TimeInForce::Fak,
+            };
+            let size = order.size;
+            self.insert_order(
+                order,
+                SettleType::Close,
+                OrderType::Losscut,
+                vec![(position_id, size)],
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price::price;
+    use crate::size::size;
+
+    const SYMBOL: &str = "BTC_JPY";
+
+    fn engine() -> Engine {
+        let mut engine = Engine::new(1_000_000, 2);
+        engine.set_liquidity(Some(size("0.5")));
+        engine.set_quote(SYMBOL, Quote::at(price("1000000")));
+        engine
+    }
+
+    fn move_to(engine: &mut Engine, price: i64) {
+        engine.set_quote(SYMBOL, Quote::at(price_from_i64(price)));
+        engine.update();
+    }
+
+    fn new_order(
+        side: Side,
+        execution_type: ExecutionType,
+        order_size: &str,
+        price: Option<i64>,
+    ) -> NewOrder {
+        NewOrder {
+            symbol: SYMBOL.to_string(),
+            side,
+            execution_type,
+            size: size(order_size),
+            price: price.map(price_from_i64),
+            losscut_price: None,
+            time_in_force: match execution_type {
+                ExecutionType::Limit => TimeInForce::Fas,
+                _ => TimeInForce::Fak,
+            },
+        }
+    }
+
+    #[test]
+    fn test_market_order_opens_position() {
+        let mut engine = engine();
+        let order_id = engine
+            .place_order(new_order(Side::Buy, ExecutionType::Market, "1", None))
+            .unwrap();
+        assert_eq!(
+            engine.order(order_id).unwrap().status,
+            OrderStatus::Executed
+        );
+        let position = engine.positions().next().unwrap();
+        assert_eq!(position.price, price("1000000"));
+        assert_eq!(position.losscut_price, price("500000"));
+        let margin = engine.margin();
+        assert_eq!(margin.margin, 500_000);
+        assert_eq!(margin.available_amount, 500_000);
+    }
+
+    #[test]
+    fn test_limit_order_fills_partially() {
+        let mut engine = engine();
+        let order_id = engine
+            .place_order(new_order(
+                Side::Buy,
+                ExecutionType::Limit,
+                "1.2",
+                Some(985_000),
+            ))
+            .unwrap();
+        assert_eq!(engine.order(order_id).unwrap().status, OrderStatus::Ordered);
+        move_to(&mut engine, 990_000);
+        assert_eq!(engine.executions().len(), 0);
+        move_to(&mut engine, 980_000);
+        let order = engine.order(order_id).unwrap();
+        assert_eq!(order.status, OrderStatus::Ordered);
+        assert_eq!(order.executed_size, size("0.5"));
+        assert_eq!(engine.executions()[0].price, price("985000"));
+        move_to(&mut engine, 1_010_000);
+        assert_eq!(engine.executions().len(), 1);
+    }
+
+    #[test]
+    fn test_close_order_realizes_profit() {
+        let mut engine = engine();
+        engine
+            .place_order(new_order(Side::Buy, ExecutionType::Market, "0.5", None))
+            .unwrap();
+        let position_id = engine.positions().next().unwrap().position_id;
+        let close_id = engine
+            .place_close_order(
+                new_order(Side::Sell, ExecutionType::Limit, "0.5", Some(1_010_000)),
+                vec![(position_id, size("0.5"))],
+            )
+            .unwrap();
+        assert_eq!(engine.positions().next().unwrap().orderd_size, size("0.5"));
+        assert_eq!(
+            engine.place_close_bulk_order(new_order(
+                Side::Sell,
+                ExecutionType::Market,
+                "0.1",
+                None
+            )),
+            Err(ApiErrorCode::InvalidParameter)
+        );
+        move_to(&mut engine, 1_010_000);
+        assert_eq!(
+            engine.order(close_id).unwrap().status,
+            OrderStatus::Executed
+        );
+        assert_eq!(engine.positions().count(), 0);
+        assert_eq!(engine.balance(), 1_005_000);
+    }
+
+    #[test]
+    fn test_cancel_and_change_order() {
+        let mut engine = engine();
+        let order_id = engine
+            .place_order(new_order(
+                Side::Buy,
+                ExecutionType::Limit,
+                "0.1",
+                Some(900_000),
+            ))
+            .unwrap();
+        assert_eq!(
+            engine.change_order(order_id, price("900000"), None),
+            Err(ApiErrorCode::SamePrice)
+        );
+        engine
+            .change_order(order_id, price("1000000"), None)
+            .unwrap();
+        assert_eq!(
+            engine.order(order_id).unwrap().status,
+            OrderStatus::Executed
+        );
+        assert_eq!(
+            engine.cancel_order(order_id),
+            Err(ApiErrorCode::OrderAlreadyChanged)
+        );
+        assert_eq!(engine.cancel_order(999), Err(ApiErrorCode::OrderNotFound));
+
+        let order_id = engine
+            .place_order(new_order(
+                Side::Sell,
+                ExecutionType::Stop,
+                "0.1",
+                Some(900_000),
+            ))
+            .unwrap();
+        assert_eq!(engine.order(order_id).unwrap().status, OrderStatus::Waiting);
+        engine.cancel_order(order_id).unwrap();
+        let order = engine.order(order_id).unwrap();
+        assert_eq!(order.status, OrderStatus::Canceled);
+        assert_eq!(order.cancel_type, Some(CANCEL_TYPE_USER));
+    }
+
+    #[test]
+    fn test_losscut() {
+        let mut engine = engine();
+        engine
+            .place_order(new_order(Side::Buy, ExecutionType::Market, "1", None))
+            .unwrap();
+        let position_id = engine.positions().next().unwrap().position_id;
+        let close_id = engine
+            .place_close_order(
+                new_order(Side::Sell, ExecutionType::Limit, "1", Some(2_000_000)),
+                vec![(position_id, size("1"))],
+            )
+            .unwrap();
+        move_to(&mut engine, 1_010_000);
+        assert_eq!(engine.positions().count(), 1);
+        move_to(&mut engine, 400_000);
+        assert_eq!(engine.positions().count(), 0);
+        let close = engine.order(close_id).unwrap();
+        assert_eq!(close.status, OrderStatus::Canceled);
+        assert_eq!(close.cancel_type, Some(CANCEL_TYPE_POSITION_LOSSCUTTED));
+        let losscut = engine.orders().last().unwrap();
+        assert_eq!(losscut.order_type, OrderType::Losscut);
+        assert_eq!(losscut.status, OrderStatus::Executed);
+        assert_eq!(engine.balance(), 400_000);
+    }
+
+    #[test]
+    fn test_insufficient_funds() {
+        let mut engine = engine();
+        assert_eq!(
+            engine.place_order(new_order(Side::Buy, ExecutionType::Market, "3", None)),
+            Err(ApiErrorCode::InsufficientFunds)
+        );
+    }
+
+    #[test]
+    fn test_fills_against_orderbook_with_fees() {
+        let mut engine = Engine::new(1_000_000, 2);
+        engine.set_fee_rates(-0.0001, 0.0005);
+        let quote = Quote {
+            bid: price("999000"),
+            ask: price("1000000"),
+            bids: vec![Level {
+                price: price("999000"),
+                size: size("1"),
+            }],
+            asks: vec![
+                Level {
+                    price: price("1000000"),
+                    size: size("0.1"),
+                },
+                Level {
+                    price: price("1001000"),
+                    size: size("0.1"),
+                },
+            ],
+        };
+        engine.set_quote(SYMBOL, quote.clone());
+        let order_id = engine
+            .place_order(new_order(Side::Buy, ExecutionType::Market, "0.3", None))
+            .unwrap();
+        let order = engine.order(order_id).unwrap();
+        assert_eq!(order.status, OrderStatus::Expired);
+        assert_eq!(order.executed_size, size("0.2"));
+        let executions = engine.executions();
+        assert_eq!(executions.len(), 2);
+        assert_eq!(executions[1].price, price("1001000"));
+        assert_eq!(executions[0].fee, 50);
+        assert_eq!(engine.balance(), 1_000_000 - 50 - 50);
+
+        let order_id = engine
+            .place_order(new_order(
+                Side::Buy,
+                ExecutionType::Limit,
+                "0.1",
+                Some(1_000_000),
+            ))
+            .unwrap();
+        assert_eq!(engine.order(order_id).unwrap().status, OrderStatus::Ordered);
+        engine.set_quote(SYMBOL, quote);
+        engine.update();
+        assert_eq!(
+            engine.order(order_id).unwrap().status,
+            OrderStatus::Executed
+        );
+        assert_eq!(engine.executions()[2].fee, -10);
+    }
+}
@@ -1,6 +1,10 @@
 //! 注文方法を定義する。
 
+use serde::{Deserialize, Serialize};
+
 /// 注文方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
 pub enum ExecutionType {
     /// 成行注文
     Market,
@@ -1,5 +1,6 @@
 //! HTTPのGET, POSTリクエスト時のヘッダーを作る処理を実装する。
 
+use crate::credentials::Credentials;
 use crate::error::Error;
 use crate::timestamp::get_timestamp;
 use hex::encode;
@@ -7,10 +8,49 @@ use ring::hmac;
 use serde_json::Value;
 use std::collections::{hash_map::Iter, HashMap};
 
-const GMO_COM_API_KEY_ENVNAME: &str = "GMO_COIN_API_KEY";
-const GMO_COM_API_SECRET_ENVNAME: &str = "GMO_COIN_API_SECRET";
+/// リクエストのヘッダー。Private APIのヘッダーは署名し直すための情報も持つ。
+#[derive(Clone)]
+pub struct Headers {
+    map: HashMap<String, String>,
+    signer: Option<Signer>,
+}
+
+/// 署名に使った情報。再試行するときに新しいタイムスタンプで署名し直すのに使う。
+#[derive(Clone)]
+struct Signer {
+    credentials: Credentials,
+    method: &'static str,
+    path: String,
+    body: Option<String>,
+}
 
-pub struct Headers(HashMap<String, String>);
+impl Signer {
+    /// 現在のタイムスタンプで署名したヘッダーを作る。
+    fn sign(self) -> Headers {
+        let timestamp = get_timestamp();
+        let text = format!(
+            "{}{}{}{}",
+            timestamp,
+            self.method,
+            self.path,
+            self.body.as_deref().unwrap_or("")
+        );
+        let api_key = self.credentials.api_key().to_string();
+        let sign = Headers::sign(&self.credentials, &text);
+
+        let mut headers: HashMap<String, String> = HashMap::new();
+        if self.body.is_some() {
+            headers.insert(String::from("content-type"), "application/json".to_string());
+        }
+        headers.insert(String::from("API-KEY"), api_key);
+        headers.insert(String::from("API-TIMESTAMP"), timestamp.to_string());
+        headers.insert(String::from("API-SIGN"), sign);
+        Headers {
+            map: headers,
+            signer: Some(self),
+        }
+    }
+}
 
 impl<'a> IntoIterator for &'a Headers {
     type Item = (&'a String, &'a String);
@@ -18,58 +58,87 @@ impl<'a> IntoIterator for &'a Headers {
 
     #[inline]
     fn into_iter(self) -> Iter<'a, String, String> {
-        self.0.iter()
+        self.map.iter()
     }
 }
 
 impl Headers {
     /// 空のヘッダーを作る。
     pub(crate) fn create_empty_headers() -> Headers {
-        let headers: HashMap<String, String> = HashMap::new();
-        Headers(headers)
+        Headers {
+            map: HashMap::new(),
+            signer: None,
+        }
     }
 
-    fn api_key() -> Result<String, Error> {
-        Ok(std::env::var(GMO_COM_API_KEY_ENVNAME)?)
+    /// 現在のタイムスタンプで署名し直したヘッダーを作る。署名していないヘッダーはそのまま複製する。
+    /// 再試行するときやレートリミッターで待ったときに、API-TIMESTAMPとAPI-SIGNが古くなって認証に失敗しないようにするために使う。
+    pub fn resign(&self) -> Headers {
+        match &self.signer {
+            Some(signer) => signer.clone().sign(),
+            None => self.clone(),
+        }
     }
 
-    fn api_secret() -> Result<String, Error> {
-        Ok(std::env::var(GMO_COM_API_SECRET_ENVNAME)?)
+    fn sign(credentials: &Credentials, text: &str) -> String {
+        let signed_key = hmac::Key::new(hmac::HMAC_SHA256, credentials.api_secret().as_bytes());
+        encode(hmac::sign(&signed_key, text.as_bytes()).as_ref())
     }
 
-    fn sign(text: &str) -> Result<String, Error> {
-        let secret_key = Headers::api_secret()?;
-        let signed_key = hmac::Key::new(hmac::HMAC_SHA256, secret_key.as_bytes());
-        let sign = encode(hmac::sign(&signed_key, text.as_bytes()).as_ref());
-        Ok(sign)
+    /// GETリクエスト時のヘッダーを作る。
+    pub(crate) fn create_get_headers(
+        credentials: &Credentials,
+        path: &str,
+    ) -> Result<Headers, Error> {
+        let signer = Signer {
+            credentials: credentials.clone(),
+            method: "GET",
+            path: path.to_string(),
+            body: None,
+        };
+        Ok(signer.sign())
     }
 
-    /// GETリクエスト時のヘッダーを作る。
-    pub(crate) fn create_get_headers(path: &str) -> Result<Headers, Error> {
-        let timestamp = get_timestamp();
-        let text = format!("{}{}{}", timestamp, "GET", path);
-        let api_key = Headers::api_key()?;
-        let sign = Headers::sign(&text)?;
+    /// POSTリクエスト時のヘッダーを作る。
+    pub(crate) fn create_post_headers(
+        credentials: &Credentials,
+        path: &str,
+        parameters: &Value,
+    ) -> Result<Headers, Error> {
+        Headers::create_headers_with_body(credentials, "POST", path, parameters)
+    }
 
-        let mut headers: HashMap<String, String> = HashMap::new();
-        headers.insert(String::from("API-KEY"), api_key);
-        headers.insert(String::from("API-TIMESTAMP"), timestamp.to_string());
-        headers.insert(String::from("API-SIGN"), sign);
-        Ok(Headers(headers))
+    /// PUTリクエスト時のヘッダーを作る。
+    pub(crate) fn create_put_headers(
+        credentials: &Credentials,
+        path: &str,
+        parameters: &Value,
+    ) -> Result<Headers, Error> {
+        Headers::create_headers_with_body(credentials, "PUT", path, parameters)
     }
 
-    /// POSTリクエスト時のヘッダーを作る。
-    pub(crate) fn create_post_headers(path: &str, parameters: &Value) -> Result<Headers, Error> {
-        let timestamp = get_timestamp();
-        let text = format!("{}{}{}{}", timestamp, "POST", path, &parameters);
-        let api_key = Headers::api_key()?;
-        let sign = Headers::sign(&text)?;
+    /// DELETEリクエスト時のヘッダーを作る。
+    pub(crate) fn create_delete_headers(
+        credentials: &Credentials,
+        path: &str,
+        parameters: &Value,
+    ) -> Result<Headers, Error> {
+        Headers::create_headers_with_body(credentials, "DELETE", path, parameters)
+    }
 
-        let mut headers: HashMap<String, String> = HashMap::new();
-        headers.insert(String::from("content-type"), "application/json".to_string());
-        headers.insert(String::from("API-KEY"), api_key);
-        headers.insert(String::from("API-TIMESTAMP"), timestamp.to_string());
-        headers.insert(String::from("API-SIGN"), sign);
-        Ok(Headers(headers))
+    /// ボディを持つリクエスト時のヘッダーを作る。
+    fn create_headers_with_body(
+        credentials: &Credentials,
+        method: &'static str,
+        path: &str,
+        parameters: &Value,
+    ) -> Result<Headers, Error> {
+        let signer = Signer {
+            credentials: credentials.clone(),
+            method,
+            path: path.to_string(),
+            body: Some(parameters.to_string()),
+        };
+        Ok(signer.sign())
     }
 }
@@ -1,10 +1,13 @@
 //! HTTPクライアントを定義する。
 
+use crate::client_config::ClientConfig;
 use crate::error::*;
 use crate::headers::Headers;
 use crate::response::*;
 use async_trait::async_trait;
 use serde_json::Value;
+use std::future::Future;
+use std::time::Duration;
 
 /// HTTPクライアントのtrait。GET, POSTとか。
 #[async_trait]
@@ -16,16 +19,228 @@ pub trait HttpClient {
         headers: &Headers,
         parameters: &Value,
     ) -> Result<RawResponse, Error>;
+    async fn put(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error>;
+    async fn delete(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error>;
 }
 
 /// ネットワークアクセス時に用いるHttpクライアント。
 /// Rustではreqwestがデファクトっぽいのでネットワークアクセスするときはreqwestを使う。
-pub struct Reqwest;
+/// 作成したときの`reqwest::Client`を使い回すので、接続プールのおかげで2回目以降のリクエストではTCP接続とTLSハンドシェイクを省ける。
+/// クローンしても同じ接続プールを共有するので、tokioのタスク間で安く共有できる。
+#[derive(Clone)]
+pub struct Reqwest {
+    client: reqwest::Client,
+}
+
+impl Default for Reqwest {
+    fn default() -> Self {
+        Reqwest::new()
+    }
+}
+
+impl Reqwest {
+    /// デフォルトの設定でHTTPクライアントを作成する。TLSの初期化に失敗した場合はパニックする。
+    pub fn new() -> Reqwest {
+        Reqwest {
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// 接続の設定を指定してHTTPクライアントを作成するビルダーを取得する。
+    pub fn builder() -> ReqwestBuilder {
+        ReqwestBuilder::default()
+    }
+
+    /// 設定済みの`reqwest::Client`からHTTPクライアントを作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - リクエストを送るのに使うクライアント。
+    ///
+    pub fn from_client(client: reqwest::Client) -> Reqwest {
+        Reqwest { client }
+    }
+
+    /// リクエストを送るのに使う`reqwest::Client`を取得する。
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+}
+
+/// `Reqwest`の接続の設定を指定するビルダー。指定しなかった設定はreqwestのデフォルトを使う。
+#[derive(Clone, Debug, Default)]
+pub struct ReqwestBuilder {
+    /// 使っていない接続を接続プールに残しておく時間。
+    pool_idle_timeout: Option<Duration>,
+
+    /// ホストごとに接続プールに残しておく使っていない接続の数の上限。
+    pool_max_idle_per_host: Option<usize>,
+
+    /// TCPのキープアライブの間隔。
+    tcp_keepalive: Option<Duration>,
+
+    /// TCP_NODELAYを有効にするか？
+    tcp_nodelay: Option<bool>,
+
+    /// ALPNでネゴシエーションせずにHTTP/2で接続するか？
+    http2_prior_knowledge: bool,
+
+    /// リクエストを送ってからレスポンスを受け取り終わるまでのタイムアウト。
+    timeout: Option<Duration>,
+
+    /// 接続のタイムアウト。
+    connect_timeout: Option<Duration>,
+
+    /// すべてのリクエストに使うプロキシのURL。
+    proxy: Option<String>,
+
+    /// User-Agentヘッダーの値。
+    user_agent: Option<String>,
+}
+
+impl ReqwestBuilder {
+    /// 使っていない接続を接続プールに残しておく時間を指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - 残しておく時間。
+    ///
+    pub fn with_pool_idle_timeout(mut self, timeout: Duration) -> ReqwestBuilder {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// ホストごとに接続プールに残しておく使っていない接続の数の上限を指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `max` - 接続の数の上限。
+    ///
+    pub fn with_pool_max_idle_per_host(mut self, max: usize) -> ReqwestBuilder {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// TCPのキープアライブを有効にする。
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - キープアライブの間隔。
+    ///
+    pub fn with_tcp_keepalive(mut self, interval: Duration) -> ReqwestBuilder {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// TCP_NODELAYを有効にするかを指定する。reqwestのデフォルトでは有効。
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - 有効にする場合は`true`。
+    ///
+    pub fn with_tcp_nodelay(mut self, enabled: bool) -> ReqwestBuilder {
+        self.tcp_nodelay = Some(enabled);
+        self
+    }
+
+    /// ALPNでネゴシエーションせずにHTTP/2で接続する。
+    pub fn with_http2_prior_knowledge(mut self) -> ReqwestBuilder {
+        self.http2_prior_knowledge = true;
+        self
+    }
+
+    /// リクエストを送ってからレスポンスを受け取り終わるまでのタイムアウトを指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - タイムアウト。
+    ///
+    pub fn with_timeout(mut self, timeout: Duration) -> ReqwestBuilder {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// 接続のタイムアウトを指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - タイムアウト。
+    ///
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> ReqwestBuilder {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// すべてのリクエストに使うプロキシを指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - プロキシのURL。例: `http://localhost:3128`
+    ///
+    pub fn with_proxy(mut self, url: impl Into<String>) -> ReqwestBuilder {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    /// User-Agentヘッダーの値を指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `user_agent` - User-Agentヘッダーの値。
+    ///
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> ReqwestBuilder {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// HTTPクライアントを作成する。プロキシのURLが不正な場合やTLSの初期化に失敗した場合はエラーを返す。
+    pub fn build(self) -> Result<Reqwest, Error> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        if let Some(enabled) = self.tcp_nodelay {
+            builder = builder.tcp_nodelay_(enabled);
+        }
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(url) = self.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(&url)?);
+        }
+        if let Some(user_agent) = self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        Ok(Reqwest::from_client(builder.build()?))
+    }
+}
 
 #[async_trait]
 impl HttpClient for Reqwest {
     async fn get(&self, url: String, headers: &Headers) -> Result<RawResponse, Error> {
-        let mut request_builder = reqwest::Client::new().get(reqwest::Url::parse(&url)?);
+        let mut request_builder = self.client.get(reqwest::Url::parse(&url)?);
         for (key, value) in headers {
             request_builder = request_builder.header(key, value);
         }
@@ -43,17 +258,61 @@ impl HttpClient for Reqwest {
         headers: &Headers,
         parameters: &Value,
     ) -> Result<RawResponse, Error> {
-        let mut request_builder = reqwest::Client::new().post(reqwest::Url::parse(&url)?);
-        for (key, value) in headers {
-            request_builder = request_builder.header(key, value);
-        }
+        let request_builder = self.client.post(reqwest::Url::parse(&url)?);
+        send_with_body(request_builder, headers, parameters).await
+    }
 
-        let request_builder = request_builder.json(&parameters);
-        let response = request_builder.send().await?;
-        Ok(RawResponse {
-            http_status_code: (response.status().as_u16()),
-            body_text: (response.text().await?),
-        })
+    async fn put(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        let request_builder = self.client.put(reqwest::Url::parse(&url)?);
+        send_with_body(request_builder, headers, parameters).await
+    }
+
+    async fn delete(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        let request_builder = self.client.delete(reqwest::Url::parse(&url)?);
+        send_with_body(request_builder, headers, parameters).await
+    }
+}
+
+/// ヘッダーとJSONのボディを付けてリクエストを送る。
+async fn send_with_body(
+    request_builder: reqwest::RequestBuilder,
+    headers: &Headers,
+    parameters: &Value,
+) -> Result<RawResponse, Error> {
+    let mut request_builder = request_builder;
+    for (key, value) in headers {
+        request_builder = request_builder.header(key, value);
+    }
+
+    let request_builder = request_builder.json(&parameters);
+    let response = request_builder.send().await?;
+    Ok(RawResponse {
+        http_status_code: (response.status().as_u16()),
+        body_text: (response.text().await?),
+    })
+}
+
+/// `config`にタイムアウトが設定されていれば、その時間までにレスポンスが返ってこない場合にエラーを返す。
+pub(crate) async fn with_timeout(
+    config: &ClientConfig,
+    request: impl Future<Output = Result<RawResponse, Error>>,
+) -> Result<RawResponse, Error> {
+    match config.timeout() {
+        Some(timeout) => match tokio::time::timeout(timeout, request).await {
+            Ok(response) => response,
+            Err(_) => Err(Error::TimeoutError()),
+        },
+        None => request.await,
     }
 }
 
@@ -62,6 +321,7 @@ pub(crate) mod tests {
     use super::*;
 
     /// 単体テスト用のHttpクライアント。
+    #[derive(Clone)]
     pub(crate) struct InmemClient {
         pub(crate) http_status_code: u16,
         pub(crate) body_text: String,
@@ -95,5 +355,63 @@ pub(crate) mod tests {
         ) -> Result<RawResponse, Error> {
             self.return_result().await
         }
+
+        async fn put(
+            &self,
+            _url: String,
+            _headers: &Headers,
+            _parameters: &Value,
+        ) -> Result<RawResponse, Error> {
+            self.return_result().await
+        }
+
+        async fn delete(
+            &self,
+            _url: String,
+            _headers: &Headers,
+            _parameters: &Value,
+        ) -> Result<RawResponse, Error> {
+            self.return_result().await
+        }
+    }
+
+    #[test]
+    fn test_reqwest_builder() {
+        let http_client = Reqwest::builder()
+            .with_pool_idle_timeout(Duration::from_secs(90))
+            .with_pool_max_idle_per_host(4)
+            .with_tcp_keepalive(Duration::from_secs(30))
+            .with_tcp_nodelay(true)
+            .with_timeout(Duration::from_secs(5))
+            .with_connect_timeout(Duration::from_secs(1))
+            .with_proxy("http://localhost:3128")
+            .with_user_agent("gmo-coin-rs")
+            .build();
+        assert!(http_client.is_ok());
+        let http_client = Reqwest::builder().with_proxy("not a url").build();
+        assert!(matches!(http_client, Err(Error::ReqwestError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_with_timeout() {
+        let http_client = InmemClient {
+            http_status_code: 200,
+            body_text: "{}".to_string(),
+            return_error: false,
+        };
+        let config = ClientConfig::default().with_timeout(std::time::Duration::from_secs(1));
+        let headers = Headers::create_empty_headers();
+        let resp = with_timeout(&config, http_client.get("".to_string(), &headers))
+            .await
+            .unwrap();
+        assert_eq!(resp.http_status_code, 200);
+
+        let config = ClientConfig::default().with_timeout(std::time::Duration::from_millis(10));
+        let slow_request = async {
+            tokio::time::delay_for(std::time::Duration::from_secs(1)).await;
+            http_client.get("".to_string(), &headers).await
+        };
+        let resp = with_timeout(&config, slow_request).await;
+        assert!(matches!(resp, Err(Error::TimeoutError())));
     }
 }
@@ -0,0 +1,114 @@
+//! 注文ID、約定ID、建玉IDを表す型を定義する。
+//! どれも中身は文字列だが、型を分けて注文IDを渡すべきところに建玉IDを渡すような間違いを防ぐ。
+
+use crate::error::Error;
+use crate::json::{id_to_num, id_to_str};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+macro_rules! define_id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        pub struct $name(String);
+
+        impl $name {
+            /// IDの文字列から作成する。
+            ///
+            /// # Arguments
+            ///
+            /// * `id` - IDの文字列。
+            ///
+            pub fn new(id: impl Into<String>) -> $name {
+                $name(id.into())
+            }
+
+            /// IDの文字列を取得する。
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            /// 数値に変換する。リクエストのボディに含めるときに使う。
+            pub fn to_num(&self) -> Result<i64, Error> {
+                id_to_num(&self.0)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(id: &str) -> $name {
+                $name::new(id)
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(id: String) -> $name {
+                $name(id)
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&self.0)
+            }
+        }
+
+        /// 数値でも文字列でも受け付ける。
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$name, D::Error> {
+                id_to_str(deserializer).map($name)
+            }
+        }
+    };
+}
+
+define_id!(
+    /// 注文ID。
+    OrderId
+);
+
+define_id!(
+    /// 約定ID。
+    ExecutionId
+);
+
+define_id!(
+    /// 建玉ID。
+    PositionId
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_deserialize_from_number_or_string() {
+        let ids: Vec<OrderId> = serde_json::from_str(r#"[123456789012, "123456789013"]"#).unwrap();
+        assert_eq!(ids[0], "123456789012");
+        assert_eq!(ids[1].as_str(), "123456789013");
+        assert_eq!(ids[0].to_num().unwrap(), 123_456_789_012);
+        assert_eq!(ids[1].to_string(), "123456789013");
+        assert_eq!(serde_json::to_string(&ids[0]).unwrap(), r#""123456789012""#);
+        let set: HashSet<OrderId> = ids.into_iter().collect();
+        assert!(set.contains(&OrderId::new("123456789012")));
+        assert!(PositionId::new("abc").to_num().is_err());
+    }
+}
@@ -1,19 +1,40 @@
 //! JSON文字列をパースするときに型変換行うための関数を定義する。
 
+use crate::client_config::ClientConfig;
 use crate::error::Error;
+use crate::price::Price;
 use crate::response::{ErrorResponse, RawResponse, RestResponse};
-use chrono::{DateTime, Utc};
+use crate::size::Size;
+use chrono::{DateTime, LocalResult, TimeZone, Utc};
 use serde::{de, Deserialize, Deserializer};
 use serde_json::Value;
+use std::collections::HashMap;
 
 /// strからf64への変換を行う。
 pub(crate) fn str_to_f64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
     Ok(match Value::deserialize(deserializer)? {
         Value::String(s) => s.parse().map_err(de::Error::custom)?,
-        Value::Number(num) => {
-            num.as_f64()
-                .ok_or_else(|| de::Error::custom("Invalid number"))? as f64
-        }
+        Value::Number(num) => num
+            .as_f64()
+            .ok_or_else(|| de::Error::custom("Invalid number"))?,
+        _ => return Err(de::Error::custom("wrong type")),
+    })
+}
+
+/// strから`Size`への変換を行う。`decimal`フィーチャーが有効な場合も文字列から誤差なく変換する。
+pub(crate) fn str_to_size<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Size, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(s) => s.parse().map_err(de::Error::custom)?,
+        Value::Number(num) => num.to_string().parse().map_err(de::Error::custom)?,
+        _ => return Err(de::Error::custom("wrong type")),
+    })
+}
+
+/// strから`Price`への変換を行う。小数を含む価格も誤差なく変換する。
+pub(crate) fn str_to_price<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Price, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(s) => s.parse().map_err(de::Error::custom)?,
+        Value::Number(num) => num.to_string().parse().map_err(de::Error::custom)?,
         _ => return Err(de::Error::custom("wrong type")),
     })
 }
@@ -22,10 +43,9 @@ pub(crate) fn str_to_f64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f
 pub(crate) fn str_to_i64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
     Ok(match Value::deserialize(deserializer)? {
         Value::String(s) => s.parse().map_err(de::Error::custom)?,
-        Value::Number(num) => {
-            num.as_i64()
-                .ok_or_else(|| de::Error::custom("Invalid number"))? as i64
-        }
+        Value::Number(num) => num
+            .as_i64()
+            .ok_or_else(|| de::Error::custom("Invalid number"))?,
         _ => return Err(de::Error::custom("wrong type")),
     })
 }
@@ -42,34 +62,13 @@ pub(crate) fn id_to_str<'de, D: Deserializer<'de>>(deserializer: D) -> Result<St
 }
 
 /// Idを数値に変換する。
-pub(crate) fn id_to_num(id: &str) -> Result<i32, Error> {
-    Ok(match id.parse::<i32>() {
+pub(crate) fn id_to_num(id: &str) -> Result<i64, Error> {
+    Ok(match id.parse::<i64>() {
         Ok(n) => n,
         Err(_) => return Err(Error::IdToNumberError(id.to_string())),
     })
 }
 
-/// 複数のIdが格納された配列を要素が文字列となるようにに変換する。
-pub(crate) fn ids_to_strvec<'de, D: Deserializer<'de>>(
-    deserializer: D,
-) -> Result<Vec<String>, D::Error> {
-    let mut strvec = Vec::<String>::new();
-    match Value::deserialize(deserializer)? {
-        Value::Array(array) => {
-            for v in array {
-                let id = match v {
-                    Value::String(s) => s,
-                    Value::Number(n) => n.to_string(),
-                    _ => return Err(de::Error::custom("wrong type")),
-                };
-                strvec.push(id);
-            }
-        }
-        _ => return Err(de::Error::custom("wrong type")),
-    }
-    Ok(strvec)
-}
-
 /// GMOコインAPIから返ってくるタイムスタンプをchronoの日時に変換する。
 /// GMOコインのタイムスタンプはUTC。この関数でもUTCの日時を返す。
 pub(crate) fn gmo_timestamp_to_chrono_timestamp<'de, D: Deserializer<'de>>(
@@ -78,35 +77,130 @@ pub(crate) fn gmo_timestamp_to_chrono_timestamp<'de, D: Deserializer<'de>>(
     let s: String = Deserialize::deserialize(deserializer)?;
     Ok(
         match chrono::naive::NaiveDateTime::parse_from_str(&s, "%Y-%m-%dT%H:%M:%S.%3fZ") {
-            Ok(date) => DateTime::<Utc>::from_utc(date, Utc),
+            Ok(date) => Utc.from_utc_datetime(&date),
             Err(_) => return Err(de::Error::custom("wrong datetime format")),
         },
     )
 }
 
+/// Unixエポックからの経過ミリ秒を表す文字列をchronoの日時に変換する。
+pub(crate) fn unix_millis_to_chrono_timestamp<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<DateTime<Utc>, D::Error> {
+    let millis = str_to_i64(deserializer)?;
+    Ok(match Utc.timestamp_millis_opt(millis) {
+        LocalResult::Single(date) => date,
+        _ => return Err(de::Error::custom("wrong unix timestamp")),
+    })
+}
+
 /// GMOコインのAPIを呼び出して得られるHTTPレスポンスをええ感じに構造体RestResponse<T>に詰めなおす
+/// 構造体Tにもエラーレスポンスにもバインディングできない場合は、元のボディと両方のエラーを`Error::ResponseParseError`に詰めて返す。
 pub(crate) fn parse_from_http_response<'a, T>(
     http_response: &'a RawResponse,
+    path: &str,
+    config: &ClientConfig,
 ) -> Result<RestResponse<T>, Error>
 where
     T: serde::de::Deserialize<'a>,
 {
-    let body: Result<T, serde_json::Error> = serde_json::from_str(&http_response.body_text);
+    let mut unknown_fields = HashMap::new();
+    let body: Result<T, serde_json::Error> = if config.collect_unknown_fields() {
+        parse_collecting_unknown_fields(&http_response.body_text, &mut unknown_fields)
+    } else {
+        serde_json::from_str(&http_response.body_text)
+    };
     Ok(match body {
         Ok(b) => RestResponse {
             http_status_code: http_response.http_status_code,
             body: b,
+            unknown_fields,
         },
-        Err(_) => {
-            let err_resp: ErrorResponse = serde_json::from_str(&http_response.body_text)?;
-            return Err(Error::APIError(err_resp));
+        Err(body_error) => {
+            return Err(
+                match serde_json::from_str::<ErrorResponse>(&http_response.body_text) {
+                    Ok(err_resp) => Error::from(err_resp),
+                    Err(error_response_error) => Error::ResponseParseError {
+                        http_status_code: http_response.http_status_code,
+                        body_text: http_response.body_text.clone(),
+                        path: path.to_string(),
+                        body_error,
+                        error_response_error,
+                    },
+                },
+            );
         }
     })
 }
 
+/// 構造体Tにバインディングしつつ、Tが知らないフィールドを`unknown_fields`に詰める。キーはJSON Pointer(例: `/data/list/0/foo`)。
+fn parse_collecting_unknown_fields<'a, T>(
+    text: &'a str,
+    unknown_fields: &mut HashMap<String, Value>,
+) -> Result<T, serde_json::Error>
+where
+    T: serde::de::Deserialize<'a>,
+{
+    let mut pointers = Vec::new();
+    let mut deserializer = serde_json::Deserializer::from_str(text);
+    let body = serde_ignored::deserialize(&mut deserializer, |path| {
+        pointers.push(to_json_pointer(&path))
+    })?;
+    if !pointers.is_empty() {
+        let raw: Value = serde_json::from_str(text)?;
+        for pointer in pointers {
+            if let Some(value) = raw.pointer(&pointer) {
+                unknown_fields.insert(pointer, value.clone());
+            }
+        }
+    }
+    Ok(body)
+}
+
+/// serde_ignoredのパスをJSON Pointerに変換する。
+fn to_json_pointer(path: &serde_ignored::Path) -> String {
+    match path {
+        serde_ignored::Path::Root => String::new(),
+        serde_ignored::Path::Seq { parent, index } => {
+            format!("{}/{}", to_json_pointer(parent), index)
+        }
+        serde_ignored::Path::Map { parent, key } => format!(
+            "{}/{}",
+            to_json_pointer(parent),
+            key.replace('~', "~0").replace('/', "~1")
+        ),
+        serde_ignored::Path::Some { parent }
+        | serde_ignored::Path::NewtypeStruct { parent }
+        | serde_ignored::Path::NewtypeVariant { parent } => to_json_pointer(parent),
+    }
+}
+
+/// WebSocket APIから返ってくるエラーメッセージ。
+#[derive(Deserialize)]
+struct WebSocketErrorMessage {
+    error: String,
+}
+
+/// GMOコインのWebSocket APIから受け取ったテキストメッセージを構造体Tに詰めなおす。
+pub(crate) fn parse_from_web_socket_message<'a, T>(text: &'a str) -> Result<T, Error>
+where
+    T: serde::de::Deserialize<'a>,
+{
+    let message: Result<T, serde_json::Error> = serde_json::from_str(text);
+    Ok(match message {
+        Ok(m) => m,
+        Err(e) => match serde_json::from_str::<WebSocketErrorMessage>(text) {
+            Ok(err_msg) => return Err(Error::WebSocketAPIError(err_msg.error)),
+            Err(_) => return Err(Error::SerdeJsonError(e)),
+        },
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::json::{gmo_timestamp_to_chrono_timestamp, str_to_f64, str_to_i64};
+    use crate::json::{
+        gmo_timestamp_to_chrono_timestamp, str_to_f64, str_to_i64, unix_millis_to_chrono_timestamp,
+    };
     use chrono::*;
     use serde::Deserialize;
 
@@ -125,10 +219,16 @@ mod tests {
         d: DateTime<Utc>,
     }
 
+    #[derive(Deserialize)]
+    struct UnixDate {
+        #[serde(deserialize_with = "unix_millis_to_chrono_timestamp")]
+        d: DateTime<Utc>,
+    }
+
     #[test]
     fn test_str_to_numbers() {
         let json_str = r#"{"i": "100", "f": "-10.55"}"#;
-        let json: Number = serde_json::from_str(&json_str).unwrap();
+        let json: Number = serde_json::from_str(json_str).unwrap();
         assert_eq!(json.i, 100);
         assert_eq!(json.f, -10.55);
     }
@@ -136,7 +236,7 @@ mod tests {
     #[test]
     fn test_str_to_datetime() {
         let json_str = r#"{"d": "2019-03-19T02:15:06.001Z"}"#;
-        let json: Date = serde_json::from_str(&json_str).unwrap();
+        let json: Date = serde_json::from_str(json_str).unwrap();
         assert_eq!(json.d.year(), 2019);
         assert_eq!(json.d.month(), 3);
         assert_eq!(json.d.day(), 19);
@@ -145,4 +245,15 @@ mod tests {
         assert_eq!(json.d.second(), 6);
         assert_eq!(json.d.timestamp_subsec_millis(), 1);
     }
+
+    #[test]
+    fn test_unix_millis_to_datetime() {
+        let json_str = r#"{"d": "1618588800001"}"#;
+        let json: UnixDate = serde_json::from_str(json_str).unwrap();
+        assert_eq!(json.d.year(), 2021);
+        assert_eq!(json.d.month(), 4);
+        assert_eq!(json.d.day(), 16);
+        assert_eq!(json.d.hour(), 16);
+        assert_eq!(json.d.timestamp_subsec_millis(), 1);
+    }
 }
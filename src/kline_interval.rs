@@ -0,0 +1,107 @@
+//! ローソク足の時間足を定義する。
+
+/// ローソク足の時間足。
+#[derive(Copy, Clone)]
+pub enum KlineInterval {
+    /// 1分足。
+    Min1,
+
+    /// 5分足。
+    Min5,
+
+    /// 10分足。
+    Min10,
+
+    /// 15分足。
+    Min15,
+
+    /// 30分足。
+    Min30,
+
+    /// 1時間足。
+    Hour1,
+
+    /// 4時間足。
+    Hour4,
+
+    /// 8時間足。
+    Hour8,
+
+    /// 12時間足。
+    Hour12,
+
+    /// 日足。
+    Day1,
+
+    /// 週足。
+    Week1,
+
+    /// 月足。
+    Month1,
+}
+
+pub const MIN1: &str = "1min";
+pub const MIN5: &str = "5min";
+pub const MIN10: &str = "10min";
+pub const MIN15: &str = "15min";
+pub const MIN30: &str = "30min";
+pub const HOUR1: &str = "1hour";
+pub const HOUR4: &str = "4hour";
+pub const HOUR8: &str = "8hour";
+pub const HOUR12: &str = "12hour";
+pub const DAY1: &str = "1day";
+pub const WEEK1: &str = "1week";
+pub const MONTH1: &str = "1month";
+
+impl KlineInterval {
+    /// 時間足を文字列に変換する。
+    pub fn to_string(&self) -> &str {
+        match self {
+            KlineInterval::Min1 => MIN1,
+            KlineInterval::Min5 => MIN5,
+            KlineInterval::Min10 => MIN10,
+            KlineInterval::Min15 => MIN15,
+            KlineInterval::Min30 => MIN30,
+            KlineInterval::Hour1 => HOUR1,
+            KlineInterval::Hour4 => HOUR4,
+            KlineInterval::Hour8 => HOUR8,
+            KlineInterval::Hour12 => HOUR12,
+            KlineInterval::Day1 => DAY1,
+            KlineInterval::Week1 => WEEK1,
+            KlineInterval::Month1 => MONTH1,
+        }
+    }
+
+    /// 1回のAPI呼び出しで1日分のローソク足を取得する時間足か？
+    /// 1時間足以下は日付(YYYYMMDD)単位、4時間足以上は年(YYYY)単位で取得する。
+    pub fn is_daily(&self) -> bool {
+        matches!(
+            self,
+            KlineInterval::Min1
+                | KlineInterval::Min5
+                | KlineInterval::Min10
+                | KlineInterval::Min15
+                | KlineInterval::Min30
+                | KlineInterval::Hour1
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_string() {
+        assert_eq!(KlineInterval::Min1.to_string(), MIN1);
+        assert_eq!(KlineInterval::Hour12.to_string(), HOUR12);
+        assert_eq!(KlineInterval::Month1.to_string(), MONTH1);
+    }
+
+    #[test]
+    fn test_is_daily() {
+        assert!(KlineInterval::Hour1.is_daily());
+        assert!(!KlineInterval::Hour4.is_daily());
+        assert!(!KlineInterval::Day1.is_daily());
+    }
+}
@@ -2,18 +2,48 @@
 
 #![crate_name = "gmo_coin_rs"]
 
+pub mod api_error_code;
+#[cfg(any(test, feature = "backtest"))]
+pub mod backtest;
+mod batch;
+pub mod cassette;
+pub mod client_config;
+pub mod close_policy;
+pub mod credentials;
 pub mod dto;
 pub mod end_point;
 pub mod error;
+#[cfg(any(test, feature = "simulator", feature = "paper", feature = "backtest"))]
+mod exchange;
 pub mod execution_type;
 pub mod headers;
 pub mod http_client;
+pub mod id;
 mod json;
+pub mod kline_interval;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod order_builder;
+pub mod order_status;
+pub mod order_type;
+pub mod order_validator;
+pub mod pagination;
+#[cfg(any(test, feature = "paper"))]
+pub mod paper;
+pub mod price;
 pub mod private;
+pub mod private_ws;
 pub mod public;
+pub mod public_ws;
+pub mod rate_limiter;
 pub mod response;
+pub mod retry;
 pub mod settle_type;
 pub mod side;
+#[cfg(any(test, feature = "simulator"))]
+pub mod simulator;
+pub mod size;
 pub mod symbol;
 pub mod time_in_force;
 mod timestamp;
+mod web_socket;
@@ -0,0 +1,320 @@
+//! 下流のクレートの単体テストで使うモックのHTTPクライアントを実装する。`mock`フィーチャーを有効にすると使える。
+
+use crate::error::Error;
+use crate::headers::Headers;
+use crate::http_client::HttpClient;
+use crate::response::RawResponse;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// モックが受け取ったリクエスト。
+#[derive(Clone, Debug)]
+pub struct MockRequest {
+    /// HTTPメソッド。
+    pub method: String,
+
+    /// リクエスト先のURL。
+    pub url: String,
+
+    /// URLのパス。例: `/public/v1/ticker`
+    pub path: String,
+
+    /// リクエストヘッダー。
+    pub headers: HashMap<String, String>,
+
+    /// リクエストのボディ。GETリクエストの場合は`None`。
+    pub body: Option<Value>,
+}
+
+/// リクエストからレスポンスを作る関数。
+type Responder = Box<dyn Fn(&MockRequest) -> Result<RawResponse, Error> + Send + Sync>;
+
+/// メソッドとパスのパターンに対応するレスポンス。
+struct Route {
+    method: String,
+    pattern: String,
+    responder: Responder,
+}
+
+/// メソッドとパスのパターンごとにレスポンスを登録できるモックのHTTPクライアント。
+/// 受け取ったリクエストはすべて記録するので、送ったURL、ヘッダー、ボディをテストで検証できる。
+///
+/// パスのパターンはURLのパスのうち、いずれかの`/`から後ろと比べる。`*`は任意の文字列に一致する。
+/// 例えば`/v1/ticker`はPublic APIのベースURLに関係なく`https://api.coin.z.com/public/v1/ticker`に一致する。
+/// 複数のパターンに一致する場合は先に登録したものを使う。
+#[derive(Default)]
+pub struct MockHttpClient {
+    routes: Vec<Route>,
+    calls: Mutex<Vec<MockRequest>>,
+}
+
+impl MockHttpClient {
+    /// レスポンスを何も登録していないモックを作成する。
+    pub fn new() -> MockHttpClient {
+        MockHttpClient::default()
+    }
+
+    /// 決まったレスポンスを登録する。
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - HTTPメソッド。例: `GET`
+    /// * `pattern` - パスのパターン。例: `/v1/ticker`
+    /// * `http_status_code` - 返すHTTPステータスコード。
+    /// * `body_text` - 返すボディ。
+    ///
+    pub fn on(
+        self,
+        method: &str,
+        pattern: &str,
+        http_status_code: u16,
+        body_text: impl Into<String>,
+    ) -> MockHttpClient {
+        let body_text = body_text.into();
+        self.on_fn(method, pattern, move |_| {
+            Ok(RawResponse {
+                http_status_code,
+                body_text: body_text.clone(),
+            })
+        })
+    }
+
+    /// リクエストからレスポンスを作る関数を登録する。エラーを返すと通信エラーが起きたことにできる。
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - HTTPメソッド。例: `POST`
+    /// * `pattern` - パスのパターン。例: `/v1/order`
+    /// * `responder` - リクエストを受け取ってレスポンスを返す関数。
+    ///
+    pub fn on_fn(
+        mut self,
+        method: &str,
+        pattern: &str,
+        responder: impl Fn(&MockRequest) -> Result<RawResponse, Error> + Send + Sync + 'static,
+    ) -> MockHttpClient {
+        self.routes.push(Route {
+            method: method.to_uppercase(),
+            pattern: pattern.to_string(),
+            responder: Box::new(responder),
+        });
+        self
+    }
+
+    /// これまでに受け取ったリクエストを受け取った順に返す。
+    pub fn calls(&self) -> Vec<MockRequest> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// これまでに受け取ったリクエストのうち、メソッドとパスのパターンに一致するものを返す。
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - HTTPメソッド。
+    /// * `pattern` - パスのパターン。
+    ///
+    pub fn calls_to(&self, method: &str, pattern: &str) -> Vec<MockRequest> {
+        self.calls()
+            .into_iter()
+            .filter(|c| c.method.eq_ignore_ascii_case(method) && matches_suffix(pattern, &c.path))
+            .collect()
+    }
+
+    /// リクエストを記録し、登録したレスポンスを返す。
+    fn respond(
+        &self,
+        method: &str,
+        url: String,
+        headers: &Headers,
+        body: Option<&Value>,
+    ) -> Result<RawResponse, Error> {
+        let path = reqwest::Url::parse(&url)?.path().to_string();
+        let request = MockRequest {
+            method: method.to_string(),
+            url,
+            path,
+            headers: headers
+                .into_iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            body: body.cloned(),
+        };
+        self.calls.lock().unwrap().push(request.clone());
+        match self
+            .routes
+            .iter()
+            .find(|r| r.method == method && matches_suffix(&r.pattern, &request.path))
+        {
+            Some(route) => (route.responder)(&request),
+            None => Err(Error::MockRouteNotFoundError(format!(
+                "{} {}",
+                method, request.url
+            ))),
+        }
+    }
+}
+
+/// `path`のいずれかの`/`から後ろがパターンに一致するか？ `*`は任意の文字列に一致する。
+fn matches_suffix(pattern: &str, path: &str) -> bool {
+    path.match_indices('/')
+        .any(|(i, _)| matches_glob(pattern.as_bytes(), &path.as_bytes()[i..]))
+}
+
+/// 文字列全体がパターンに一致するか？ `*`は任意の文字列に一致する。
+fn matches_glob(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => (0..=text.len()).any(|i| matches_glob(rest, &text[i..])),
+        Some((c, rest)) => match text.split_first() {
+            Some((t, text_rest)) if t == c => matches_glob(rest, text_rest),
+            _ => false,
+        },
+    }
+}
+
+#[async_trait]
+impl HttpClient for MockHttpClient {
+    async fn get(&self, url: String, headers: &Headers) -> Result<RawResponse, Error> {
+        self.respond("GET", url, headers, None)
+    }
+
+    async fn post(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        self.respond("POST", url, headers, Some(parameters))
+    }
+
+    async fn put(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        self.respond("PUT", url, headers, Some(parameters))
+    }
+
+    async fn delete(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        self.respond("DELETE", url, headers, Some(parameters))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::credentials::tests::fake_credentials;
+    use crate::id::OrderId;
+    use crate::price::price;
+    use crate::private::PrivateAPI;
+    use crate::public::PublicAPI;
+    use crate::symbol::Symbol;
+
+    /// 建玉一覧APIの1ページ目に`list`を、2ページ目以降に空の一覧を返すモックを作る。
+    ///
+    /// # Arguments
+    ///
+    /// * `list` - 建玉一覧のJSON配列。
+    ///
+    pub(crate) fn open_positions_mock(list: &'static str) -> MockHttpClient {
+        MockHttpClient::new().on_fn("GET", "/v1/openPositions", move |request| {
+            let list = if request.url.contains("page=1&") {
+                list
+            } else {
+                "[]"
+            };
+            Ok(RawResponse {
+                http_status_code: 200,
+                body_text: format!(
+                    r#"{{"status":0,"data":{{"pagination":{{"currentPage":1,"count":30}},"list":{}}},"responsetime":"2019-03-19T02:15:06.095Z"}}"#,
+                    list
+                ),
+            })
+        })
+    }
+
+    const TICKER_RESPONSE_SAMPLE: &str = r#"{"status":0,"data":[{"ask":"750760","bid":"750600","high":"762302","last":"756662","low":"704874","symbol":"BTC","timestamp":"2018-03-30T12:34:56.789Z","volume":"194785.8484"}],"responsetime":"2019-03-19T02:15:06.014Z"}"#;
+
+    const ORDERBOOKS_RESPONSE_SAMPLE: &str = r#"{"status":0,"data":{"asks":[{"price":"455672","size":"0.1"}],"bids":[{"price":"455665","size":"0.1"}],"symbol":"BTC"},"responsetime":"2019-03-19T02:15:06.026Z"}"#;
+
+    #[test]
+    fn test_matches_suffix() {
+        assert!(matches_suffix("/v1/ticker", "/public/v1/ticker"));
+        assert!(!matches_suffix("/v1/ticker", "/public/v1/ticker/x"));
+        assert!(matches_suffix("/v1/*", "/public/v1/orderbooks"));
+        assert!(matches_suffix("/public/*/status", "/public/v1/status"));
+        assert!(!matches_suffix("/v1/order", "/private/v1/orders"));
+        assert!(!matches_suffix("Order", "/private/v1/cancelOrder"));
+    }
+
+    #[tokio::test]
+    async fn test_routes_by_path() {
+        let mock = MockHttpClient::new()
+            .on("GET", "/v1/ticker", 200, TICKER_RESPONSE_SAMPLE)
+            .on("GET", "/v1/orderbooks", 200, ORDERBOOKS_RESPONSE_SAMPLE);
+        let public_api = PublicAPI::new(mock);
+        let ticker = public_api.ticker(&Symbol::Btc).await.unwrap();
+        assert_eq!(ticker.ask().unwrap(), price("750760"));
+        let orderbooks = public_api.orderbooks(&Symbol::Btc).await.unwrap();
+        assert_eq!(orderbooks.asks()[0].price, price("455672"));
+        let status = public_api.status().await;
+        assert!(matches!(status, Err(Error::MockRouteNotFoundError(_))));
+
+        let calls = public_api.http_client.calls();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(
+            calls[0].url,
+            "https://api.coin.z.com/public/v1/ticker?symbol=BTC"
+        );
+        assert_eq!(
+            public_api
+                .http_client
+                .calls_to("get", "/v1/orderbooks")
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_records_headers_and_body() {
+        let mock = MockHttpClient::new().on_fn("POST", "/v1/cancelOrder", |request| {
+            let order_id = request.body.as_ref().unwrap()["orderId"].clone();
+            Ok(RawResponse {
+                http_status_code: 200,
+                body_text: if order_id == 200 {
+                    r#"{"status":0,"responsetime":"2019-03-19T01:07:24.557Z"}"#.to_string()
+                } else {
+                    r#"{"status":1,"messages":[{"message_code":"ERR-5123","message_string":"not found"}]}"#
+                        .to_string()
+                },
+            })
+        });
+        let private_api = PrivateAPI::new(mock).with_credentials(fake_credentials());
+        private_api
+            .cancel_order(&OrderId::new("200"))
+            .await
+            .unwrap();
+        let err = match private_api.cancel_order(&OrderId::new("300")).await {
+            Err(e) => e,
+            Ok(_) => panic!("エラーレスポンスを受け取れなかった"),
+        };
+        assert_eq!(
+            err.api_error_code(),
+            Some(crate::api_error_code::ApiErrorCode::OrderNotFound.into())
+        );
+
+        let calls = private_api.http_client.calls_to("POST", "/v1/cancelOrder");
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].headers["API-KEY"], "fake");
+        assert!(calls[0].headers.contains_key("API-SIGN"));
+        assert_eq!(calls[1].body.as_ref().unwrap()["orderId"], 300);
+    }
+}
@@ -0,0 +1,817 @@
+//! 注文系APIの引数を組み立てるビルダーを実装する。`PrivateAPI::submit`に渡して注文する。
+
+use crate::error::Error;
+use crate::execution_type::ExecutionType;
+use crate::http_client::HttpClient;
+use crate::id::PositionId;
+use crate::price::Price;
+use crate::private::cancel_bulk_order;
+use crate::private::close_bulk_order;
+use crate::private::close_order;
+use crate::private::order;
+use crate::private::PrivateAPI;
+use crate::response::RestResponse;
+use crate::settle_type::SettleType;
+use crate::side::Side;
+use crate::size::Size;
+use crate::symbol::Symbol;
+use crate::time_in_force::TimeInForce;
+use async_trait::async_trait;
+
+/// `PrivateAPI::submit`に渡せる注文。
+#[async_trait]
+pub trait OrderRequest: Send + Sized {
+    /// APIから返ってくるレスポンスの型。
+    type Response;
+
+    /// 注文の内容に対応するAPIを呼び出す。
+    ///
+    /// # Arguments
+    ///
+    /// * `api` - Private API。
+    ///
+    async fn submit<T: HttpClient + std::marker::Sync + std::marker::Send>(
+        self,
+        api: &PrivateAPI<T>,
+    ) -> Result<RestResponse<Self::Response>, Error>;
+}
+
+/// 注文方法に対して執行数量条件を指定できるか検証する。指値注文以外はFAKだけ指定できる。
+fn validate_time_in_force(
+    execution_type: &ExecutionType,
+    time_in_force: Option<&TimeInForce>,
+) -> Result<(), Error> {
+    match time_in_force {
+        Some(tif) if *execution_type != ExecutionType::Limit && *tif != TimeInForce::Fak => {
+            Err(Error::TimeInForceNotAllowedError(
+                execution_type.to_string().to_string(),
+                tif.to_string().to_string(),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 執行数量条件が指定されていなければ注文方法のデフォルトを返す。
+fn time_in_force_or_default(
+    execution_type: &ExecutionType,
+    time_in_force: Option<TimeInForce>,
+) -> TimeInForce {
+    time_in_force.unwrap_or(match execution_type {
+        ExecutionType::Limit => TimeInForce::Fas,
+        _ => TimeInForce::Fak,
+    })
+}
+
+/// 新規注文。成行注文は`NewOrder::market`、指値注文は`NewOrder::limit`、逆指値注文は`NewOrder::stop`で作成する。
+pub struct NewOrder<'a> {
+    execution_type: ExecutionType,
+    symbol: &'a Symbol,
+    side: Side,
+    size: Size,
+    price: Option<Price>,
+    time_in_force: Option<TimeInForce>,
+    losscut_price: Option<Price>,
+}
+
+impl<'a> NewOrder<'a> {
+    /// 成行注文を作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `side` - 売買区分。
+    /// * `size` - 注文数量。
+    ///
+    pub fn market(symbol: &'a Symbol, side: &Side, size: Size) -> NewOrder<'a> {
+        NewOrder::new(ExecutionType::Market, symbol, side, size, None)
+    }
+
+    /// 指値注文を作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `side` - 売買区分。
+    /// * `size` - 注文数量。
+    /// * `price` - 注文価格。
+    ///
+    pub fn limit(symbol: &'a Symbol, side: &Side, size: Size, price: Price) -> NewOrder<'a> {
+        NewOrder::new(ExecutionType::Limit, symbol, side, size, Some(price))
+    }
+
+    /// 逆指値注文を作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `side` - 売買区分。
+    /// * `size` - 注文数量。
+    /// * `price` - 注文価格。
+    ///
+    pub fn stop(symbol: &'a Symbol, side: &Side, size: Size, price: Price) -> NewOrder<'a> {
+        NewOrder::new(ExecutionType::Stop, symbol, side, size, Some(price))
+    }
+
+    fn new(
+        execution_type: ExecutionType,
+        symbol: &'a Symbol,
+        side: &Side,
+        size: Size,
+        price: Option<Price>,
+    ) -> NewOrder<'a> {
+        NewOrder {
+            execution_type,
+            symbol,
+            side: *side,
+            size,
+            price,
+            time_in_force: None,
+            losscut_price: None,
+        }
+    }
+
+    /// 執行数量条件を指定する。指定しない場合、指値注文はFAS、それ以外はFAKになる。
+    ///
+    /// # Arguments
+    ///
+    /// * `time_in_force` - 執行数量条件。指値注文以外はFAKだけ指定できる。
+    ///
+    pub fn with_time_in_force(mut self, time_in_force: TimeInForce) -> NewOrder<'a> {
+        self.time_in_force = Some(time_in_force);
+        self
+    }
+
+    /// ロスカットレートを指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `losscut_price` - ロスカットレート。指値注文と逆指値注文だけ指定できる。
+    ///
+    pub fn with_losscut_price(mut self, losscut_price: Price) -> NewOrder<'a> {
+        self.losscut_price = Some(losscut_price);
+        self
+    }
+
+    /// 注文方法と執行数量条件、ロスカットレートの組み合わせが正しいか検証する。
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_time_in_force(&self.execution_type, self.time_in_force.as_ref())?;
+        if self.execution_type == ExecutionType::Market && self.losscut_price.is_some() {
+            return Err(Error::LosscutPriceNotAllowedError(
+                self.execution_type.to_string().to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> OrderRequest for NewOrder<'a> {
+    type Response = order::Order;
+
+    async fn submit<T: HttpClient + std::marker::Sync + std::marker::Send>(
+        self,
+        api: &PrivateAPI<T>,
+    ) -> Result<RestResponse<order::Order>, Error> {
+        self.validate()?;
+        let time_in_force = time_in_force_or_default(&self.execution_type, self.time_in_force);
+        api.order_with_options(
+            &self.execution_type,
+            self.symbol,
+            &self.side,
+            self.size,
+            self.price,
+            &time_in_force,
+            self.losscut_price,
+        )
+        .await
+    }
+}
+
+/// 決済注文。`CloseOrder::market`, `CloseOrder::limit`, `CloseOrder::stop`で作成し、`with_position`で決済する建玉を指定する。
+pub struct CloseOrder<'a> {
+    execution_type: ExecutionType,
+    symbol: &'a Symbol,
+    side: Side,
+    price: Option<Price>,
+    positions: Vec<(&'a PositionId, Size)>,
+    time_in_force: Option<TimeInForce>,
+}
+
+impl<'a> CloseOrder<'a> {
+    /// 成行の決済注文を作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `side` - 売買区分。
+    ///
+    pub fn market(symbol: &'a Symbol, side: &Side) -> CloseOrder<'a> {
+        CloseOrder::new(ExecutionType::Market, symbol, side, None)
+    }
+
+    /// 指値の決済注文を作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `side` - 売買区分。
+    /// * `price` - 注文価格。
+    ///
+    pub fn limit(symbol: &'a Symbol, side: &Side, price: Price) -> CloseOrder<'a> {
+        CloseOrder::new(ExecutionType::Limit, symbol, side, Some(price))
+    }
+
+    /// 逆指値の決済注文を作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `side` - 売買区分。
+    /// * `price` - 注文価格。
+    ///
+    pub fn stop(symbol: &'a Symbol, side: &Side, price: Price) -> CloseOrder<'a> {
+        CloseOrder::new(ExecutionType::Stop, symbol, side, Some(price))
+    }
+
+    fn new(
+        execution_type: ExecutionType,
+        symbol: &'a Symbol,
+        side: &Side,
+        price: Option<Price>,
+    ) -> CloseOrder<'a> {
+        CloseOrder {
+            execution_type,
+            symbol,
+            side: *side,
+            price,
+            positions: Vec::new(),
+            time_in_force: None,
+        }
+    }
+
+    /// 決済する建玉を追加する。
+    ///
+    /// # Arguments
+    ///
+    /// * `position_id` - 建玉ID。
+    /// * `size` - 決済する数量。
+    ///
+    pub fn with_position(mut self, position_id: &'a PositionId, size: Size) -> CloseOrder<'a> {
+        self.positions.push((position_id, size));
+        self
+    }
+
+    /// 決済する建玉をまとめて追加する。
+    ///
+    /// # Arguments
+    ///
+    /// * `positions` - 建玉IDと決済する数量の配列。
+    ///
+    pub fn with_positions(mut self, positions: &[(&'a PositionId, Size)]) -> CloseOrder<'a> {
+        self.positions.extend_from_slice(positions);
+        self
+    }
+
+    /// 執行数量条件を指定する。指定しない場合、指値注文はFAS、それ以外はFAKになる。
+    ///
+    /// # Arguments
+    ///
+    /// * `time_in_force` - 執行数量条件。指値注文以外はFAKだけ指定できる。
+    ///
+    pub fn with_time_in_force(mut self, time_in_force: TimeInForce) -> CloseOrder<'a> {
+        self.time_in_force = Some(time_in_force);
+        self
+    }
+
+    /// 決済する建玉が指定されているか、注文方法と執行数量条件の組み合わせが正しいか検証する。
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.positions.is_empty() {
+            return Err(Error::PositionNotSpecifiedError());
+        }
+        validate_time_in_force(&self.execution_type, self.time_in_force.as_ref())
+    }
+}
+
+#[async_trait]
+impl<'a> OrderRequest for CloseOrder<'a> {
+    type Response = close_order::CloseOrder;
+
+    async fn submit<T: HttpClient + std::marker::Sync + std::marker::Send>(
+        self,
+        api: &PrivateAPI<T>,
+    ) -> Result<RestResponse<close_order::CloseOrder>, Error> {
+        self.validate()?;
+        let time_in_force = time_in_force_or_default(&self.execution_type, self.time_in_force);
+        api.close_order_with_options(
+            &self.execution_type,
+            self.symbol,
+            &self.side,
+            self.price,
+            &self.positions,
+            &time_in_force,
+        )
+        .await
+    }
+}
+
+/// 一括決済注文。`CloseBulkOrder::market`, `CloseBulkOrder::limit`, `CloseBulkOrder::stop`で作成する。
+pub struct CloseBulkOrder<'a> {
+    execution_type: ExecutionType,
+    symbol: &'a Symbol,
+    side: Side,
+    size: Size,
+    price: Option<Price>,
+    time_in_force: Option<TimeInForce>,
+}
+
+impl<'a> CloseBulkOrder<'a> {
+    /// 成行の一括決済注文を作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `side` - 売買区分。
+    /// * `size` - 注文数量。
+    ///
+    pub fn market(symbol: &'a Symbol, side: &Side, size: Size) -> CloseBulkOrder<'a> {
+        CloseBulkOrder::new(ExecutionType::Market, symbol, side, size, None)
+    }
+
+    /// 指値の一括決済注文を作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `side` - 売買区分。
+    /// * `size` - 注文数量。
+    /// * `price` - 注文価格。
+    ///
+    pub fn limit(symbol: &'a Symbol, side: &Side, size: Size, price: Price) -> CloseBulkOrder<'a> {
+        CloseBulkOrder::new(ExecutionType::Limit, symbol, side, size, Some(price))
+    }
+
+    /// 逆指値の一括決済注文を作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `side` - 売買区分。
+    /// * `size` - 注文数量。
+    /// * `price` - 注文価格。
+    ///
+    pub fn stop(symbol: &'a Symbol, side: &Side, size: Size, price: Price) -> CloseBulkOrder<'a> {
+        CloseBulkOrder::new(ExecutionType::Stop, symbol, side, size, Some(price))
+    }
+
+    fn new(
+        execution_type: ExecutionType,
+        symbol: &'a Symbol,
+        side: &Side,
+        size: Size,
+        price: Option<Price>,
+    ) -> CloseBulkOrder<'a> {
+        CloseBulkOrder {
+            execution_type,
+            symbol,
+            side: *side,
+            size,
+            price,
+            time_in_force: None,
+        }
+    }
+
+    /// 執行数量条件を指定する。指定しない場合、指値注文はFAS、それ以外はFAKになる。
+    ///
+    /// # Arguments
+    ///
+    /// * `time_in_force` - 執行数量条件。指値注文以外はFAKだけ指定できる。
+    ///
+    pub fn with_time_in_force(mut self, time_in_force: TimeInForce) -> CloseBulkOrder<'a> {
+        self.time_in_force = Some(time_in_force);
+        self
+    }
+
+    /// 注文方法と執行数量条件の組み合わせが正しいか検証する。
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_time_in_force(&self.execution_type, self.time_in_force.as_ref())
+    }
+}
+
+#[async_trait]
+impl<'a> OrderRequest for CloseBulkOrder<'a> {
+    type Response = close_bulk_order::CloseBulkOrder;
+
+    async fn submit<T: HttpClient + std::marker::Sync + std::marker::Send>(
+        self,
+        api: &PrivateAPI<T>,
+    ) -> Result<RestResponse<close_bulk_order::CloseBulkOrder>, Error> {
+        self.validate()?;
+        let time_in_force = time_in_force_or_default(&self.execution_type, self.time_in_force);
+        api.close_bulk_order_with_options(
+            &self.execution_type,
+            self.symbol,
+            &self.side,
+            self.size,
+            self.price,
+            &time_in_force,
+        )
+        .await
+    }
+}
+
+/// 注文の一括キャンセル。`CancelBulkOrder::new`で作成する。
+pub struct CancelBulkOrder<'a> {
+    symbols: &'a [&'a Symbol],
+    side: Option<Side>,
+    settle_type: Option<SettleType>,
+    desc: bool,
+}
+
+impl<'a> CancelBulkOrder<'a> {
+    /// 注文の一括キャンセルを作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbols` - 銘柄の配列。
+    ///
+    pub fn new(symbols: &'a [&'a Symbol]) -> CancelBulkOrder<'a> {
+        CancelBulkOrder {
+            symbols,
+            side: None,
+            settle_type: None,
+            desc: false,
+        }
+    }
+
+    /// 指定した売買区分の注文だけを取り消す。
+    ///
+    /// # Arguments
+    ///
+    /// * `side` - 売買区分。
+    ///
+    pub fn with_side(mut self, side: &Side) -> CancelBulkOrder<'a> {
+        self.side = Some(*side);
+        self
+    }
+
+    /// 現物取引注文と指定した決済区分のレバレッジ取引注文だけを取り消す。
+    ///
+    /// # Arguments
+    ///
+    /// * `settle_type` - 決済区分。
+    ///
+    pub fn with_settle_type(mut self, settle_type: &SettleType) -> CancelBulkOrder<'a> {
+        self.settle_type = Some(*settle_type);
+        self
+    }
+
+    /// 注文日時が新しい注文から取り消す。指定しない場合は古い注文から取り消す。
+    pub fn with_desc(mut self) -> CancelBulkOrder<'a> {
+        self.desc = true;
+        self
+    }
+}
+
+#[async_trait]
+impl<'a> OrderRequest for CancelBulkOrder<'a> {
+    type Response = cancel_bulk_order::CancelBulkOrder;
+
+    async fn submit<T: HttpClient + std::marker::Sync + std::marker::Send>(
+        self,
+        api: &PrivateAPI<T>,
+    ) -> Result<RestResponse<cancel_bulk_order::CancelBulkOrder>, Error> {
+        api.cancel_bulk_order_with_options(
+            self.symbols,
+            self.side.as_ref(),
+            self.settle_type.as_ref(),
+            Some(self.desc),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::Credentials;
+    use crate::mock::tests::open_positions_mock;
+    use crate::mock::MockHttpClient;
+    use crate::price::price;
+    use crate::size::size;
+    use serde_json::json;
+
+    const ORDER_RESPONSE: &str =
+        r#"{"status":0,"data":"637000","responsetime":"2019-03-19T01:07:24.557Z"}"#;
+
+    const CANCEL_BULK_ORDER_RESPONSE: &str =
+        r#"{"status":0,"data":[637000,637002],"responsetime":"2019-03-19T01:07:24.557Z"}"#;
+
+    const OPEN_POSITIONS: &str = r#"[{"positionId":1,"symbol":"BTC_JPY","side":"BUY","size":"0.3","orderdSize":"0","price":"876045","lossGain":"14","leverage":"4","losscutPrice":"766540","timestamp":"2019-03-19T02:15:06.094Z"},{"positionId":2,"symbol":"BTC_JPY","side":"BUY","size":"0.2","orderdSize":"0","price":"876045","lossGain":"14","leverage":"4","losscutPrice":"766540","timestamp":"2019-03-19T02:15:06.094Z"},{"positionId":3,"symbol":"BTC_JPY","side":"SELL","size":"0.1","orderdSize":"0","price":"876045","lossGain":"14","leverage":"4","losscutPrice":"766540","timestamp":"2019-03-19T02:15:06.094Z"}]"#;
+
+    fn private_api() -> PrivateAPI<MockHttpClient> {
+        let mock = open_positions_mock(OPEN_POSITIONS)
+            .on("POST", "/v1/order", 200, ORDER_RESPONSE)
+            .on("POST", "/v1/closeOrder", 200, ORDER_RESPONSE)
+            .on("POST", "/v1/closeBulkOrder", 200, ORDER_RESPONSE)
+            .on(
+                "POST",
+                "/v1/cancelBulkOrder",
+                200,
+                CANCEL_BULK_ORDER_RESPONSE,
+            );
+        PrivateAPI::new(mock).with_credentials(Credentials::new("key", "secret"))
+    }
+
+    #[test]
+    fn test_validate() {
+        let symbol = Symbol::BtcJpy;
+        assert!(
+            NewOrder::limit(&symbol, &Side::Buy, size("0.1"), price("1000000"))
+                .with_time_in_force(TimeInForce::Sok)
+                .with_losscut_price(price("900000"))
+                .validate()
+                .is_ok()
+        );
+        assert!(matches!(
+            NewOrder::market(&symbol, &Side::Buy, size("0.1"))
+                .with_time_in_force(TimeInForce::Sok)
+                .validate(),
+            Err(Error::TimeInForceNotAllowedError(_, _))
+        ));
+        assert!(matches!(
+            NewOrder::market(&symbol, &Side::Buy, size("0.1"))
+                .with_losscut_price(price("900000"))
+                .validate(),
+            Err(Error::LosscutPriceNotAllowedError(_))
+        ));
+        assert!(matches!(
+            CloseOrder::market(&symbol, &Side::Sell).validate(),
+            Err(Error::PositionNotSpecifiedError())
+        ));
+        assert!(matches!(
+            CloseBulkOrder::stop(&symbol, &Side::Sell, size("0.1"), price("900000"))
+                .with_time_in_force(TimeInForce::Fas)
+                .validate(),
+            Err(Error::TimeInForceNotAllowedError(_, _))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_submit() {
+        let private_api = private_api();
+        let symbol = Symbol::BtcJpy;
+        let response = private_api
+            .submit(
+                NewOrder::limit(&symbol, &Side::Buy, size("0.1"), price("1000000"))
+                    .with_time_in_force(TimeInForce::Sok),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.order_id(), "637000");
+
+        let response = private_api
+            .submit(
+                CloseOrder::market(&symbol, &Side::Sell)
+                    .with_position(&PositionId::new("1"), size("0.1"))
+                    .with_position(&PositionId::new("2"), size("0.2")),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.order_id(), "637000");
+
+        let result = private_api
+            .submit(
+                NewOrder::stop(&symbol, &Side::Buy, size("0.1"), price("1000000"))
+                    .with_time_in_force(TimeInForce::Sok),
+            )
+            .await;
+        assert!(result.is_err());
+
+        let orders = private_api.http_client.calls_to("POST", "/v1/order");
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].body.as_ref().unwrap()["timeInForce"], "SOK");
+        let close_orders = private_api.http_client.calls_to("POST", "/v1/closeOrder");
+        assert_eq!(close_orders.len(), 1);
+        assert_eq!(
+            close_orders[0].body.as_ref().unwrap()["settlePosition"][1]["positionId"],
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_submit_new_order() {
+        let private_api = private_api();
+        let symbol = Symbol::BtcJpy;
+        private_api
+            .submit(NewOrder::market(&symbol, &Side::Buy, size("0.1")))
+            .await
+            .unwrap();
+        private_api
+            .submit(NewOrder::limit(
+                &symbol,
+                &Side::Buy,
+                size("0.1"),
+                price("1000000"),
+            ))
+            .await
+            .unwrap();
+        private_api
+            .submit(NewOrder::stop(
+                &symbol,
+                &Side::Sell,
+                size("0.2"),
+                price("900000"),
+            ))
+            .await
+            .unwrap();
+
+        let calls = private_api.http_client.calls_to("POST", "/v1/order");
+        assert_eq!(calls.len(), 3);
+        assert_eq!(
+            calls[0].body.as_ref().unwrap(),
+            &json!({
+                "executionType": "MARKET",
+                "symbol": "BTC_JPY",
+                "side": "BUY",
+                "timeInForce": "FAK",
+                "size": "0.1",
+            })
+        );
+        assert_eq!(
+            calls[1].body.as_ref().unwrap(),
+            &json!({
+                "executionType": "LIMIT",
+                "symbol": "BTC_JPY",
+                "side": "BUY",
+                "timeInForce": "FAS",
+                "price": "1000000",
+                "size": "0.1",
+            })
+        );
+        assert_eq!(
+            calls[2].body.as_ref().unwrap(),
+            &json!({
+                "executionType": "STOP",
+                "symbol": "BTC_JPY",
+                "side": "SELL",
+                "timeInForce": "FAK",
+                "price": "900000",
+                "size": "0.2",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_submit_close_order() {
+        let private_api = private_api();
+        let symbol = Symbol::BtcJpy;
+        let id1 = PositionId::new("1");
+        let id2 = PositionId::new("2");
+        let id3 = PositionId::new("3");
+        private_api
+            .submit(
+                CloseOrder::limit(&symbol, &Side::Sell, price("1000000"))
+                    .with_positions(&[(&id1, size("0.1")), (&id2, size("0.2"))]),
+            )
+            .await
+            .unwrap();
+        private_api
+            .submit(
+                CloseOrder::stop(&symbol, &Side::Buy, price("900000"))
+                    .with_position(&id3, size("0.1")),
+            )
+            .await
+            .unwrap();
+
+        let calls = private_api.http_client.calls_to("POST", "/v1/closeOrder");
+        assert_eq!(calls.len(), 2);
+        assert_eq!(
+            calls[0].body.as_ref().unwrap(),
+            &json!({
+                "executionType": "LIMIT",
+                "symbol": "BTC_JPY",
+                "side": "SELL",
+                "timeInForce": "FAS",
+                "price": "1000000",
+                "settlePosition": [
+                    {"positionId": 1, "size": "0.1"},
+                    {"positionId": 2, "size": "0.2"},
+                ],
+            })
+        );
+        assert_eq!(
+            calls[1].body.as_ref().unwrap(),
+            &json!({
+                "executionType": "STOP",
+                "symbol": "BTC_JPY",
+                "side": "BUY",
+                "timeInForce": "FAK",
+                "price": "900000",
+                "settlePosition": [{"positionId": 3, "size": "0.1"}],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_submit_close_bulk_order() {
+        let private_api = private_api();
+        let symbol = Symbol::BtcJpy;
+        let response = private_api
+            .submit(CloseBulkOrder::market(&symbol, &Side::Sell, size("0.3")))
+            .await
+            .unwrap();
+        assert_eq!(response.order_id(), "637000");
+        private_api
+            .submit(CloseBulkOrder::limit(
+                &symbol,
+                &Side::Sell,
+                size("0.3"),
+                price("1000000"),
+            ))
+            .await
+            .unwrap();
+        private_api
+            .submit(
+                CloseBulkOrder::stop(&symbol, &Side::Buy, size("0.3"), price("900000"))
+                    .with_time_in_force(TimeInForce::Fak),
+            )
+            .await
+            .unwrap();
+
+        let calls = private_api
+            .http_client
+            .calls_to("POST", "/v1/closeBulkOrder");
+        assert_eq!(calls.len(), 3);
+        assert_eq!(
+            calls[0].body.as_ref().unwrap(),
+            &json!({
+                "executionType": "MARKET",
+                "symbol": "BTC_JPY",
+                "side": "SELL",
+                "timeInForce": "FAK",
+                "size": "0.3",
+            })
+        );
+        assert_eq!(
+            calls[1].body.as_ref().unwrap(),
+            &json!({
+                "executionType": "LIMIT",
+                "symbol": "BTC_JPY",
+                "side": "SELL",
+                "timeInForce": "FAS",
+                "price": "1000000",
+                "size": "0.3",
+            })
+        );
+        assert_eq!(
+            calls[2].body.as_ref().unwrap(),
+            &json!({
+                "executionType": "STOP",
+                "symbol": "BTC_JPY",
+                "side": "BUY",
+                "timeInForce": "FAK",
+                "price": "900000",
+                "size": "0.3",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_submit_cancel_bulk_order() {
+        let private_api = private_api();
+        let symbols = [&Symbol::Btc, &Symbol::BtcJpy];
+        let response = private_api
+            .submit(CancelBulkOrder::new(&symbols))
+            .await
+            .unwrap();
+        assert_eq!(response.order_ids().len(), 2);
+        private_api
+            .submit(
+                CancelBulkOrder::new(&symbols)
+                    .with_side(&Side::Buy)
+                    .with_settle_type(&SettleType::Close)
+                    .with_desc(),
+            )
+            .await
+            .unwrap();
+
+        let calls = private_api
+            .http_client
+            .calls_to("POST", "/v1/cancelBulkOrder");
+        assert_eq!(calls.len(), 2);
+        assert_eq!(
+            calls[0].body.as_ref().unwrap(),
+            &json!({"symbols": ["BTC", "BTC_JPY"], "desc": false})
+        );
+        assert_eq!(
+            calls[1].body.as_ref().unwrap(),
+            &json!({
+                "symbols": ["BTC", "BTC_JPY"],
+                "side": "BUY",
+                "settleType": "CLOSE",
+                "desc": true,
+            })
+        );
+    }
+}
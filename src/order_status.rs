@@ -0,0 +1,52 @@
+//! 注文ステータスを定義する。
+
+use serde::{Deserialize, Serialize};
+
+/// 注文ステータス
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum OrderStatus {
+    /// 逆指値注文が発動待ちの状態
+    Waiting,
+
+    /// 注文中
+    Ordered,
+
+    /// 注文変更中
+    Modifying,
+
+    /// 注文取消中
+    Cancelling,
+
+    /// 注文取消済み
+    Canceled,
+
+    /// 全量約定済み
+    Executed,
+
+    /// 失効済み
+    Expired,
+}
+
+pub const WAITING: &str = "WAITING";
+pub const ORDERED: &str = "ORDERED";
+pub const MODIFYING: &str = "MODIFYING";
+pub const CANCELLING: &str = "CANCELLING";
+pub const CANCELED: &str = "CANCELED";
+pub const EXECUTED: &str = "EXECUTED";
+pub const EXPIRED: &str = "EXPIRED";
+
+impl OrderStatus {
+    /// 注文ステータスを文字列に変換する。
+    pub fn to_string(&self) -> &str {
+        match self {
+            OrderStatus::Waiting => WAITING,
+            OrderStatus::Ordered => ORDERED,
+            OrderStatus::Modifying => MODIFYING,
+            OrderStatus::Cancelling => CANCELLING,
+            OrderStatus::Canceled => CANCELED,
+            OrderStatus::Executed => EXECUTED,
+            OrderStatus::Expired => EXPIRED,
+        }
+    }
+}
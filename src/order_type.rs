@@ -0,0 +1,30 @@
+//! 取引区分を定義する。
+
+use serde::{Deserialize, Serialize};
+
+/// 取引区分
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum OrderType {
+    /// 通常の注文
+    Normal,
+
+    /// ロスカットによる注文
+    Losscut,
+}
+
+/// 通常の注文
+pub const NORMAL: &str = "NORMAL";
+
+/// ロスカットによる注文
+pub const LOSSCUT: &str = "LOSSCUT";
+
+impl OrderType {
+    /// 取引区分を文字列に変換する。
+    pub fn to_string(&self) -> &str {
+        match self {
+            OrderType::Normal => NORMAL,
+            OrderType::Losscut => LOSSCUT,
+        }
+    }
+}
@@ -0,0 +1,420 @@
+//! 取引ルールに基づいて注文パラメータを検証する機能を実装する。
+
+use crate::error::Error;
+use crate::http_client::HttpClient;
+use crate::price::Price;
+use crate::public::symbols::SymbolRule;
+use crate::public::PublicAPI;
+use crate::size::Size;
+use crate::symbol::Symbol;
+use std::collections::HashMap;
+
+/// 倍数かどうかを判定するときに許容する誤差。f64で判定するときに使う。
+#[cfg(not(feature = "decimal"))]
+const MULTIPLE_TOLERANCE: f64 = 1e-8;
+
+/// 取引ルールAPIで取得した取引ルールを保持し、注文パラメータがルールを満たしているか検証する構造体。
+/// `PrivateAPI::with_validator`で設定すると、注文系のAPIを呼び出す前に検証が行われる。
+#[derive(Clone)]
+pub struct OrderValidator {
+    rules: HashMap<String, SymbolRule>,
+}
+
+/// `value`が`step`の倍数であるか？
+#[cfg(not(feature = "decimal"))]
+fn is_multiple_of(value: f64, step: f64) -> bool {
+    if step <= 0.0 {
+        return true;
+    }
+    let ratio = value / step;
+    (ratio - ratio.round()).abs() < MULTIPLE_TOLERANCE
+}
+
+/// 注文数量`size`が`step`の倍数であるか？
+#[cfg(not(feature = "decimal"))]
+fn is_size_multiple_of(size: Size, step: Size) -> bool {
+    is_multiple_of(size, step)
+}
+
+/// 注文数量`size`が`step`の倍数であるか？十進数型なので誤差なく判定する。
+#[cfg(feature = "decimal")]
+fn is_size_multiple_of(size: Size, step: Size) -> bool {
+    step <= Size::ZERO || (size % step).is_zero()
+}
+
+/// 注文価格`price`が`tick`の倍数であるか？
+#[cfg(not(feature = "decimal"))]
+fn is_price_multiple_of(price: Price, tick: Price) -> bool {
+    is_multiple_of(price, tick)
+}
+
+/// 注文価格`price`が`tick`の倍数であるか？十進数型なので誤差なく判定する。
+#[cfg(feature = "decimal")]
+fn is_price_multiple_of(price: Price, tick: Price) -> bool {
+    tick <= Price::ZERO || (price % tick).is_zero()
+}
+
+impl OrderValidator {
+    /// 取引ルールの配列から作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `rules` - 取引ルールの配列。
+    ///
+    pub fn new(rules: &[SymbolRule]) -> OrderValidator {
+        let rules = rules
+            .iter()
+            .map(|r| (r.symbol.clone(), r.clone()))
+            .collect();
+        OrderValidator { rules }
+    }
+
+    /// 取引ルールAPIを呼び出して作成する。取得した取引ルールはキャッシュされ、以降の検証で使われる。
+    ///
+    /// # Arguments
+    ///
+    /// * `public_api` - Public API。
+    ///
+    pub async fn fetch<T: HttpClient + std::marker::Sync + std::marker::Send>(
+        public_api: &PublicAPI<T>,
+    ) -> Result<OrderValidator, Error> {
+        let response = public_api.symbols().await?;
+        Ok(OrderValidator::new(response.symbols()))
+    }
+
+    /// 銘柄の取引ルールを取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    ///
+    pub fn rule(&self, symbol: &Symbol) -> Option<&SymbolRule> {
+        self.rules.get(symbol.to_string())
+    }
+
+    fn rule_by_name(&self, symbol: &str) -> Result<&SymbolRule, Error> {
+        self.rules
+            .get(symbol)
+            .ok_or_else(|| Error::SymbolRuleNotFoundError(symbol.to_string()))
+    }
+
+    /// 注文数量が最小注文数量以上、最大注文数量以下で、最小注文単位の倍数になっているか検証する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `size` - 注文数量。
+    ///
+    pub fn validate_size(&self, symbol: &Symbol, size: Size) -> Result<(), Error> {
+        let rule = self.rule_by_name(symbol.to_string())?;
+        if size < rule.min_order_size {
+            return Err(Error::OrderSizeTooSmallError(size, rule.min_order_size));
+        }
+        if size > rule.max_order_size {
+            return Err(Error::OrderSizeTooLargeError(size, rule.max_order_size));
+        }
+        if !is_size_multiple_of(size, rule.size_step) {
+            return Err(Error::OrderSizeStepError(size, rule.size_step));
+        }
+        Ok(())
+    }
+
+    /// 注文価格が呼値の倍数になっているか検証する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `price` - 注文価格。
+    ///
+    pub fn validate_price(&self, symbol: &Symbol, price: Price) -> Result<(), Error> {
+        self.validate_price_by_name(symbol.to_string(), price)
+    }
+
+    /// 銘柄名を指定して注文価格が呼値の倍数になっているか検証する。
+    pub(crate) fn validate_price_by_name(&self, symbol: &str, price: Price) -> Result<(), Error> {
+        let rule = self.rule_by_name(symbol)?;
+        if !is_price_multiple_of(price, rule.tick_size) {
+            return Err(Error::OrderPriceTickError(price, rule.tick_size));
+        }
+        Ok(())
+    }
+
+    /// 注文数量、注文価格、ロスカットレートをまとめて検証する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `size` - 注文数量。
+    /// * `price` - 注文価格。
+    /// * `losscut_price` - ロスカットレート。
+    ///
+    pub fn validate_order(
+        &self,
+        symbol: &Symbol,
+        size: Size,
+        price: Option<Price>,
+        losscut_price: Option<Price>,
+    ) -> Result<(), Error> {
+        self.validate_size(symbol, size)?;
+        if let Some(p) = price {
+            self.validate_price(symbol, p)?;
+        }
+        if let Some(lp) = losscut_price {
+            self.validate_price(symbol, lp)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::tests::fake_credentials;
+    use crate::execution_type::ExecutionType;
+    use crate::http_client::tests::InmemClient;
+    use crate::id::PositionId;
+    use crate::mock::tests::open_positions_mock;
+    use crate::mock::MockHttpClient;
+    use crate::price::price;
+    use crate::private::close_order::CloseOrder;
+    use crate::private::PrivateAPI;
+    use crate::response::RestResponse;
+    use crate::side::Side;
+    use crate::size::size;
+
+    fn validator() -> OrderValidator {
+        OrderValidator::new(&[
+            SymbolRule {
+                symbol: "BTC".to_string(),
+                min_order_size: size("0.0001"),
+                max_order_size: size("5.0"),
+                size_step: size("0.0001"),
+                tick_size: price("1"),
+                taker_fee: 0.0005,
+                maker_fee: -0.0001,
+            },
+            SymbolRule {
+                symbol: "XRP_JPY".to_string(),
+                min_order_size: size("10.0"),
+                max_order_size: size("500000.0"),
+                size_step: size("10.0"),
+                tick_size: price("0.001"),
+                taker_fee: 0.0,
+                maker_fee: 0.0,
+            },
+        ])
+    }
+
+    #[test]
+    fn test_validate_size() {
+        let v = validator();
+        assert!(v.validate_size(&Symbol::Btc, size("0.0001")).is_ok());
+        assert!(v.validate_size(&Symbol::Btc, size("0.0123")).is_ok());
+        assert!(v.validate_size(&Symbol::Btc, size("5.0")).is_ok());
+        assert!(matches!(
+            v.validate_size(&Symbol::Btc, size("0.00001")),
+            Err(Error::OrderSizeTooSmallError(_, _))
+        ));
+        assert!(matches!(
+            v.validate_size(&Symbol::Btc, size("5.1")),
+            Err(Error::OrderSizeTooLargeError(_, _))
+        ));
+        assert!(matches!(
+            v.validate_size(&Symbol::Btc, size("0.00015")),
+            Err(Error::OrderSizeStepError(_, _))
+        ));
+        assert!(matches!(
+            v.validate_size(&Symbol::XrpJpy, size("15.0")),
+            Err(Error::OrderSizeStepError(_, _))
+        ));
+        assert!(matches!(
+            v.validate_size(&Symbol::Eth, size("1.0")),
+            Err(Error::SymbolRuleNotFoundError(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_price() {
+        let v = validator();
+        assert!(v.validate_price(&Symbol::Btc, price("6418255")).is_ok());
+        assert!(v.validate_price(&Symbol::XrpJpy, price("55")).is_ok());
+        assert!(v
+            .validate_order(
+                &Symbol::Btc,
+                size("0.01"),
+                Some(price("6418255")),
+                Some(price("6000000"))
+            )
+            .is_ok());
+
+        let v = OrderValidator::new(&[SymbolRule {
+            symbol: "BTC_JPY".to_string(),
+            min_order_size: size("0.01"),
+            max_order_size: size("5.0"),
+            size_step: size("0.01"),
+            tick_size: price("5"),
+            taker_fee: 0.0,
+            maker_fee: 0.0,
+        }]);
+        assert!(v.validate_price(&Symbol::BtcJpy, price("6418255")).is_ok());
+        assert!(matches!(
+            v.validate_price(&Symbol::BtcJpy, price("6418253")),
+            Err(Error::OrderPriceTickError(p, t)) if p == price("6418253") && t == price("5")
+        ));
+        assert!(matches!(
+            v.validate_order(&Symbol::BtcJpy, size("0.01"), Some(price("6418255")), Some(price("6000001"))),
+            Err(Error::OrderPriceTickError(p, _)) if p == price("6000001")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_private_api_with_validator() {
+        let http_client = InmemClient {
+            http_status_code: 200,
+            body_text: "".to_string(),
+            return_error: true,
+        };
+        let private_api = PrivateAPI::new(http_client)
+            .with_credentials(fake_credentials())
+            .with_validator(validator());
+        let resp = private_api
+            .order(
+                &ExecutionType::Limit,
+                &Symbol::Btc,
+                &Side::Buy,
+                size("0.00001"),
+                Some(price("6418255")),
+            )
+            .await;
+        assert!(matches!(resp, Err(Error::OrderSizeTooSmallError(_, _))));
+        let resp = private_api
+            .close_bulk_order(
+                &ExecutionType::Market,
+                &Symbol::Btc,
+                &Side::Sell,
+                size("6"),
+                None,
+            )
+            .await;
+        assert!(matches!(resp, Err(Error::OrderSizeTooLargeError(_, _))));
+
+        // 複製しても設定とバリデータは共有する。
+        let cloned = private_api.clone();
+        assert!(std::ptr::eq(
+            private_api.validator().unwrap(),
+            cloned.validator().unwrap()
+        ));
+        assert!(std::ptr::eq(private_api.config(), cloned.config()));
+    }
+
+    const OPEN_POSITIONS: &str = r#"[{"positionId":1,"symbol":"BTC","side":"BUY","size":"0.3","orderdSize":"0.1","price":"876045","lossGain":"14","leverage":"4","losscutPrice":"766540","timestamp":"2019-03-19T02:15:06.094Z"},{"positionId":2,"symbol":"BTC","side":"BUY","size":"0.1","orderdSize":"0","price":"876045","lossGain":"14","leverage":"4","losscutPrice":"766540","timestamp":"2019-03-19T02:15:06.094Z"},{"positionId":3,"symbol":"BTC","side":"SELL","size":"0.1","orderdSize":"0","price":"876045","lossGain":"14","leverage":"4","losscutPrice":"766540","timestamp":"2019-03-19T02:15:06.094Z"}]"#;
+
+    async fn close(
+        private_api: &PrivateAPI<MockHttpClient>,
+        positions: &[(&PositionId, Size)],
+    ) -> Result<RestResponse<CloseOrder>, Error> {
+        private_api
+            .close_order(
+                &ExecutionType::Market,
+                &Symbol::Btc,
+                &Side::Sell,
+                None,
+                positions,
+            )
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_close_order_with_validator() {
+        let mock = open_positions_mock(OPEN_POSITIONS).on(
+            "POST",
+            "/v1/closeOrder",
+            200,
+            r#"{"status":0,"data":"637000","responsetime":"2019-03-19T01:07:24.557Z"}"#,
+        );
+        let private_api = PrivateAPI::new(mock)
+            .with_credentials(fake_credentials())
+            .with_validator(validator());
+        let (id1, id2, id3, id4) = (
+            PositionId::new("1"),
+            PositionId::new("2"),
+            PositionId::new("3"),
+            PositionId::new("4"),
+        );
+        let resp = close(&private_api, &[(&id1, size("0.2")), (&id2, size("0.1"))]).await;
+        assert_eq!(resp.unwrap().order_id(), "637000");
+        assert!(matches!(
+            close(&private_api, &[]).await,
+            Err(Error::PositionNotSpecifiedError())
+        ));
+        assert!(matches!(
+            close(&private_api, &[(&id1, size("0.1")), (&id1, size("0.1"))]).await,
+            Err(Error::DuplicatePositionError(id)) if id == "1"
+        ));
+        assert!(matches!(
+            close(
+                &private_api,
+                &[(&id1, size("0.0001")), (&id2, size("0.00015"))]
+            )
+            .await,
+            Err(Error::OrderSizeStepError(_, _))
+        ));
+        assert!(matches!(
+            close(&private_api, &[(&id1, size("0.3")), (&id2, size("0.1"))]).await,
+            Err(Error::PositionSizeExceededError(id, _, _)) if id == "1"
+        ));
+        assert!(matches!(
+            close(&private_api, &[(&id2, size("0.1")), (&id3, size("0.1"))]).await,
+            Err(Error::PositionSideMismatchError(id, _)) if id == "3"
+        ));
+        assert!(matches!(
+            close(&private_api, &[(&id4, size("0.1"))]).await,
+            Err(Error::PositionNotFoundError(id)) if id == "4"
+        ));
+
+        let calls = private_api.http_client.calls_to("POST", "/v1/closeOrder");
+        assert_eq!(calls.len(), 1);
+        let settle_position = &calls[0].body.as_ref().unwrap()["settlePosition"];
+        assert_eq!(settle_position[0]["positionId"], 1);
+        assert_eq!(settle_position[1]["positionId"], 2);
+        assert_eq!(settle_position[1]["size"], "0.1");
+    }
+
+    #[tokio::test]
+    async fn test_close_order_without_validator() {
+        let mock = open_positions_mock(OPEN_POSITIONS).on(
+            "POST",
+            "/v1/closeOrder",
+            200,
+            r#"{"status":0,"data":"637000","responsetime":"2019-03-19T01:07:24.557Z"}"#,
+        );
+        let private_api = PrivateAPI::new(mock).with_credentials(fake_credentials());
+        let (id1, id3, id4) = (
+            PositionId::new("1"),
+            PositionId::new("3"),
+            PositionId::new("4"),
+        );
+        // 取引ルールは検証しないが、建玉との整合は検証する。
+        let resp = close(&private_api, &[(&id1, size("0.00015"))]).await;
+        assert_eq!(resp.unwrap().order_id(), "637000");
+        assert!(matches!(
+            close(&private_api, &[(&id1, size("0.3"))]).await,
+            Err(Error::PositionSizeExceededError(id, _, _)) if id == "1"
+        ));
+        assert!(matches!(
+            close(&private_api, &[(&id3, size("0.1"))]).await,
+            Err(Error::PositionSideMismatchError(id, _)) if id == "3"
+        ));
+        assert!(matches!(
+            close(&private_api, &[(&id4, size("0.1"))]).await,
+            Err(Error::PositionNotFoundError(id)) if id == "4"
+        ));
+        assert_eq!(
+            private_api
+                .http_client
+                .calls_to("POST", "/v1/closeOrder")
+                .len(),
+            1
+        );
+    }
+}
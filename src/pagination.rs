@@ -0,0 +1,300 @@
+//! 一覧系APIのページを自動でめくって1件ずつ返すストリームを実装する。
+
+use crate::dto::{Execution, Order, Position, Trade, DEFAULT_COUNT};
+use crate::error::Error;
+use chrono::{DateTime, Utc};
+use futures::stream::{self, Stream};
+use std::collections::VecDeque;
+use std::future::Future;
+
+/// 一覧系APIのページを自動でめくるときのオプション。デフォルトでは1ページ当たり100件ずつ、空のページが返ってくるまで取得する。
+#[derive(Clone, Debug, Default)]
+pub struct PageOptions {
+    /// 1ページ当たりの取得件数。
+    count: Option<i32>,
+
+    /// 返す件数の上限。
+    limit: Option<usize>,
+
+    /// この時刻より前の項目は返さない。
+    since: Option<DateTime<Utc>>,
+
+    /// この時刻より後の項目は返さない。
+    until: Option<DateTime<Utc>>,
+}
+
+impl PageOptions {
+    /// デフォルトのオプションを作成する。
+    pub fn new() -> PageOptions {
+        PageOptions::default()
+    }
+
+    /// 1ページ当たりの取得件数を指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - 取得件数。最大値は100。
+    ///
+    pub fn with_count(mut self, count: i32) -> PageOptions {
+        self.count = Some(count);
+        self
+    }
+
+    /// 返す件数の上限を指定する。上限に達したら次のページを取得しない。
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - 件数の上限。
+    ///
+    pub fn with_limit(mut self, limit: usize) -> PageOptions {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// この時刻より前の項目を返さないようにする。
+    /// GMOコインの一覧は新しい順に並んでいるので、1ページ分がすべてこの時刻より前だったら次のページを取得しない。
+    ///
+    /// # Arguments
+    ///
+    /// * `since` - 時刻。
+    ///
+    pub fn with_since(mut self, since: DateTime<Utc>) -> PageOptions {
+        self.since = Some(since);
+        self
+    }
+
+    /// この時刻より後の項目を返さないようにする。
+    ///
+    /// # Arguments
+    ///
+    /// * `until` - 時刻。
+    ///
+    pub fn with_until(mut self, until: DateTime<Utc>) -> PageOptions {
+        self.until = Some(until);
+        self
+    }
+
+    /// 項目の時刻が`since`から`until`までの範囲に入っているか？
+    fn contains(&self, timestamp: DateTime<Utc>) -> bool {
+        !matches!(self.since, Some(s) if timestamp < s)
+            && !matches!(self.until, Some(u) if timestamp > u)
+    }
+}
+
+/// 一覧系APIが返す項目。時刻で絞り込むのに使う。
+pub trait PageItem {
+    /// 項目の時刻。
+    fn timestamp(&self) -> DateTime<Utc>;
+}
+
+impl PageItem for Order {
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+}
+
+impl PageItem for Execution {
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+}
+
+impl PageItem for Position {
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+}
+
+impl PageItem for Trade {
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+}
+
+/// ページをめくる状態。
+struct Pager<T, F> {
+    fetch: F,
+    options: PageOptions,
+    page: i32,
+    buffer: VecDeque<T>,
+    returned: usize,
+    finished: bool,
+}
+
+/// ページ番号と取得件数を受け取って1ページ分の項目を返す関数から、すべての項目を1件ずつ返すストリームを作る。
+/// 空のページが返ってくるか、件数の上限や`since`に達したら止まる。エラーが起きたらそのエラーを返して止まる。
+///
+/// # Arguments
+///
+/// * `options` - オプション。
+/// * `fetch` - ページ番号と取得件数を受け取って1ページ分の項目を返す関数。
+///
+pub(crate) fn paginate<T, F, Fut>(
+    options: PageOptions,
+    fetch: F,
+) -> impl Stream<Item = Result<T, Error>>
+where
+    T: PageItem,
+    F: Fn(i32, i32) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, Error>>,
+{
+    let pager = Pager {
+        fetch,
+        options,
+        page: 1,
+        buffer: VecDeque::new(),
+        returned: 0,
+        finished: false,
+    };
+    stream::unfold(pager, |mut pager| async move {
+        loop {
+            if matches!(pager.options.limit, Some(limit) if pager.returned >= limit) {
+                return None;
+            }
+            if let Some(item) = pager.buffer.pop_front() {
+                pager.returned += 1;
+                return Some((Ok(item), pager));
+            }
+            if pager.finished {
+                return None;
+            }
+            let count = pager.options.count.unwrap_or(DEFAULT_COUNT);
+            let list = match (pager.fetch)(pager.page, count).await {
+                Ok(list) => list,
+                Err(e) => {
+                    pager.finished = true;
+                    return Some((Err(e), pager));
+                }
+            };
+            if list.is_empty() {
+                return None;
+            }
+            pager.page += 1;
+            if let Some(since) = pager.options.since {
+                if list.iter().all(|item| item.timestamp() < since) {
+                    pager.finished = true;
+                }
+            }
+            let options = &pager.options;
+            let items: Vec<T> = list
+                .into_iter()
+                .filter(|item| options.contains(item.timestamp()))
+                .collect();
+            pager.buffer.extend(items);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockHttpClient;
+    use crate::price::price;
+    use crate::public::PublicAPI;
+    use crate::response::RawResponse;
+    use crate::symbol::Symbol;
+    use chrono::TimeZone;
+    use futures::{StreamExt, TryStreamExt};
+    use std::sync::Mutex;
+
+    struct Item(i64);
+
+    impl PageItem for Item {
+        fn timestamp(&self) -> DateTime<Utc> {
+            Utc.timestamp_opt(self.0, 0).unwrap()
+        }
+    }
+
+    /// 時刻が100から1まで新しい順に並んだ一覧をページに分けて返す。
+    async fn fetch(page: i32, count: i32) -> Result<Vec<Item>, Error> {
+        let start = 100 - (page as i64 - 1) * count as i64;
+        Ok((0..count as i64)
+            .map(|i| start - i)
+            .filter(|t| *t >= 1)
+            .map(Item)
+            .collect())
+    }
+
+    #[tokio::test]
+    async fn test_paginate_until_empty_page() {
+        let pages = Mutex::new(Vec::new());
+        let items: Vec<Item> = paginate(PageOptions::new().with_count(30), |page, count| {
+            pages.lock().unwrap().push(page);
+            fetch(page, count)
+        })
+        .try_collect()
+        .await
+        .unwrap();
+        assert_eq!(items.len(), 100);
+        assert_eq!(items[99].0, 1);
+        assert_eq!(*pages.lock().unwrap(), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[tokio::test]
+    async fn test_paginate_with_limit_and_time_range() {
+        let pages = Mutex::new(Vec::new());
+        let options = PageOptions::new()
+            .with_count(10)
+            .with_since(Utc.timestamp_opt(55, 0).unwrap())
+            .with_until(Utc.timestamp_opt(90, 0).unwrap());
+        let items: Vec<Item> = paginate(options, |page, count| {
+            pages.lock().unwrap().push(page);
+            fetch(page, count)
+        })
+        .try_collect()
+        .await
+        .unwrap();
+        assert_eq!(items.first().unwrap().0, 90);
+        assert_eq!(items.last().unwrap().0, 55);
+        assert_eq!(*pages.lock().unwrap(), vec![1, 2, 3, 4, 5, 6]);
+
+        let items: Vec<Item> = paginate(PageOptions::new().with_limit(3), fetch)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(items.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_paginate_stops_on_error() {
+        let stream = paginate(
+            PageOptions::new().with_count(40),
+            |page, count| async move {
+                if page == 2 {
+                    Err(Error::UnknownError)
+                } else {
+                    fetch(page, count).await
+                }
+            },
+        );
+        let results: Vec<Result<Item, Error>> = stream.collect().await;
+        assert_eq!(results.len(), 41);
+        assert!(results[40].is_err());
+    }
+
+    #[tokio::test]
+    async fn test_trades_all() {
+        let mock = MockHttpClient::new().on_fn("GET", "/v1/trades", |request| {
+            let list = if request.url.contains("page=1&") {
+                r#"[{"price":"750760","side":"BUY","size":"0.1","timestamp":"2018-03-30T12:34:56.789Z"},{"price":"750700","side":"SELL","size":"0.2","timestamp":"2018-03-30T12:34:50.789Z"}]"#
+            } else {
+                "[]"
+            };
+            Ok(RawResponse {
+                http_status_code: 200,
+                body_text: format!(
+                    r#"{{"status":0,"data":{{"pagination":{{"currentPage":1,"count":2}},"list":{}}},"responsetime":"2019-03-28T09:28:07.980Z"}}"#,
+                    list
+                ),
+            })
+        });
+        let public_api = PublicAPI::new(mock);
+        let trades = public_api
+            .trades_all(&Symbol::Btc, PageOptions::new().with_count(2))
+            .await
+            .unwrap();
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[1].price, price("750700"));
+        assert_eq!(public_api.http_client.calls().len(), 2);
+    }
+}
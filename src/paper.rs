@@ -0,0 +1,382 @@
+//! ペーパートレード用のHTTPクライアントを実装する。`paper`フィーチャーを有効にすると使える。
+//! Public APIは本物の(または記録した)相場情報を使い、Private APIは模擬口座で処理するので、戦略を書き換えずに紙上で動かせる。
+
+use crate::client_config::ClientConfig;
+use crate::error::Error;
+use crate::exchange::engine::{Engine, Level, Quote};
+use crate::exchange::{self, parse_query, DEFAULT_MAKER_FEE_RATE, DEFAULT_TAKER_FEE_RATE};
+use crate::headers::Headers;
+use crate::http_client::HttpClient;
+use crate::public::orderbooks::Orderbooks;
+use crate::public::ticker::Ticker;
+use crate::response::RawResponse;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+
+/// 板情報APIのパス。
+const ORDERBOOKS_API_PATH: &str = "/v1/orderbooks";
+
+/// 最新レートAPIのパス。
+const TICKER_API_PATH: &str = "/v1/ticker";
+
+/// Public APIへのリクエストは内側のHTTPクライアントにそのまま渡し、Private APIへのリクエストは模擬口座で処理するHTTPクライアント。
+/// `PrivateAPI<PaperClient<Reqwest>>`のように使うと、本番の相場で注文を模擬約定させられる。
+/// 内側を`ReplayClient`にすると記録した相場で、`MockHttpClient`にすると決まった相場で動かせる。
+///
+/// Private APIを呼ぶたびに、リクエストの銘柄と注文や建玉がある銘柄の板情報を内側のHTTPクライアントから取得し、
+/// 板に並んでいる数量の範囲で成行注文と指値注文を約定させる。板に並んでいる数量より大きい注文は部分約定する。
+/// Public APIの最新レートや板情報のレスポンスを受け取ったときも、その相場で指値/逆指値注文の約定とロスカットを判定する。
+/// 署名は検証しないので、APIキーとAPIシークレットは何でもよい。
+pub struct PaperClient<T: HttpClient> {
+    inner: T,
+    config: ClientConfig,
+    engine: Mutex<Engine>,
+}
+
+impl<T: HttpClient + Sync> PaperClient<T> {
+    /// ペーパートレード用のHTTPクライアントを作成する。
+    /// 円残高は100万円、レバレッジは2倍、手数料率はメイカー-0.01%、テイカー0.05%。
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - Public APIへのリクエストを渡すHTTPクライアント。
+    ///
+    pub fn new(inner: T) -> PaperClient<T> {
+        let mut engine = Engine::new(1_000_000, 2);
+        engine.set_fee_rates(DEFAULT_MAKER_FEE_RATE, DEFAULT_TAKER_FEE_RATE);
+        PaperClient {
+            inner,
+            config: ClientConfig::default(),
+            engine: Mutex::new(engine),
+        }
+    }
+
+    /// Public API, Private APIのベースURLを指定する。`PublicAPI`, `PrivateAPI`に渡す設定と同じものを指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - 設定。
+    ///
+    pub fn with_config(mut self, config: ClientConfig) -> PaperClient<T> {
+        self.config = config;
+        self
+    }
+
+    /// 模擬口座の円残高を指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `balance` - 円残高。
+    ///
+    pub fn with_balance(self, balance: i64) -> PaperClient<T> {
+        self.engine.lock().unwrap().set_balance(balance);
+        self
+    }
+
+    /// レバレッジを指定する。証拠金とロスカットレートの初期値の計算に使う。
+    ///
+    /// # Arguments
+    ///
+    /// * `leverage` - レバレッジ。
+    ///
+    pub fn with_leverage(self, leverage: i64) -> PaperClient<T> {
+        self.engine.lock().unwrap().set_leverage(leverage);
+        self
+    }
+
+    /// 約定代金に対する手数料率を指定する。負の手数料率は手数料を受け取ることを表す。
+    ///
+    /// # Arguments
+    ///
+    /// * `maker_fee_rate` - 板に並んでいた指値注文が約定したときの手数料率。例: `-0.0001`
+    /// * `taker_fee_rate` - 成行注文や即時約定した指値注文の手数料率。例: `0.0005`
+    ///
+    pub fn with_fee_rates(self, maker_fee_rate: f64, taker_fee_rate: f64) -> PaperClient<T> {
+        self.engine
+            .lock()
+            .unwrap()
+            .set_fee_rates(maker_fee_rate, taker_fee_rate);
+        self
+    }
+
+    /// 内側のHTTPクライアントを取得する。
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// 確定した損益と手数料を含む模擬口座の円残高を取得する。
+    pub fn balance(&self) -> i64 {
+        self.engine.lock().unwrap().balance()
+    }
+
+    /// リクエストを振り分ける。Private APIへのリクエストは模擬口座で処理し、それ以外は内側のHTTPクライアントに渡す。
+    async fn send(
+        &self,
+        method: &str,
+        url: String,
+        headers: &Headers,
+        parameters: Option<&Value>,
+    ) -> Result<RawResponse, Error> {
+        if let Some(path) = url.strip_prefix(self.config.private_endpoint()) {
+            return self.private(method, path, parameters).await;
+        }
+        let response = match (method, parameters) {
+            ("GET", _) => self.inner.get(url.clone(), headers).await?,
+            ("PUT", Some(p)) => self.inner.put(url.clone(), headers, p).await?,
+            ("DELETE", Some(p)) => self.inner.delete(url.clone(), headers, p).await?,
+            (_, p) => {
+                self.inner
+                    .post(url.clone(), headers, p.unwrap_or(&Value::Null))
+                    .await?
+            }
+        };
+        if let Some(path) = url.strip_prefix(self.config.public_endpoint()) {
+            self.observe(path, &response);
+        }
+        Ok(response)
+    }
+
+    /// Private APIへのリクエストを模擬口座で処理する。処理する前に関係する銘柄の板情報を取得する。
+    async fn private(
+        &self,
+        method: &str,
+        path_and_query: &str,
+        parameters: Option<&Value>,
+    ) -> Result<RawResponse, Error> {
+        let (path, query) = match path_and_query.find('?') {
+            Some(i) => (&path_and_query[..i], Some(&path_and_query[i + 1..])),
+            None => (path_and_query, None),
+        };
+        let query = parse_query(query);
+        let mut symbols: BTreeSet<String> = self.engine.lock().unwrap().symbols_in_use();
+        if let Some(symbol) = parameters
+            .and_then(|p| p.get("symbol"))
+            .and_then(|s| s.as_str())
+            .or_else(|| exchange::query_symbol(&query).ok())
+        {
+            symbols.insert(symbol.to_string());
+        }
+        for symbol in symbols {
+            let url = format!(
+                "{}{}?symbol={}",
+                self.config.public_endpoint(),
+                ORDERBOOKS_API_PATH,
+                symbol
+            );
+            let response = self
+                .inner
+                .get(url, &Headers::create_empty_headers())
+                .await?;
+            self.observe(ORDERBOOKS_API_PATH, &response);
+        }
+        let body = parameters.map(|p| p.to_string()).unwrap_or_default();
+        let result = exchange::private_route(
+            &mut self.engine.lock().unwrap(),
+            method,
+            path,
+            &query,
+            &body,
+        );
+        let (http_status_code, body_text) = exchange::to_response(result);
+        Ok(RawResponse {
+            http_status_code,
+            body_text,
+        })
+    }
+
+    /// Public APIのレスポンスから相場を読み取り、模擬口座の注文の約定とロスカットを判定する。
+    /// 読み取れないレスポンスは無視する。
+    fn observe(&self, path: &str, response: &RawResponse) {
+        let path = path.split('?').next().unwrap_or("");
+        let mut engine = self.engine.lock().unwrap();
+        if path == ORDERBOOKS_API_PATH {
+            let orderbooks: Orderbooks = match serde_json::from_str(&response.body_text) {
+                Ok(o) => o,
+                Err(_) => return,
+            };
+            let data = orderbooks.data;
+            let levels = |list: Vec<crate::public::orderbooks::PriceAndSize>| -> Vec<Level> {
+                list.into_iter()
+                    .map(|p| Level {
+                        price: p.price,
+                        size: p.size,
+                    })
+                    .collect()
+            };
+            let (bid, ask) = match (data.bids.first(), data.asks.first()) {
+                (Some(b), Some(a)) => (b.price, a.price),
+                _ => return,
+            };
+            engine.set_quote(
+                &data.symbol,
+                Quote {
+                    bid,
+                    ask,
+                    bids: levels(data.bids),
+                    asks: levels(data.asks),
+                },
+            );
+        } else if path == TICKER_API_PATH {
+            let ticker: Ticker = match serde_json::from_str(&response.body_text) {
+                Ok(t) => t,
+                Err(_) => return,
+            };
+            for data in ticker.data {
+                // 気配が変わっていなければ直前の板情報を使い続ける。変わった場合は板の厚みがわからないので、気配でいくらでも約定するとみなす。
+                let unchanged = matches!(engine.quote(&data.symbol), Some(q) if q.bid == data.bid && q.ask == data.ask);
+                if unchanged {
+                    continue;
+                }
+                let quote = Quote {
+                    bid: data.bid,
+                    ask: data.ask,
+                    bids: Vec::new(),
+                    asks: Vec::new(),
+                };
+                engine.set_quote(&data.symbol, quote);
+            }
+        } else {
+            return;
+        }
+        engine.update();
+    }
+}
+
+#[async_trait]
+impl<T: HttpClient + Sync + Send> HttpClient for PaperClient<T> {
+    async fn get(&self, url: String, headers: &Headers) -> Result<RawResponse, Error> {
+        self.send("GET", url, headers, None).await
+    }
+
+    async fn post(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        self.send("POST", url, headers, Some(parameters)).await
+    }
+
+    async fn put(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        self.send("PUT", url, headers, Some(parameters)).await
+    }
+
+    async fn delete(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        self.send("DELETE", url, headers, Some(parameters)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::tests::fake_credentials;
+    use crate::execution_type::ExecutionType;
+    use crate::mock::MockHttpClient;
+    use crate::price::price;
+    use crate::private::PrivateAPI;
+    use crate::public::PublicAPI;
+    use crate::side::Side;
+    use crate::size::size;
+    use crate::symbol::Symbol;
+
+    const ORDERBOOKS_RESPONSE: &str = r#"{"status":0,"data":{"asks":[{"price":"1000000","size":"0.1"},{"price":"1001000","size":"0.2"}],"bids":[{"price":"999000","size":"0.1"},{"price":"998000","size":"0.2"}],"symbol":"BTC_JPY"},"responsetime":"2019-03-19T02:15:06.026Z"}"#;
+
+    const TICKER_RESPONSE: &str = r#"{"status":0,"data":[{"ask":"990000","bid":"989000","high":"1010000","last":"990000","low":"980000","symbol":"BTC_JPY","timestamp":"2019-03-19T02:15:06.014Z","volume":"100"}],"responsetime":"2019-03-19T02:15:06.014Z"}"#;
+
+    fn paper() -> PaperClient<MockHttpClient> {
+        PaperClient::new(
+            MockHttpClient::new()
+                .on("GET", "/v1/orderbooks", 200, ORDERBOOKS_RESPONSE)
+                .on("GET", "/v1/ticker", 200, TICKER_RESPONSE),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_market_order_walks_orderbook() {
+        let private_api = PrivateAPI::new(paper()).with_credentials(fake_credentials());
+        let order = private_api
+            .order(
+                &ExecutionType::Market,
+                &Symbol::BtcJpy,
+                &Side::Buy,
+                size("0.5"),
+                None,
+            )
+            .await
+            .unwrap();
+        let executions = private_api
+            .executions_with_order_id(order.order_id())
+            .await
+            .unwrap();
+        let executions = executions.executions();
+        assert_eq!(executions.len(), 2);
+        assert_eq!(executions[0].price, price("1000000"));
+        assert_eq!(executions[0].fee, 50);
+        assert_eq!(executions[1].size, size("0.2"));
+        let positions = private_api.open_positions(&Symbol::BtcJpy).await.unwrap();
+        assert_eq!(positions.open_positions().len(), 2);
+        assert_eq!(private_api.http_client.balance(), 1_000_000 - 50 - 100);
+        assert!(!private_api
+            .http_client
+            .inner()
+            .calls_to("GET", "/v1/orderbooks")
+            .is_empty());
+        assert!(private_api
+            .http_client
+            .inner()
+            .calls_to("POST", "*")
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_limit_order_fills_on_observed_ticker() {
+        let paper = paper();
+        let private_api = PrivateAPI::new(paper).with_credentials(fake_credentials());
+        let order = private_api
+            .order(
+                &ExecutionType::Limit,
+                &Symbol::BtcJpy,
+                &Side::Buy,
+                size("0.1"),
+                Some(price("995000")),
+            )
+            .await
+            .unwrap();
+        let active = private_api.active_orders(&Symbol::BtcJpy).await.unwrap();
+        assert_eq!(active.active_orders().len(), 1);
+
+        let public_api = PublicAPI::new(private_api.http_client);
+        public_api.ticker(&Symbol::BtcJpy).await.unwrap();
+        let engine = public_api.http_client.engine.lock().unwrap();
+        let order = engine
+            .order(order.order_id().as_str().parse().unwrap())
+            .unwrap();
+        assert_eq!(order.executed_size, size("0.1"));
+        assert_eq!(engine.executions()[0].fee, -10);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_unknown_path() {
+        let paper = paper();
+        let response = paper
+            .get(
+                "https://api.coin.z.com/private/v1/unknown".to_string(),
+                &Headers::create_empty_headers(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.http_status_code, 404);
+    }
+}
@@ -0,0 +1,93 @@
+//! 価格を表す型を定義する。
+//! XRP_JPYのように価格に小数を含む銘柄があるので整数ではなく`Size`と同じ型を使う。
+//! `decimal`フィーチャーを有効にすると、誤差の出ない十進数型(`rust_decimal::Decimal`)になる。
+
+/// 価格を表す型。
+#[cfg(not(feature = "decimal"))]
+pub type Price = f64;
+
+/// 価格を表す型。
+#[cfg(feature = "decimal")]
+pub type Price = rust_decimal::Decimal;
+
+/// `Price`をf64に変換する。
+#[cfg(not(feature = "decimal"))]
+pub(crate) fn price_to_f64(price: Price) -> f64 {
+    price
+}
+
+/// `Price`をf64に変換する。
+#[cfg(feature = "decimal")]
+pub(crate) fn price_to_f64(price: Price) -> f64 {
+    use rust_decimal::prelude::ToPrimitive;
+    price.to_f64().unwrap_or(f64::NAN)
+}
+
+/// 整数から`Price`を作る。
+#[cfg(all(
+    not(feature = "decimal"),
+    any(test, feature = "simulator", feature = "paper", feature = "backtest")
+))]
+pub(crate) fn price_from_i64(price: i64) -> Price {
+    price as f64
+}
+
+/// 整数から`Price`を作る。
+#[cfg(all(
+    feature = "decimal",
+    any(test, feature = "simulator", feature = "paper", feature = "backtest")
+))]
+pub(crate) fn price_from_i64(price: i64) -> Price {
+    Price::from(price)
+}
+
+/// 文字列から`Price`を作る。テストで使う。
+#[cfg(test)]
+pub(crate) fn price(s: &str) -> Price {
+    s.parse().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::Credentials;
+    use crate::execution_type::ExecutionType;
+    use crate::mock::MockHttpClient;
+    use crate::private::PrivateAPI;
+    use crate::side::Side;
+    use crate::size::size;
+    use crate::symbol::Symbol;
+    use crate::time_in_force::TimeInForce;
+
+    #[tokio::test]
+    async fn test_fractional_price() {
+        let p = price("52.123");
+        assert_eq!(p.to_string(), "52.123");
+        assert_eq!(price_to_f64(p), 52.123);
+        assert_eq!(price("52").to_string(), "52");
+
+        let mock = MockHttpClient::new().on(
+            "POST",
+            "/v1/order",
+            200,
+            r#"{"status":0,"data":"637000","responsetime":"2019-03-19T01:07:24.557Z"}"#,
+        );
+        let private_api = PrivateAPI::new(mock).with_credentials(Credentials::new("key", "secret"));
+        private_api
+            .order_with_options(
+                &ExecutionType::Limit,
+                &Symbol::XrpJpy,
+                &Side::Buy,
+                size("10"),
+                Some(p),
+                &TimeInForce::Fas,
+                Some(price("50.5")),
+            )
+            .await
+            .unwrap();
+        let calls = private_api.http_client.calls();
+        let body = calls[0].body.as_ref().unwrap();
+        assert_eq!(body["price"], "52.123");
+        assert_eq!(body["losscutPrice"], "50.5");
+    }
+}
@@ -18,11 +18,23 @@ pub mod open_positions;
 pub mod order;
 pub mod orders;
 pub mod position_summary;
+pub mod ws_auth;
 
-use crate::dto::{DEFAULT_COUNT, DEFAULT_PAGE};
+use crate::batch::call_in_chunks;
+use crate::client_config::ClientConfig;
+use crate::close_policy::{select_positions, ClosePolicy};
+use crate::credentials::{
+    Credentials, CredentialsProvider, EnvCredentialsProvider, StaticCredentialsProvider,
+};
+use crate::dto::{self, DEFAULT_COUNT, DEFAULT_PAGE};
 use crate::error::Error;
 use crate::execution_type::ExecutionType;
 use crate::http_client::HttpClient;
+use crate::id::{ExecutionId, OrderId, PositionId};
+use crate::order_builder::OrderRequest;
+use crate::order_validator::OrderValidator;
+use crate::pagination::{paginate, PageOptions};
+use crate::price::Price;
 use crate::private::active_orders::{request_active_orders, ActiveOrders};
 use crate::private::assets::{request_assets, Assets};
 use crate::private::cancel_bulk_order::{request_cancel_bulk_order, CancelBulkOrder};
@@ -41,15 +53,79 @@ use crate::private::open_positions::{request_open_positions, OpenPositions};
 use crate::private::order::{request_order, Order};
 use crate::private::orders::{request_orders, Orders};
 use crate::private::position_summary::{request_position_summary, PositionSummary};
+use crate::private::ws_auth::{
+    request_create_ws_auth, request_extend_ws_auth, request_revoke_ws_auth, ExtendWsAuth,
+    RevokeWsAuth, WsAuth,
+};
 use crate::response::RestResponse;
 use crate::settle_type::SettleType;
 use crate::side::Side;
+use crate::size::{size_exceeds, Size};
 use crate::symbol::Symbol;
 use crate::time_in_force::TimeInForce;
+use futures::{Stream, TryStreamExt};
+use std::sync::Arc;
 
 /// Private API。
+#[derive(Clone)]
 pub struct PrivateAPI<T: HttpClient + std::marker::Sync + std::marker::Send> {
     pub http_client: T,
+
+    /// 接続先のURLやタイムアウトの設定。複製しても共有する。
+    config: Arc<ClientConfig>,
+
+    /// APIキー, APIシークレットの提供元。
+    credentials_provider: Arc<dyn CredentialsProvider>,
+
+    /// 注文パラメータの検証に使う。設定されていない場合は検証しない。複製しても共有する。
+    validator: Option<Arc<OrderValidator>>,
+}
+
+/// 決済する建玉が建玉一覧に存在し、銘柄と売買区分が決済注文と整合していて、決済数量が決済できる数量を超えていないか検証する。
+///
+/// # Arguments
+///
+/// * `open_positions` - 建玉一覧APIで取得した建玉。
+/// * `symbol` - 銘柄。
+/// * `side` - 決済注文の売買区分。
+/// * `positions` - 決済する建玉IDと決済数量の組。
+///
+fn validate_close_positions(
+    open_positions: &[dto::Position],
+    symbol: &Symbol,
+    side: &Side,
+    positions: &[(&PositionId, Size)],
+) -> Result<(), Error> {
+    for (position_id, size) in positions {
+        let position = match open_positions
+            .iter()
+            .find(|p| &p.position_id == *position_id)
+        {
+            Some(p) => p,
+            None => return Err(Error::PositionNotFoundError((*position_id).clone())),
+        };
+        if position.symbol != symbol.to_string() {
+            return Err(Error::PositionSymbolMismatchError(
+                (*position_id).clone(),
+                position.symbol.clone(),
+            ));
+        }
+        if position.side == *side {
+            return Err(Error::PositionSideMismatchError(
+                (*position_id).clone(),
+                position.side.to_string(),
+            ));
+        }
+        let closable = position.size - position.orderd_size;
+        if size_exceeds(*size, closable) {
+            return Err(Error::PositionSizeExceededError(
+                (*position_id).clone(),
+                *size,
+                closable,
+            ));
+        }
+    }
+    Ok(())
 }
 
 fn get_default_time_in_force(execution_type: &ExecutionType) -> TimeInForce {
@@ -60,13 +136,134 @@ fn get_default_time_in_force(execution_type: &ExecutionType) -> TimeInForce {
 }
 
 impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
+    /// Private APIを作成する。APIキー, APIシークレットは環境変数`GMO_COIN_API_KEY`, `GMO_COIN_API_SECRET`から読み取る。
+    /// 注文パラメータの検証は行わない。
+    ///
+    /// # Arguments
+    ///
+    /// * `http_client` - HTTPクライアント。
+    ///
+    pub fn new(http_client: T) -> PrivateAPI<T> {
+        PrivateAPI {
+            http_client,
+            config: Arc::new(ClientConfig::default()),
+            credentials_provider: Arc::new(EnvCredentialsProvider::new()),
+            validator: None,
+        }
+    }
+
+    /// 接続先のURLやタイムアウトを指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - クライアントの設定。
+    ///
+    pub fn with_config(mut self, config: ClientConfig) -> PrivateAPI<T> {
+        self.config = Arc::new(config);
+        self
+    }
+
+    /// 接続先のURLやタイムアウトの設定を取得する。
+    pub fn config(&self) -> &ClientConfig {
+        &self.config
+    }
+
+    /// APIキー, APIシークレットを指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `credentials` - APIキー, APIシークレット。
+    ///
+    pub fn with_credentials(self, credentials: Credentials) -> PrivateAPI<T> {
+        self.with_credentials_provider(StaticCredentialsProvider::new(credentials))
+    }
+
+    /// APIキー, APIシークレットの提供元を指定する。APIを呼び出すたびに提供元から取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `credentials_provider` - APIキー, APIシークレットの提供元。
+    ///
+    pub fn with_credentials_provider(
+        mut self,
+        credentials_provider: impl CredentialsProvider + 'static,
+    ) -> PrivateAPI<T> {
+        self.credentials_provider = Arc::new(credentials_provider);
+        self
+    }
+
+    fn credentials(&self) -> Result<Credentials, Error> {
+        self.credentials_provider.credentials()
+    }
+
+    /// 注文パラメータの検証を有効にする。
+    /// 新規注文、決済注文、一括決済注文、注文変更APIを呼び出す前に、取引ルールを満たしているか検証する。
+    ///
+    /// # Arguments
+    ///
+    /// * `validator` - 取引ルールを保持したバリデータ。
+    ///
+    pub fn with_validator(mut self, validator: OrderValidator) -> PrivateAPI<T> {
+        self.validator = Some(Arc::new(validator));
+        self
+    }
+
+    /// 設定されているバリデータを取得する。
+    pub fn validator(&self) -> Option<&OrderValidator> {
+        self.validator.as_deref()
+    }
+
+    fn validate_order(
+        &self,
+        symbol: &Symbol,
+        size: Size,
+        price: Option<Price>,
+        losscut_price: Option<Price>,
+    ) -> Result<(), Error> {
+        match &self.validator {
+            Some(v) => v.validate_order(symbol, size, price, losscut_price),
+            None => Ok(()),
+        }
+    }
+
+    /// 注文変更のパラメータを検証する。注文の銘柄を知るために注文情報取得APIを呼び出す。
+    async fn validate_change_order(
+        &self,
+        order_id: &OrderId,
+        price: Price,
+        losscut_price: Option<Price>,
+    ) -> Result<(), Error> {
+        let validator = match &self.validator {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+        let response = request_orders(
+            &self.http_client,
+            &self.config,
+            &self.credentials()?,
+            &[order_id],
+        )
+        .await?;
+        // 注文が見つからない場合はAPIがエラーを返すので、ここでは検証しない。
+        let order = match response.orders().first() {
+            Some(o) => o,
+            None => return Ok(()),
+        };
+        validator.validate_price_by_name(&order.symbol, price)?;
+        if let Some(lp) = losscut_price {
+            validator.validate_price_by_name(&order.symbol, lp)?;
+        }
+        Ok(())
+    }
+
     /// 余力情報APIを呼び出す。
     ///
     /// # Arguments
     ///
     ///
     pub async fn margin(&self) -> Result<RestResponse<Margin>, Error> {
-        let response = request_margin(&self.http_client).await?;
+        let response =
+            request_margin(&self.http_client, &self.config, &self.credentials()?).await?;
         Ok(response)
     }
 
@@ -76,18 +273,33 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
     ///
     ///
     pub async fn assets(&self) -> Result<RestResponse<Assets>, Error> {
-        let response = request_assets(&self.http_client).await?;
+        let response =
+            request_assets(&self.http_client, &self.config, &self.credentials()?).await?;
         Ok(response)
     }
 
     /// 注文情報取得APIを呼び出す。
+    /// 注文IDが10件を超える場合は10件ずつに分けて並行に呼び出し、注文情報を1つの配列にまとめて返す。
+    /// 並行数は`ClientConfig::with_batch_concurrency`で指定する。
     ///
     /// # Arguments
     ///
-    /// * `order_ids` - 取得する注文の注文ID。最大10件まで指定できる。
+    /// * `order_ids` - 取得する注文の注文ID。重複して指定すると`Error::DuplicateIdError`を返す。
     ///
-    pub async fn orders(&self, order_ids: &[&str]) -> Result<RestResponse<Orders>, Error> {
-        let response = request_orders(&self.http_client, &order_ids).await?;
+    pub async fn orders(&self, order_ids: &[&OrderId]) -> Result<RestResponse<Orders>, Error> {
+        let credentials = self.credentials()?;
+        let responses = call_in_chunks(
+            order_ids,
+            orders::MAX_ORDER_IDS,
+            self.config.batch_concurrency(),
+            |chunk| request_orders(&self.http_client, &self.config, &credentials, chunk),
+        )
+        .await?;
+        let mut responses = responses.into_iter();
+        let mut response = responses.next().ok_or(Error::EmptyResponseError())?;
+        for r in responses {
+            response.extend(r);
+        }
         Ok(response)
     }
 
@@ -101,8 +313,15 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
         &self,
         symbol: &Symbol,
     ) -> Result<RestResponse<ActiveOrders>, Error> {
-        let response =
-            request_active_orders(&self.http_client, &symbol, DEFAULT_PAGE, DEFAULT_COUNT).await?;
+        let response = request_active_orders(
+            &self.http_client,
+            &self.config,
+            &self.credentials()?,
+            symbol,
+            DEFAULT_PAGE,
+            DEFAULT_COUNT,
+        )
+        .await?;
         Ok(response)
     }
 
@@ -120,10 +339,53 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
         page: i32,
         count: i32,
     ) -> Result<RestResponse<ActiveOrders>, Error> {
-        let response = request_active_orders(&self.http_client, &symbol, page, count).await?;
+        let response = request_active_orders(
+            &self.http_client,
+            &self.config,
+            &self.credentials()?,
+            symbol,
+            page,
+            count,
+        )
+        .await?;
         Ok(response)
     }
 
+    /// 有効注文一覧APIのページを自動でめくり、注文を1件ずつ返すストリームを作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `options` - 1ページ当たりの取得件数、返す件数の上限、時刻の範囲。
+    ///
+    pub fn active_orders_stream<'a>(
+        &'a self,
+        symbol: &'a Symbol,
+        options: PageOptions,
+    ) -> impl Stream<Item = Result<dto::Order, Error>> + 'a {
+        paginate(options, move |page, count| async move {
+            let response = self.active_orders_with_options(symbol, page, count).await?;
+            Ok(response.body.data.list)
+        })
+    }
+
+    /// 有効注文一覧APIのページを自動でめくり、注文をすべて取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `options` - 1ページ当たりの取得件数、返す件数の上限、時刻の範囲。
+    ///
+    pub async fn active_orders_all(
+        &self,
+        symbol: &Symbol,
+        options: PageOptions,
+    ) -> Result<Vec<dto::Order>, Error> {
+        self.active_orders_stream(symbol, options)
+            .try_collect()
+            .await
+    }
+
     /// 約定情報取得APIを呼び出す。指定した注文IDの約定情報が取得できる。
     ///
     /// # Arguments
@@ -132,9 +394,15 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
     ///
     pub async fn executions_with_order_id(
         &self,
-        order_id: &str,
+        order_id: &OrderId,
     ) -> Result<RestResponse<Executions>, Error> {
-        let response = request_executions_with_order_id(&self.http_client, &order_id).await?;
+        let response = request_executions_with_order_id(
+            &self.http_client,
+            &self.config,
+            &self.credentials()?,
+            order_id,
+        )
+        .await?;
         Ok(response)
     }
 
@@ -146,10 +414,15 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
     ///
     pub async fn executions_with_execution_id(
         &self,
-        execution_id: &str,
+        execution_id: &ExecutionId,
     ) -> Result<RestResponse<Executions>, Error> {
-        let response =
-            request_executions_with_execution_id(&self.http_client, &execution_id).await?;
+        let response = request_executions_with_execution_id(
+            &self.http_client,
+            &self.config,
+            &self.credentials()?,
+            execution_id,
+        )
+        .await?;
         Ok(response)
     }
 
@@ -163,9 +436,15 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
         &self,
         symbol: &Symbol,
     ) -> Result<RestResponse<LatestExecutions>, Error> {
-        let response =
-            request_latest_executions(&self.http_client, &symbol, DEFAULT_PAGE, DEFAULT_COUNT)
-                .await?;
+        let response = request_latest_executions(
+            &self.http_client,
+            &self.config,
+            &self.credentials()?,
+            symbol,
+            DEFAULT_PAGE,
+            DEFAULT_COUNT,
+        )
+        .await?;
         Ok(response)
     }
 
@@ -183,10 +462,55 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
         page: i32,
         count: i32,
     ) -> Result<RestResponse<LatestExecutions>, Error> {
-        let response = request_latest_executions(&self.http_client, &symbol, page, count).await?;
+        let response = request_latest_executions(
+            &self.http_client,
+            &self.config,
+            &self.credentials()?,
+            symbol,
+            page,
+            count,
+        )
+        .await?;
         Ok(response)
     }
 
+    /// 最新の約定一覧APIのページを自動でめくり、約定を1件ずつ返すストリームを作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `options` - 1ページ当たりの取得件数、返す件数の上限、時刻の範囲。
+    ///
+    pub fn latest_executions_stream<'a>(
+        &'a self,
+        symbol: &'a Symbol,
+        options: PageOptions,
+    ) -> impl Stream<Item = Result<dto::Execution, Error>> + 'a {
+        paginate(options, move |page, count| async move {
+            let response = self
+                .latest_executions_with_options(symbol, page, count)
+                .await?;
+            Ok(response.body.data.list)
+        })
+    }
+
+    /// 最新の約定一覧APIのページを自動でめくり、約定をすべて取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `options` - 1ページ当たりの取得件数、返す件数の上限、時刻の範囲。
+    ///
+    pub async fn latest_executions_all(
+        &self,
+        symbol: &Symbol,
+        options: PageOptions,
+    ) -> Result<Vec<dto::Execution>, Error> {
+        self.latest_executions_stream(symbol, options)
+            .try_collect()
+            .await
+    }
+
     /// 建玉一覧APIを呼び出す。取得ページは1、取得件数は100(最大値)を指定したとする。
     ///
     /// # Arguments
@@ -199,8 +523,15 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
         &self,
         symbol: &Symbol,
     ) -> Result<RestResponse<OpenPositions>, Error> {
-        let response =
-            request_open_positions(&self.http_client, &symbol, DEFAULT_PAGE, DEFAULT_COUNT).await?;
+        let response = request_open_positions(
+            &self.http_client,
+            &self.config,
+            &self.credentials()?,
+            symbol,
+            DEFAULT_PAGE,
+            DEFAULT_COUNT,
+        )
+        .await?;
         Ok(response)
     }
 
@@ -218,10 +549,55 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
         page: i32,
         count: i32,
     ) -> Result<RestResponse<OpenPositions>, Error> {
-        let response = request_open_positions(&self.http_client, &symbol, page, count).await?;
+        let response = request_open_positions(
+            &self.http_client,
+            &self.config,
+            &self.credentials()?,
+            symbol,
+            page,
+            count,
+        )
+        .await?;
         Ok(response)
     }
 
+    /// 建玉一覧APIのページを自動でめくり、建玉を1件ずつ返すストリームを作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `options` - 1ページ当たりの取得件数、返す件数の上限、時刻の範囲。
+    ///
+    pub fn open_positions_stream<'a>(
+        &'a self,
+        symbol: &'a Symbol,
+        options: PageOptions,
+    ) -> impl Stream<Item = Result<dto::Position, Error>> + 'a {
+        paginate(options, move |page, count| async move {
+            let response = self
+                .open_positions_with_options(symbol, page, count)
+                .await?;
+            Ok(response.body.data.list)
+        })
+    }
+
+    /// 建玉一覧APIのページを自動でめくり、建玉をすべて取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `options` - 1ページ当たりの取得件数、返す件数の上限、時刻の範囲。
+    ///
+    pub async fn open_positions_all(
+        &self,
+        symbol: &Symbol,
+        options: PageOptions,
+    ) -> Result<Vec<dto::Position>, Error> {
+        self.open_positions_stream(symbol, options)
+            .try_collect()
+            .await
+    }
+
     /// 建玉サマリーAPIを呼び出す。
     ///
     /// # Arguments
@@ -232,10 +608,29 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
         &self,
         symbol: &Symbol,
     ) -> Result<RestResponse<PositionSummary>, Error> {
-        let response = request_position_summary(&self.http_client, &symbol).await?;
+        let response = request_position_summary(
+            &self.http_client,
+            &self.config,
+            &self.credentials()?,
+            symbol,
+        )
+        .await?;
         Ok(response)
     }
 
+    /// `order_builder`のビルダーで組み立てた注文を、注文の種類に対応するAPIで出す。
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - 注文。`NewOrder`, `CloseOrder`, `CloseBulkOrder`, `CancelBulkOrder`のいずれか。
+    ///
+    pub async fn submit<R: OrderRequest>(
+        &self,
+        request: R,
+    ) -> Result<RestResponse<R::Response>, Error> {
+        request.submit(self).await
+    }
+
     /// 新規注文APIを呼び出す。
     ///
     /// # Arguments
@@ -251,15 +646,18 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
         execution_type: &ExecutionType,
         symbol: &Symbol,
         side: &Side,
-        size: f64,
-        price: Option<i64>,
+        size: Size,
+        price: Option<Price>,
     ) -> Result<RestResponse<Order>, Error> {
-        let time_in_force = get_default_time_in_force(&execution_type);
+        self.validate_order(symbol, size, price, None)?;
+        let time_in_force = get_default_time_in_force(execution_type);
         let response = request_order(
             &self.http_client,
-            &execution_type,
-            &symbol,
-            &side,
+            &self.config,
+            &self.credentials()?,
+            execution_type,
+            symbol,
+            side,
             size,
             &time_in_force,
             price,
@@ -286,18 +684,21 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
         execution_type: &ExecutionType,
         symbol: &Symbol,
         side: &Side,
-        size: f64,
-        price: Option<i64>,
+        size: Size,
+        price: Option<Price>,
         time_in_force: &TimeInForce,
-        losscut_price: Option<i64>,
+        losscut_price: Option<Price>,
     ) -> Result<RestResponse<Order>, Error> {
+        self.validate_order(symbol, size, price, losscut_price)?;
         let response = request_order(
             &self.http_client,
-            &execution_type,
-            &symbol,
-            &side,
+            &self.config,
+            &self.credentials()?,
+            execution_type,
+            symbol,
+            side,
             size,
-            &time_in_force,
+            time_in_force,
             price,
             losscut_price,
         )
@@ -314,10 +715,19 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
     ///
     pub async fn change_order(
         &self,
-        order_id: &str,
-        price: i64,
+        order_id: &OrderId,
+        price: Price,
     ) -> Result<RestResponse<ChangeOrder>, Error> {
-        let response = request_change_order(&self.http_client, &order_id, price, None).await?;
+        self.validate_change_order(order_id, price, None).await?;
+        let response = request_change_order(
+            &self.http_client,
+            &self.config,
+            &self.credentials()?,
+            order_id,
+            price,
+            None,
+        )
+        .await?;
         Ok(response)
     }
 
@@ -331,12 +741,21 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
     ///
     pub async fn change_order_with_options(
         &self,
-        order_id: &str,
-        price: i64,
-        losscut_price: i64,
+        order_id: &OrderId,
+        price: Price,
+        losscut_price: Price,
     ) -> Result<RestResponse<ChangeOrder>, Error> {
-        let response =
-            request_change_order(&self.http_client, &order_id, price, Some(losscut_price)).await?;
+        self.validate_change_order(order_id, price, Some(losscut_price))
+            .await?;
+        let response = request_change_order(
+            &self.http_client,
+            &self.config,
+            &self.credentials()?,
+            order_id,
+            price,
+            Some(losscut_price),
+        )
+        .await?;
         Ok(response)
     }
 
@@ -346,22 +765,45 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
     ///
     /// * `order_id` - 注文ID。
     ///
-    pub async fn cancel_order(&self, order_id: &str) -> Result<RestResponse<CancelOrder>, Error> {
-        let response = request_cancel_order(&self.http_client, &order_id).await?;
+    pub async fn cancel_order(
+        &self,
+        order_id: &OrderId,
+    ) -> Result<RestResponse<CancelOrder>, Error> {
+        let response = request_cancel_order(
+            &self.http_client,
+            &self.config,
+            &self.credentials()?,
+            order_id,
+        )
+        .await?;
         Ok(response)
     }
 
     /// 複数の注文キャンセルAPIを呼び出す。
+    /// 注文IDが10件を超える場合は10件ずつに分けて並行に呼び出し、成功した注文IDと失敗した注文をそれぞれ1つの配列にまとめて返す。
+    /// 途中の呼び出しがエラーになった場合はエラーを返すが、それまでの呼び出しでキャンセルされた注文は元に戻らない。
     ///
     /// # Arguments
     ///
-    /// * `order_ids` - 注文IDの配列。
+    /// * `order_ids` - 注文IDの配列。重複して指定すると`Error::DuplicateIdError`を返す。
     ///
     pub async fn cancel_orders(
         &self,
-        order_ids: &[&str],
+        order_ids: &[&OrderId],
     ) -> Result<RestResponse<CancelOrders>, Error> {
-        let response = request_cancel_orders(&self.http_client, &order_ids).await?;
+        let credentials = self.credentials()?;
+        let responses = call_in_chunks(
+            order_ids,
+            cancel_orders::MAX_ORDER_IDS,
+            self.config.batch_concurrency(),
+            |chunk| request_cancel_orders(&self.http_client, &self.config, &credentials, chunk),
+        )
+        .await?;
+        let mut responses = responses.into_iter();
+        let mut response = responses.next().ok_or(Error::EmptyResponseError())?;
+        for r in responses {
+            response.extend(r);
+        }
         Ok(response)
     }
 
@@ -375,8 +817,16 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
         &self,
         symbols: &[&Symbol],
     ) -> Result<RestResponse<CancelBulkOrder>, Error> {
-        let response =
-            request_cancel_bulk_order(&self.http_client, &symbols, None, None, false).await?;
+        let response = request_cancel_bulk_order(
+            &self.http_client,
+            &self.config,
+            &self.credentials()?,
+            symbols,
+            None,
+            None,
+            false,
+        )
+        .await?;
         Ok(response)
     }
 
@@ -398,61 +848,76 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
     ) -> Result<RestResponse<CancelBulkOrder>, Error> {
         let response = match desc {
             Some(d) => {
-                request_cancel_bulk_order(&self.http_client, &symbols, side, settle_type, d).await?
+                request_cancel_bulk_order(
+                    &self.http_client,
+                    &self.config,
+                    &self.credentials()?,
+                    symbols,
+                    side,
+                    settle_type,
+                    d,
+                )
+                .await?
             }
             None => {
-                request_cancel_bulk_order(&self.http_client, &symbols, side, settle_type, false)
-                    .await?
+                request_cancel_bulk_order(
+                    &self.http_client,
+                    &self.config,
+                    &self.credentials()?,
+                    symbols,
+                    side,
+                    settle_type,
+                    false,
+                )
+                .await?
             }
         };
         Ok(response)
     }
 
-    /// 決済注文APIを呼び出す。
+    /// 決済注文APIを呼び出す。複数の建玉を建玉ごとの数量を指定してまとめて決済できる。
+    ///
+    /// 発注前に建玉の指定漏れ(`PositionNotSpecifiedError`)と重複(`DuplicatePositionError`)を検証し、
+    /// 建玉一覧APIを呼び出して建玉が存在するか、銘柄と売買区分が合っているか、決済数量が決済できる数量を超えていないかを検証する。
+    /// これらの検証はバリデータの有無に関係なく行う。バリデータを設定した場合は合計数量も取引ルールで検証する。
     ///
     /// # Arguments
     ///
     /// * `execution_type` - 注文方法。
     /// * `symbol` - 銘柄。
     /// * `side` - 売買区分。
-    /// * `size` - 注文数量。
     /// * `price` - 注文価格。Marketの場合は不要。
-    /// * `positon_id` - 建玉ID。
+    /// * `positions` - 決済する建玉IDと決済数量の組。
     ///
     pub async fn close_order(
         &self,
         execution_type: &ExecutionType,
         symbol: &Symbol,
         side: &Side,
-        size: f64,
-        price: Option<i64>,
-        position_id: &str,
+        price: Option<Price>,
+        positions: &[(&PositionId, Size)],
     ) -> Result<RestResponse<CloseOrder>, Error> {
-        let time_in_force = get_default_time_in_force(&execution_type);
-        let response = request_close_order(
-            &self.http_client,
-            &execution_type,
-            &symbol,
-            &side,
-            size,
+        let time_in_force = get_default_time_in_force(execution_type);
+        self.close_order_with_options(
+            execution_type,
+            symbol,
+            side,
             price,
-            &position_id,
+            positions,
             &time_in_force,
         )
-        .await?;
-        Ok(response)
+        .await
     }
 
-    /// 決済注文APIをオプション引数つきで呼び出す。
+    /// 決済注文APIをオプション引数つきで呼び出す。検証の範囲は`close_order`と同じ。
     ///
     /// # Arguments
     ///
     /// * `execution_type` - 注文方法。
     /// * `symbol` - 銘柄。
     /// * `side` - 売買区分。
-    /// * `size` - 注文数量。
     /// * `price` - 注文価格。Marketの場合は不要。
-    /// * `positon_id` - 建玉ID。
+    /// * `positions` - 決済する建玉IDと決済数量の組。
     /// * `time_in_force` - 執行数量条件。
     ///
     pub async fn close_order_with_options(
@@ -460,25 +925,121 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
         execution_type: &ExecutionType,
         symbol: &Symbol,
         side: &Side,
-        size: f64,
-        price: Option<i64>,
-        position_id: &str,
+        price: Option<Price>,
+        positions: &[(&PositionId, Size)],
         time_in_force: &TimeInForce,
     ) -> Result<RestResponse<CloseOrder>, Error> {
+        self.validate_close_order(symbol, side, price, positions)
+            .await?;
         let response = request_close_order(
             &self.http_client,
-            &execution_type,
-            &symbol,
-            &side,
-            size,
+            &self.config,
+            &self.credentials()?,
+            execution_type,
+            symbol,
+            side,
             price,
-            &position_id,
-            &time_in_force,
+            positions,
+            time_in_force,
         )
         .await?;
         Ok(response)
     }
 
+    /// 決済注文のパラメータを検証する。
+    /// 建玉の指定漏れと重複を検証し、バリデータが設定されている場合は合計数量を取引ルールで検証する。
+    /// そのうえで建玉一覧APIを呼び出し、建玉の銘柄、売買区分、決済できる数量と整合しているか検証する。
+    async fn validate_close_order(
+        &self,
+        symbol: &Symbol,
+        side: &Side,
+        price: Option<Price>,
+        positions: &[(&PositionId, Size)],
+    ) -> Result<(), Error> {
+        self.validate_close_size(symbol, price, positions)?;
+        let open_positions = self.open_positions_all(symbol, PageOptions::new()).await?;
+        validate_close_positions(&open_positions, symbol, side, positions)
+    }
+
+    /// 決済する建玉の指定漏れと重複を検証し、バリデータが設定されている場合は合計数量を取引ルールで検証する。
+    fn validate_close_size(
+        &self,
+        symbol: &Symbol,
+        price: Option<Price>,
+        positions: &[(&PositionId, Size)],
+    ) -> Result<(), Error> {
+        if positions.is_empty() {
+            return Err(Error::PositionNotSpecifiedError());
+        }
+        for (i, (position_id, _)) in positions.iter().enumerate() {
+            if positions[..i].iter().any(|(id, _)| id == position_id) {
+                return Err(Error::DuplicatePositionError((*position_id).clone()));
+            }
+        }
+        let total: Size = positions.iter().map(|(_, size)| *size).sum();
+        self.validate_order(symbol, total, price, None)
+    }
+
+    /// 建玉一覧APIで取得した建玉から方針に従って決済する建玉を選び、決済注文APIを呼び出す。
+    /// 発注中の決済注文に拘束されている数量は除いて選ぶ。
+    /// 選んだ建玉は決済注文APIで一度に指定できる件数ずつに分けて並行に発注し、レスポンスを発注した順に返す。
+    ///
+    /// # Arguments
+    ///
+    /// * `execution_type` - 注文方法。
+    /// * `symbol` - 銘柄。
+    /// * `side` - 売買区分。買い建玉を決済する場合は`Side::Sell`を指定する。
+    /// * `size` - 決済する合計数量。
+    /// * `price` - 注文価格。Marketの場合は不要。
+    /// * `policy` - 建玉を選ぶ方針。
+    ///
+    pub async fn close_order_by_policy(
+        &self,
+        execution_type: &ExecutionType,
+        symbol: &Symbol,
+        side: &Side,
+        size: Size,
+        price: Option<Price>,
+        policy: &dyn ClosePolicy,
+    ) -> Result<Vec<RestResponse<CloseOrder>>, Error> {
+        let open_positions = self.open_positions_all(symbol, PageOptions::new()).await?;
+        let positions = select_positions(&open_positions, symbol.to_string(), side, size, policy)?;
+        for chunk in positions.chunks(close_order::MAX_SETTLE_POSITIONS) {
+            self.validate_close_size(symbol, price, chunk)?;
+        }
+        validate_close_positions(&open_positions, symbol, side, &positions)?;
+        let position_ids: Vec<&PositionId> = positions.iter().map(|(id, _)| *id).collect();
+        let credentials = &self.credentials()?;
+        let time_in_force = &get_default_time_in_force(execution_type);
+        call_in_chunks(
+            &position_ids,
+            close_order::MAX_SETTLE_POSITIONS,
+            self.config.batch_concurrency(),
+            |chunk| {
+                let settle_positions: Vec<(&PositionId, Size)> = positions
+                    .iter()
+                    .filter(|(id, _)| chunk.contains(id))
+                    .cloned()
+                    .collect();
+                async move {
+                    request_close_order(
+                        &self.http_client,
+                        &self.config,
+                        credentials,
+                        execution_type,
+                        symbol,
+                        side,
+                        price,
+                        &settle_positions,
+                        time_in_force,
+                    )
+                    .await
+                }
+            },
+        )
+        .await
+    }
+
     /// 一括決済注文APIを呼び出す。
     ///
     /// # Arguments
@@ -494,15 +1055,18 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
         execution_type: &ExecutionType,
         symbol: &Symbol,
         side: &Side,
-        size: f64,
-        price: Option<i64>,
+        size: Size,
+        price: Option<Price>,
     ) -> Result<RestResponse<CloseBulkOrder>, Error> {
-        let time_in_force = get_default_time_in_force(&execution_type);
+        self.validate_order(symbol, size, price, None)?;
+        let time_in_force = get_default_time_in_force(execution_type);
         let response = request_close_bulk_order(
             &self.http_client,
-            &execution_type,
-            &symbol,
-            &side,
+            &self.config,
+            &self.credentials()?,
+            execution_type,
+            symbol,
+            side,
             size,
             price,
             &time_in_force,
@@ -527,18 +1091,21 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
         execution_type: &ExecutionType,
         symbol: &Symbol,
         side: &Side,
-        size: f64,
-        price: Option<i64>,
+        size: Size,
+        price: Option<Price>,
         time_in_force: &TimeInForce,
     ) -> Result<RestResponse<CloseBulkOrder>, Error> {
+        self.validate_order(symbol, size, price, None)?;
         let response = request_close_bulk_order(
             &self.http_client,
-            &execution_type,
-            &symbol,
-            &side,
+            &self.config,
+            &self.credentials()?,
+            execution_type,
+            symbol,
+            side,
             size,
             price,
-            &time_in_force,
+            time_in_force,
         )
         .await?;
         Ok(response)
@@ -553,11 +1120,54 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PrivateAPI<T> {
     ///
     pub async fn change_losscut_price(
         &self,
-        position_id: &str,
-        losscut_price: i64,
+        position_id: &PositionId,
+        losscut_price: Price,
     ) -> Result<RestResponse<ChangeLosscutPrice>, Error> {
+        let response = request_change_losscut_price(
+            &self.http_client,
+            &self.config,
+            &self.credentials()?,
+            position_id,
+            losscut_price,
+        )
+        .await?;
+        Ok(response)
+    }
+
+    /// Private WebSocket APIのアクセストークン取得APIを呼び出す。
+    ///
+    /// # Arguments
+    ///
+    ///
+    pub async fn create_ws_auth(&self) -> Result<RestResponse<WsAuth>, Error> {
         let response =
-            request_change_losscut_price(&self.http_client, &position_id, losscut_price).await?;
+            request_create_ws_auth(&self.http_client, &self.config, &self.credentials()?).await?;
+        Ok(response)
+    }
+
+    /// Private WebSocket APIのアクセストークン延長APIを呼び出す。
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - アクセストークン。
+    ///
+    pub async fn extend_ws_auth(&self, token: &str) -> Result<RestResponse<ExtendWsAuth>, Error> {
+        let response =
+            request_extend_ws_auth(&self.http_client, &self.config, &self.credentials()?, token)
+                .await?;
+        Ok(response)
+    }
+
+    /// Private WebSocket APIのアクセストークン削除APIを呼び出す。
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - アクセストークン。
+    ///
+    pub async fn revoke_ws_auth(&self, token: &str) -> Result<RestResponse<RevokeWsAuth>, Error> {
+        let response =
+            request_revoke_ws_auth(&self.http_client, &self.config, &self.credentials()?, token)
+                .await?;
         Ok(response)
     }
 }
@@ -1,7 +1,8 @@
 //! 有効注文一覧APIを実装する。
 
+use crate::client_config::ClientConfig;
+use crate::credentials::Credentials;
 use crate::dto::{get_pagination_default_value, get_vector_default_value, Order, Pagination};
-use crate::end_point::*;
 use crate::error::Error;
 use crate::headers::Headers;
 use crate::http_client::*;
@@ -60,26 +61,29 @@ impl RestResponse<ActiveOrders> {
 /// 有効注文一覧APIを呼び出す。オプショナルなパラメーターを明示的に指定する場合こちらを呼ぶ。
 pub async fn request_active_orders(
     http_client: &impl HttpClient,
+    config: &ClientConfig,
+    credentials: &Credentials,
     symbol: &Symbol,
     page: i32,
     count: i32,
 ) -> Result<RestResponse<ActiveOrders>, Error> {
     let url = format!(
         "{}{}?symbol={}&page={}&count={}",
-        PRIVATE_ENDPOINT,
+        config.private_endpoint(),
         ACTIVE_ORDERS_API_PATH,
         symbol.to_string(),
         page,
         count,
     );
-    let headers = Headers::create_get_headers(&ACTIVE_ORDERS_API_PATH)?;
-    let response = http_client.get(url, &headers).await?;
-    parse_from_http_response::<ActiveOrders>(&response)
+    let headers = Headers::create_get_headers(credentials, ACTIVE_ORDERS_API_PATH)?;
+    let response = with_timeout(config, http_client.get(url, &headers)).await?;
+    parse_from_http_response::<ActiveOrders>(&response, ACTIVE_ORDERS_API_PATH, config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::credentials::tests::fake_credentials;
     use crate::http_client::tests::InmemClient;
     use crate::symbol::Symbol;
     use chrono::SecondsFormat;
@@ -131,9 +135,16 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_active_orders(&http_client, &Symbol::Bch, 1, 100)
-            .await
-            .unwrap();
+        let resp = request_active_orders(
+            &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
+            &Symbol::Bch,
+            1,
+            100,
+        )
+        .await
+        .unwrap();
         assert_eq!(resp.http_status_code, 200);
         assert_eq!(resp.body.status, 0);
         assert_eq!(
@@ -155,9 +166,16 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_active_orders(&http_client, &Symbol::BtcJpy, 1, 100)
-            .await
-            .unwrap();
+        let resp = request_active_orders(
+            &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
+            &Symbol::BtcJpy,
+            1,
+            100,
+        )
+        .await
+        .unwrap();
         assert_eq!(resp.http_status_code, 200);
         assert_eq!(resp.body.status, 0);
         assert_eq!(
@@ -1,12 +1,14 @@
 //! 資産残高APIを実装する。
 
+use crate::client_config::ClientConfig;
+use crate::credentials::Credentials;
 use crate::dto::get_vector_default_value;
-use crate::end_point::*;
 use crate::error::Error;
 use crate::headers::Headers;
 use crate::http_client::*;
 use crate::json::*;
 use crate::response::*;
+use crate::size::{size_to_f64, Size};
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
 
@@ -17,12 +19,12 @@ const ASSETS_API_PATH: &str = "/v1/account/assets";
 #[derive(Deserialize)]
 pub struct Data {
     /// 残高。
-    #[serde(deserialize_with = "str_to_f64")]
-    pub amount: f64,
+    #[serde(deserialize_with = "str_to_size")]
+    pub amount: Size,
 
     /// 利用可能金額。残高 - 出金予定額。
-    #[serde(deserialize_with = "str_to_f64")]
-    pub available: f64,
+    #[serde(deserialize_with = "str_to_size")]
+    pub available: Size,
 
     /// 円転レート。
     #[serde(deserialize_with = "str_to_f64", rename = "conversionRate")]
@@ -35,12 +37,12 @@ pub struct Data {
 impl Data {
     /// 残高を円で取得する。
     pub fn amount_as_jpy(&self) -> f64 {
-        self.amount * self.conversion_rate
+        size_to_f64(self.amount) * self.conversion_rate
     }
 
     /// 利用可能金額を円で取得する。
     pub fn available_as_jpy(&self) -> f64 {
-        self.available * self.conversion_rate
+        size_to_f64(self.available) * self.conversion_rate
     }
 }
 
@@ -67,16 +69,21 @@ impl RestResponse<Assets> {
 }
 
 /// 資産残高APIを呼び出す。
-pub async fn request_assets(http_client: &impl HttpClient) -> Result<RestResponse<Assets>, Error> {
-    let url = format!("{}{}", PRIVATE_ENDPOINT, ASSETS_API_PATH,);
-    let headers = Headers::create_get_headers(&ASSETS_API_PATH)?;
-    let response = http_client.get(url, &headers).await?;
-    parse_from_http_response::<Assets>(&response)
+pub async fn request_assets(
+    http_client: &impl HttpClient,
+    config: &ClientConfig,
+    credentials: &Credentials,
+) -> Result<RestResponse<Assets>, Error> {
+    let url = format!("{}{}", config.private_endpoint(), ASSETS_API_PATH,);
+    let headers = Headers::create_get_headers(credentials, ASSETS_API_PATH)?;
+    let response = with_timeout(config, http_client.get(url, &headers)).await?;
+    parse_from_http_response::<Assets>(&response, ASSETS_API_PATH, config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::credentials::tests::fake_credentials;
     use crate::http_client::tests::InmemClient;
     use chrono::SecondsFormat;
 
@@ -117,7 +124,9 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_assets(&http_client).await.unwrap();
+        let resp = request_assets(&http_client, &ClientConfig::default(), &fake_credentials())
+            .await
+            .unwrap();
         assert_eq!(resp.http_status_code, 200);
         assert_eq!(resp.body.status, 0);
         assert_eq!(
@@ -137,7 +146,9 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_assets(&http_client).await.unwrap();
+        let resp = request_assets(&http_client, &ClientConfig::default(), &fake_credentials())
+            .await
+            .unwrap();
         assert_eq!(resp.http_status_code, 200);
         assert_eq!(resp.body.status, 0);
         assert_eq!(
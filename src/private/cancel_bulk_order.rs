@@ -1,10 +1,12 @@
 //! 注文の一括キャンセルAPIを実装する。
 
+use crate::client_config::ClientConfig;
+use crate::credentials::Credentials;
 use crate::dto::get_vector_default_value;
-use crate::end_point::*;
 use crate::error::Error;
 use crate::headers::Headers;
 use crate::http_client::*;
+use crate::id::OrderId;
 use crate::json::*;
 use crate::response::*;
 use crate::settle_type::SettleType;
@@ -28,16 +30,13 @@ pub struct CancelBulkOrder {
     pub responsetime: DateTime<Utc>,
 
     /// レスポンスの`data`の部分。
-    #[serde(
-        deserialize_with = "ids_to_strvec",
-        default = "get_vector_default_value"
-    )]
-    pub data: Vec<String>,
+    #[serde(default = "get_vector_default_value")]
+    pub data: Vec<OrderId>,
 }
 
 impl RestResponse<CancelBulkOrder> {
     /// 注文Idが格納された配列を取得する。
-    pub fn order_ids(&self) -> &Vec<String> {
+    pub fn order_ids(&self) -> &Vec<OrderId> {
         &self.body.data
     }
 }
@@ -80,21 +79,29 @@ fn build_parameters(
 /// 注文の一括キャンセルAPIを呼び出す。
 pub async fn request_cancel_bulk_order(
     http_client: &impl HttpClient,
+    config: &ClientConfig,
+    credentials: &Credentials,
     symbols: &[&Symbol],
     side: Option<&Side>,
     settle_type: Option<&SettleType>,
     desc: bool,
 ) -> Result<RestResponse<CancelBulkOrder>, Error> {
-    let url = format!("{}{}", PRIVATE_ENDPOINT, CANCEL_BULK_ORDERS_API_PATH,);
-    let parameters = build_parameters(&symbols, side, settle_type, desc)?;
-    let headers = Headers::create_post_headers(&CANCEL_BULK_ORDERS_API_PATH, &parameters)?;
-    let response = http_client.post(url, &headers, &parameters).await?;
-    parse_from_http_response::<CancelBulkOrder>(&response)
+    let url = format!(
+        "{}{}",
+        config.private_endpoint(),
+        CANCEL_BULK_ORDERS_API_PATH,
+    );
+    let parameters = build_parameters(symbols, side, settle_type, desc)?;
+    let headers =
+        Headers::create_post_headers(credentials, CANCEL_BULK_ORDERS_API_PATH, &parameters)?;
+    let response = with_timeout(config, http_client.post(url, &headers, &parameters)).await?;
+    parse_from_http_response::<CancelBulkOrder>(&response, CANCEL_BULK_ORDERS_API_PATH, config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::credentials::tests::fake_credentials;
     use crate::http_client::tests::InmemClient;
     use chrono::SecondsFormat;
 
@@ -116,7 +123,9 @@ mod tests {
         };
         let resp = request_cancel_bulk_order(
             &http_client,
-            &vec![&Symbol::Btc, &Symbol::EthJpy],
+            &ClientConfig::default(),
+            &fake_credentials(),
+            &[&Symbol::Btc, &Symbol::EthJpy],
             Some(&Side::Sell),
             None,
             false,
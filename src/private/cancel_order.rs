@@ -1,9 +1,11 @@
 //! 注文キャンセルAPIを実装する。
 
-use crate::end_point::*;
+use crate::client_config::ClientConfig;
+use crate::credentials::Credentials;
 use crate::error::Error;
 use crate::headers::Headers;
 use crate::http_client::*;
+use crate::id::OrderId;
 use crate::json::*;
 use crate::response::*;
 use chrono::{DateTime, Utc};
@@ -24,26 +26,29 @@ pub struct CancelOrder {
     pub responsetime: DateTime<Utc>,
 }
 
-fn build_parameters(order_id: &str) -> Result<Value, Error> {
-    let order_id_num = id_to_num(&order_id)?;
+fn build_parameters(order_id: &OrderId) -> Result<Value, Error> {
+    let order_id_num = order_id.to_num()?;
     Ok(json!({"orderId": order_id_num,}))
 }
 
 /// 注文キャンセルAPIを呼び出す。
 pub async fn request_cancel_order(
     http_client: &impl HttpClient,
-    order_id: &str,
+    config: &ClientConfig,
+    credentials: &Credentials,
+    order_id: &OrderId,
 ) -> Result<RestResponse<CancelOrder>, Error> {
-    let url = format!("{}{}", PRIVATE_ENDPOINT, CANCEL_ORDER_API_PATH,);
+    let url = format!("{}{}", config.private_endpoint(), CANCEL_ORDER_API_PATH,);
     let parameters = build_parameters(order_id)?;
-    let headers = Headers::create_post_headers(&CANCEL_ORDER_API_PATH, &parameters)?;
-    let response = http_client.post(url, &headers, &parameters).await?;
-    parse_from_http_response::<CancelOrder>(&response)
+    let headers = Headers::create_post_headers(credentials, CANCEL_ORDER_API_PATH, &parameters)?;
+    let response = with_timeout(config, http_client.post(url, &headers, &parameters)).await?;
+    parse_from_http_response::<CancelOrder>(&response, CANCEL_ORDER_API_PATH, config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::credentials::tests::fake_credentials;
     use crate::http_client::tests::InmemClient;
     use chrono::SecondsFormat;
 
@@ -62,7 +67,14 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_cancel_order(&http_client, "200").await.unwrap();
+        let resp = request_cancel_order(
+            &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
+            &OrderId::new("200"),
+        )
+        .await
+        .unwrap();
         assert_eq!(resp.http_status_code, 200);
         assert_eq!(resp.body.status, 0);
         assert_eq!(
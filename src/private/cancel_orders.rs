@@ -1,10 +1,12 @@
 //! 注文の複数キャンセルAPIを実装する。
 
+use crate::client_config::ClientConfig;
+use crate::credentials::Credentials;
 use crate::dto::{get_vector_default_value, CancelFailedOrder};
-use crate::end_point::*;
 use crate::error::Error;
 use crate::headers::Headers;
 use crate::http_client::*;
+use crate::id::OrderId;
 use crate::json::*;
 use crate::response::*;
 use chrono::{DateTime, Utc};
@@ -14,6 +16,9 @@ use serde_json::{json, Value};
 /// 注文の複数キャンセルAPIのパス。
 const CANCEL_ORDERS_API_PATH: &str = "/v1/cancelOrders";
 
+/// 注文の複数キャンセルAPIで一度に指定できる注文IDの最大件数。
+pub(crate) const MAX_ORDER_IDS: usize = 10;
+
 /// 注文の複数キャンセルAPIから返ってくるレスポンスのうち`data`の部分を格納する構造体。
 #[derive(Deserialize)]
 pub struct Data {
@@ -22,11 +27,8 @@ pub struct Data {
     pub failed: Vec<CancelFailedOrder>,
 
     /// キャンセルに成功した注文Idの配列。
-    #[serde(
-        deserialize_with = "ids_to_strvec",
-        default = "get_vector_default_value"
-    )]
-    pub success: Vec<String>,
+    #[serde(default = "get_vector_default_value")]
+    pub success: Vec<OrderId>,
 }
 
 /// 注文の複数キャンセルAPIから返ってくるレスポンスを格納する構造体。
@@ -50,15 +52,22 @@ impl RestResponse<CancelOrders> {
     }
 
     /// キャンセルに成功した注文の注文Idが格納された配列を取得する。
-    pub fn success(&self) -> &Vec<String> {
+    pub fn success(&self) -> &Vec<OrderId> {
         &self.body.data.success
     }
+
+    /// 別の呼び出しでキャンセルした結果を後ろにつなげる。
+    pub(crate) fn extend(&mut self, other: RestResponse<CancelOrders>) {
+        self.body.data.failed.extend(other.body.data.failed);
+        self.body.data.success.extend(other.body.data.success);
+        self.unknown_fields.extend(other.unknown_fields);
+    }
 }
 
-fn build_parameters(order_ids: &[&str]) -> Result<Value, Error> {
-    let mut order_ids_as_numvec: Vec<i32> = Vec::<i32>::new();
+fn build_parameters(order_ids: &[&OrderId]) -> Result<Value, Error> {
+    let mut order_ids_as_numvec: Vec<i64> = Vec::<i64>::new();
     for id in order_ids {
-        order_ids_as_numvec.push(id_to_num(id)?);
+        order_ids_as_numvec.push(id.to_num()?);
     }
     Ok(json!({"orderIds": order_ids_as_numvec,}))
 }
@@ -66,19 +75,26 @@ fn build_parameters(order_ids: &[&str]) -> Result<Value, Error> {
 /// 注文の複数キャンセルAPIを呼び出す。
 pub async fn request_cancel_orders(
     http_client: &impl HttpClient,
-    order_ids: &[&str],
+    config: &ClientConfig,
+    credentials: &Credentials,
+    order_ids: &[&OrderId],
 ) -> Result<RestResponse<CancelOrders>, Error> {
-    let url = format!("{}{}", PRIVATE_ENDPOINT, CANCEL_ORDERS_API_PATH,);
-    let parameters = build_parameters(&order_ids)?;
-    let headers = Headers::create_post_headers(&CANCEL_ORDERS_API_PATH, &parameters)?;
-    let response = http_client.post(url, &headers, &parameters).await?;
-    parse_from_http_response::<CancelOrders>(&response)
+    let url = format!("{}{}", config.private_endpoint(), CANCEL_ORDERS_API_PATH,);
+    let parameters = build_parameters(order_ids)?;
+    let headers = Headers::create_post_headers(credentials, CANCEL_ORDERS_API_PATH, &parameters)?;
+    let response = with_timeout(config, http_client.post(url, &headers, &parameters)).await?;
+    parse_from_http_response::<CancelOrders>(&response, CANCEL_ORDERS_API_PATH, config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api_error_code::ApiErrorCode;
+    use crate::credentials::tests::fake_credentials;
+    use crate::credentials::Credentials;
     use crate::http_client::tests::InmemClient;
+    use crate::mock::MockHttpClient;
+    use crate::private::PrivateAPI;
     use chrono::SecondsFormat;
 
     const SAMPLE_RESPONSE: &str = r#"
@@ -111,9 +127,19 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_cancel_orders(&http_client, &vec!["1", "2", "3", "4"])
-            .await
-            .unwrap();
+        let resp = request_cancel_orders(
+            &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
+            &[
+                &OrderId::new("1"),
+                &OrderId::new("2"),
+                &OrderId::new("3"),
+                &OrderId::new("4"),
+            ],
+        )
+        .await
+        .unwrap();
         assert_eq!(resp.http_status_code, 200);
         assert_eq!(resp.body.status, 0);
         assert_eq!(
@@ -123,6 +149,54 @@ mod tests {
             "2019-03-19T01:07:24.557Z"
         );
         assert_eq!(resp.failed().len(), 2);
+        assert_eq!(
+            resp.failed()[0].message_code,
+            ApiErrorCode::OrderAlreadyChanged
+        );
         assert_eq!(resp.success().len(), 2);
     }
+
+    #[tokio::test]
+    async fn test_cancel_orders_in_chunks() {
+        // 7の倍数の注文IDはキャンセルに失敗したことにする。
+        let mock = MockHttpClient::new().on_fn("POST", "/v1/cancelOrders", |request| {
+            let ids: Vec<i64> = request.body.as_ref().unwrap()["orderIds"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|id| id.as_i64().unwrap())
+                .collect();
+            let (failed, success): (Vec<i64>, Vec<i64>) =
+                ids.into_iter().partition(|id| id % 7 == 0);
+            let failed: Vec<Value> = failed
+                .into_iter()
+                .map(|id| json!({"message_code": "ERR-5122", "message_string": "", "orderId": id}))
+                .collect();
+            Ok(RawResponse {
+                http_status_code: 200,
+                body_text: json!({
+                    "status": 0,
+                    "data": {"failed": failed, "success": success},
+                    "responsetime": "2019-03-19T01:07:24.557Z"
+                })
+                .to_string(),
+            })
+        });
+        let private_api = PrivateAPI::new(mock).with_credentials(Credentials::new("key", "secret"));
+        let ids: Vec<OrderId> = (1..=25).map(|i| OrderId::new(i.to_string())).collect();
+        let refs: Vec<&OrderId> = ids.iter().collect();
+        let resp = private_api.cancel_orders(&refs).await.unwrap();
+        assert_eq!(private_api.http_client.calls().len(), 3);
+        assert_eq!(resp.success().len(), 22);
+        assert_eq!(resp.success()[21], "25");
+        let failed: Vec<&str> = resp.failed().iter().map(|f| f.order_id.as_str()).collect();
+        assert_eq!(failed, vec!["7", "14", "21"]);
+
+        let refs = vec![&ids[0], &ids[0]];
+        assert!(matches!(
+            private_api.cancel_orders(&refs).await,
+            Err(Error::DuplicateIdError(_))
+        ));
+        assert_eq!(private_api.http_client.calls().len(), 3);
+    }
 }
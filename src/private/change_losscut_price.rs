@@ -1,10 +1,13 @@
 //! ロスカットレート変更APIを実装する。
 
-use crate::end_point::*;
+use crate::client_config::ClientConfig;
+use crate::credentials::Credentials;
 use crate::error::Error;
 use crate::headers::Headers;
 use crate::http_client::*;
+use crate::id::PositionId;
 use crate::json::*;
+use crate::price::Price;
 use crate::response::*;
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
@@ -24,7 +27,7 @@ pub struct ChangeLosscutPrice {
     pub responsetime: DateTime<Utc>,
 }
 
-fn build_parameters(position_id: &str, losscut_price: i64) -> Value {
+fn build_parameters(position_id: &PositionId, losscut_price: Price) -> Value {
     json!({
         "positionId": position_id,
         "losscutPrice": losscut_price.to_string(),
@@ -34,20 +37,29 @@ fn build_parameters(position_id: &str, losscut_price: i64) -> Value {
 /// ロスカットレート変更APIを呼び出す。
 pub async fn request_change_losscut_price(
     http_client: &impl HttpClient,
-    position_id: &str,
-    losscut_price: i64,
+    config: &ClientConfig,
+    credentials: &Credentials,
+    position_id: &PositionId,
+    losscut_price: Price,
 ) -> Result<RestResponse<ChangeLosscutPrice>, Error> {
-    let url = format!("{}{}", PRIVATE_ENDPOINT, CHANGE_LOSSCUT_PRICE_API_PATH,);
-    let parameters = build_parameters(&position_id, losscut_price);
-    let headers = Headers::create_post_headers(&CHANGE_LOSSCUT_PRICE_API_PATH, &parameters)?;
-    let response = http_client.post(url, &headers, &parameters).await?;
-    parse_from_http_response::<ChangeLosscutPrice>(&response)
+    let url = format!(
+        "{}{}",
+        config.private_endpoint(),
+        CHANGE_LOSSCUT_PRICE_API_PATH,
+    );
+    let parameters = build_parameters(position_id, losscut_price);
+    let headers =
+        Headers::create_post_headers(credentials, CHANGE_LOSSCUT_PRICE_API_PATH, &parameters)?;
+    let response = with_timeout(config, http_client.post(url, &headers, &parameters)).await?;
+    parse_from_http_response::<ChangeLosscutPrice>(&response, CHANGE_LOSSCUT_PRICE_API_PATH, config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::credentials::tests::fake_credentials;
     use crate::http_client::tests::InmemClient;
+    use crate::price::price;
     use chrono::SecondsFormat;
 
     const SAMPLE_RESPONSE: &str = r#"
@@ -65,9 +77,15 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_change_losscut_price(&http_client, "200", 100)
-            .await
-            .unwrap();
+        let resp = request_change_losscut_price(
+            &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
+            &PositionId::new("200"),
+            price("100"),
+        )
+        .await
+        .unwrap();
         assert_eq!(resp.http_status_code, 200);
         assert_eq!(resp.body.status, 0);
         assert_eq!(
@@ -1,10 +1,13 @@
 //! 注文変更APIを実装する。
 
-use crate::end_point::*;
+use crate::client_config::ClientConfig;
+use crate::credentials::Credentials;
 use crate::error::Error;
 use crate::headers::Headers;
 use crate::http_client::*;
+use crate::id::OrderId;
 use crate::json::*;
+use crate::price::Price;
 use crate::response::*;
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
@@ -25,11 +28,11 @@ pub struct ChangeOrder {
 }
 
 fn build_parameters(
-    order_id: &str,
-    price: i64,
-    losscut_price: Option<i64>,
+    order_id: &OrderId,
+    price: Price,
+    losscut_price: Option<Price>,
 ) -> Result<Value, Error> {
-    let order_id_num = id_to_num(&order_id)?;
+    let order_id_num = order_id.to_num()?;
     Ok(match losscut_price {
         Some(lp) => json!({
             "orderId": order_id_num,
@@ -46,21 +49,25 @@ fn build_parameters(
 /// 注文変更APIを呼び出す。
 pub async fn request_change_order(
     http_client: &impl HttpClient,
-    order_id: &str,
-    price: i64,
-    losscut_price: Option<i64>,
+    config: &ClientConfig,
+    credentials: &Credentials,
+    order_id: &OrderId,
+    price: Price,
+    losscut_price: Option<Price>,
 ) -> Result<RestResponse<ChangeOrder>, Error> {
-    let url = format!("{}{}", PRIVATE_ENDPOINT, CHANGE_ORDER_API_PATH,);
+    let url = format!("{}{}", config.private_endpoint(), CHANGE_ORDER_API_PATH,);
     let parameters = build_parameters(order_id, price, losscut_price)?;
-    let headers = Headers::create_post_headers(&CHANGE_ORDER_API_PATH, &parameters)?;
-    let response = http_client.post(url, &headers, &parameters).await?;
-    parse_from_http_response::<ChangeOrder>(&response)
+    let headers = Headers::create_post_headers(credentials, CHANGE_ORDER_API_PATH, &parameters)?;
+    let response = with_timeout(config, http_client.post(url, &headers, &parameters)).await?;
+    parse_from_http_response::<ChangeOrder>(&response, CHANGE_ORDER_API_PATH, config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::credentials::tests::fake_credentials;
     use crate::http_client::tests::InmemClient;
+    use crate::price::price;
     use chrono::SecondsFormat;
 
     const SAMPLE_RESPONSE: &str = r#"
@@ -78,9 +85,16 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_change_order(&http_client, "200", 100, None)
-            .await
-            .unwrap();
+        let resp = request_change_order(
+            &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
+            &OrderId::new("200"),
+            price("100"),
+            None,
+        )
+        .await
+        .unwrap();
         assert_eq!(resp.http_status_code, 200);
         assert_eq!(resp.body.status, 0);
         assert_eq!(
@@ -2,14 +2,18 @@
 
 #![allow(clippy::too_many_arguments)]
 
-use crate::end_point::*;
+use crate::client_config::ClientConfig;
+use crate::credentials::Credentials;
 use crate::error::Error;
 use crate::execution_type::ExecutionType;
 use crate::headers::Headers;
 use crate::http_client::*;
+use crate::id::OrderId;
 use crate::json::*;
+use crate::price::Price;
 use crate::response::*;
 use crate::side::Side;
+use crate::size::Size;
 use crate::symbol::Symbol;
 use crate::time_in_force::TimeInForce;
 use chrono::{DateTime, Utc};
@@ -30,12 +34,12 @@ pub struct CloseBulkOrder {
     pub responsetime: DateTime<Utc>,
 
     /// 注文ID。
-    pub data: String,
+    pub data: OrderId,
 }
 
 impl RestResponse<CloseBulkOrder> {
     /// 注文IDを取得する。
-    pub fn order_id(&self) -> &str {
+    pub fn order_id(&self) -> &OrderId {
         &self.body.data
     }
 }
@@ -44,23 +48,18 @@ fn build_parameters(
     execution_type: &ExecutionType,
     symbol: &Symbol,
     side: &Side,
-    size: f64,
-    price: Option<i64>,
+    size: Size,
+    price: Option<Price>,
     time_in_force: &TimeInForce,
 ) -> Result<Value, Error> {
     Ok(match execution_type {
         ExecutionType::Market => {
-            build_market_parameters(&execution_type, &symbol, &side, size, &time_in_force)?
+            build_market_parameters(execution_type, symbol, side, size, time_in_force)?
         }
         _ => match price {
-            Some(p) => build_limit_or_stop_paramters(
-                &execution_type,
-                &symbol,
-                &side,
-                size,
-                p,
-                &time_in_force,
-            )?,
+            Some(p) => {
+                build_limit_or_stop_paramters(execution_type, symbol, side, size, p, time_in_force)?
+            }
             None => return Err(Error::PriceNotSpecifiedError()),
         },
     })
@@ -70,7 +69,7 @@ fn build_market_parameters(
     execution_type: &ExecutionType,
     symbol: &Symbol,
     side: &Side,
-    size: f64,
+    size: Size,
     time_in_force: &TimeInForce,
 ) -> Result<Value, Error> {
     Ok(json!({
@@ -86,8 +85,8 @@ fn build_limit_or_stop_paramters(
     execution_type: &ExecutionType,
     symbol: &Symbol,
     side: &Side,
-    size: f64,
-    price: i64,
+    size: Size,
+    price: Price,
     time_in_force: &TimeInForce,
 ) -> Result<Value, Error> {
     Ok(json!({
@@ -103,25 +102,29 @@ fn build_limit_or_stop_paramters(
 /// 一括決済注文APIを呼び出す。
 pub async fn request_close_bulk_order(
     http_client: &impl HttpClient,
+    config: &ClientConfig,
+    credentials: &Credentials,
     execution_type: &ExecutionType,
     symbol: &Symbol,
     side: &Side,
-    size: f64,
-    price: Option<i64>,
+    size: Size,
+    price: Option<Price>,
     time_in_force: &TimeInForce,
 ) -> Result<RestResponse<CloseBulkOrder>, Error> {
-    let url = format!("{}{}", PRIVATE_ENDPOINT, CLOSE_ORDER_API_PATH,);
-    let parameters =
-        build_parameters(&execution_type, &symbol, &side, size, price, &time_in_force)?;
-    let headers = Headers::create_post_headers(&CLOSE_ORDER_API_PATH, &parameters)?;
-    let response = http_client.post(url, &headers, &parameters).await?;
-    parse_from_http_response::<CloseBulkOrder>(&response)
+    let url = format!("{}{}", config.private_endpoint(), CLOSE_ORDER_API_PATH,);
+    let parameters = build_parameters(execution_type, symbol, side, size, price, time_in_force)?;
+    let headers = Headers::create_post_headers(credentials, CLOSE_ORDER_API_PATH, &parameters)?;
+    let response = with_timeout(config, http_client.post(url, &headers, &parameters)).await?;
+    parse_from_http_response::<CloseBulkOrder>(&response, CLOSE_ORDER_API_PATH, config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::credentials::tests::fake_credentials;
     use crate::http_client::tests::InmemClient;
+    use crate::price::price;
+    use crate::size::size;
     use chrono::SecondsFormat;
 
     const SAMPLE_RESPONSE: &str = r#"
@@ -142,11 +145,13 @@ mod tests {
         };
         let resp = request_close_bulk_order(
             &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
             &ExecutionType::Market,
             &Symbol::BtcJpy,
             &Side::Buy,
-            0.1,
-            Some(10),
+            size("0.1"),
+            Some(price("10")),
             &TimeInForce::Fak,
         )
         .await
@@ -2,14 +2,18 @@
 
 #![allow(clippy::too_many_arguments)]
 
-use crate::end_point::*;
+use crate::client_config::ClientConfig;
+use crate::credentials::Credentials;
 use crate::error::Error;
 use crate::execution_type::ExecutionType;
 use crate::headers::Headers;
 use crate::http_client::*;
+use crate::id::{OrderId, PositionId};
 use crate::json::*;
+use crate::price::Price;
 use crate::response::*;
 use crate::side::Side;
+use crate::size::Size;
 use crate::symbol::Symbol;
 use crate::time_in_force::TimeInForce;
 use chrono::{DateTime, Utc};
@@ -19,6 +23,9 @@ use serde_json::{json, Value};
 /// 決済注文APIのパス。
 const CLOSE_ORDER_API_PATH: &str = "/v1/closeOrder";
 
+/// 決済注文APIで一度に指定できる建玉の最大件数。
+pub(crate) const MAX_SETTLE_POSITIONS: usize = 10;
+
 /// 決済注文APIから返ってくるレスポンスを格納する構造体。
 #[derive(Deserialize)]
 pub struct CloseOrder {
@@ -30,12 +37,12 @@ pub struct CloseOrder {
     pub responsetime: DateTime<Utc>,
 
     /// 注文ID。
-    pub data: String,
+    pub data: OrderId,
 }
 
 impl RestResponse<CloseOrder> {
     /// 注文IDを取得する。
-    pub fn order_id(&self) -> &str {
+    pub fn order_id(&self) -> &OrderId {
         &self.body.data
     }
 }
@@ -44,111 +51,80 @@ fn build_parameters(
     execution_type: &ExecutionType,
     symbol: &Symbol,
     side: &Side,
-    size: f64,
-    price: Option<i64>,
-    position_id: &str,
+    price: Option<Price>,
+    positions: &[(&PositionId, Size)],
     time_in_force: &TimeInForce,
 ) -> Result<Value, Error> {
+    if positions.is_empty() {
+        return Err(Error::PositionNotSpecifiedError());
+    }
+    let settle_position = build_settle_position(positions)?;
     Ok(match execution_type {
-        ExecutionType::Market => build_market_parameters(
-            &execution_type,
-            &symbol,
-            &side,
-            size,
-            &position_id,
-            &time_in_force,
-        )?,
+        ExecutionType::Market => json!({
+            "executionType": execution_type.to_string(),
+            "symbol": symbol.to_string(),
+            "side": side.to_string(),
+            "timeInForce": time_in_force.to_string(),
+            "settlePosition": settle_position,
+        }),
         _ => match price {
-            Some(p) => build_limit_or_stop_paramters(
-                &execution_type,
-                &symbol,
-                &side,
-                size,
-                p,
-                &position_id,
-                &time_in_force,
-            )?,
+            Some(p) => json!({
+                "executionType": execution_type.to_string(),
+                "symbol": symbol.to_string(),
+                "side": side.to_string(),
+                "timeInForce": time_in_force.to_string(),
+                "price": p.to_string(),
+                "settlePosition": settle_position,
+            }),
             None => return Err(Error::PriceNotSpecifiedError()),
         },
     })
 }
 
-fn build_market_parameters(
-    execution_type: &ExecutionType,
-    symbol: &Symbol,
-    side: &Side,
-    size: f64,
-    position_id: &str,
-    time_in_force: &TimeInForce,
-) -> Result<Value, Error> {
-    Ok(json!({
-        "executionType": execution_type.to_string(),
-        "symbol": symbol.to_string(),
-        "side": side.to_string(),
-        "timeInForce": time_in_force.to_string(),
-        "settlePosition": [
-            {
-                "positionId": id_to_num(&position_id)?,
-                "size": size.to_string(),
-            }
-        ]
-    }))
-}
-
-fn build_limit_or_stop_paramters(
-    execution_type: &ExecutionType,
-    symbol: &Symbol,
-    side: &Side,
-    size: f64,
-    price: i64,
-    position_id: &str,
-    time_in_force: &TimeInForce,
-) -> Result<Value, Error> {
-    Ok(json!({
-        "executionType": execution_type.to_string(),
-        "symbol": symbol.to_string(),
-        "side": side.to_string(),
-        "timeInForce": time_in_force.to_string(),
-        "price": price.to_string(),
-        "settlePosition": [
-            {
-                "positionId": id_to_num(&position_id)?,
-                "size": size.to_string(),
-            }
-        ]
-    }))
+/// 決済する建玉IDと数量の配列から`settlePosition`を作る。
+fn build_settle_position(positions: &[(&PositionId, Size)]) -> Result<Value, Error> {
+    let mut list = Vec::with_capacity(positions.len());
+    for (position_id, size) in positions {
+        list.push(json!({
+            "positionId": position_id.to_num()?,
+            "size": size.to_string(),
+        }));
+    }
+    Ok(Value::Array(list))
 }
 
 /// 決済注文APIを呼び出す。
 pub async fn request_close_order(
     http_client: &impl HttpClient,
+    config: &ClientConfig,
+    credentials: &Credentials,
     execution_type: &ExecutionType,
     symbol: &Symbol,
     side: &Side,
-    size: f64,
-    price: Option<i64>,
-    position_id: &str,
+    price: Option<Price>,
+    positions: &[(&PositionId, Size)],
     time_in_force: &TimeInForce,
 ) -> Result<RestResponse<CloseOrder>, Error> {
-    let url = format!("{}{}", PRIVATE_ENDPOINT, CLOSE_ORDER_API_PATH,);
+    let url = format!("{}{}", config.private_endpoint(), CLOSE_ORDER_API_PATH,);
     let parameters = build_parameters(
-        &execution_type,
-        &symbol,
-        &side,
-        size,
+        execution_type,
+        symbol,
+        side,
         price,
-        &position_id,
-        &time_in_force,
+        positions,
+        time_in_force,
     )?;
-    let headers = Headers::create_post_headers(&CLOSE_ORDER_API_PATH, &parameters)?;
-    let response = http_client.post(url, &headers, &parameters).await?;
-    parse_from_http_response::<CloseOrder>(&response)
+    let headers = Headers::create_post_headers(credentials, CLOSE_ORDER_API_PATH, &parameters)?;
+    let response = with_timeout(config, http_client.post(url, &headers, &parameters)).await?;
+    parse_from_http_response::<CloseOrder>(&response, CLOSE_ORDER_API_PATH, config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::credentials::tests::fake_credentials;
     use crate::http_client::tests::InmemClient;
+    use crate::size::size;
     use chrono::SecondsFormat;
 
     const SAMPLE_RESPONSE: &str = r#"
@@ -169,12 +145,13 @@ mod tests {
         };
         let resp = request_close_order(
             &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
             &ExecutionType::Market,
             &Symbol::BtcJpy,
             &Side::Buy,
-            0.1,
             None,
-            "110",
+            &[(&PositionId::new("110"), size("0.1"))],
             &TimeInForce::Fak,
         )
         .await
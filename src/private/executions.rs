@@ -1,10 +1,12 @@
 //! 約定情報取得APIを実装する。
 
+use crate::client_config::ClientConfig;
+use crate::credentials::Credentials;
 use crate::dto::{get_vector_default_value, Execution};
-use crate::end_point::*;
 use crate::error::Error;
 use crate::headers::Headers;
 use crate::http_client::*;
+use crate::id::{ExecutionId, OrderId};
 use crate::json::*;
 use crate::response::*;
 use chrono::{DateTime, Utc};
@@ -45,34 +47,43 @@ impl RestResponse<Executions> {
 /// 約定情報取得APIを呼び出す。注文IDを指定してAPIを呼び出す。
 pub async fn request_executions_with_order_id(
     http_client: &impl HttpClient,
-    order_id: &str,
+    config: &ClientConfig,
+    credentials: &Credentials,
+    order_id: &OrderId,
 ) -> Result<RestResponse<Executions>, Error> {
     let url = format!(
         "{}{}?orderId={}",
-        PRIVATE_ENDPOINT, EXECUTIONS_API_PATH, order_id,
+        config.private_endpoint(),
+        EXECUTIONS_API_PATH,
+        order_id,
     );
-    let headers = Headers::create_get_headers(&EXECUTIONS_API_PATH)?;
-    let response = http_client.get(url, &headers).await?;
-    parse_from_http_response::<Executions>(&response)
+    let headers = Headers::create_get_headers(credentials, EXECUTIONS_API_PATH)?;
+    let response = with_timeout(config, http_client.get(url, &headers)).await?;
+    parse_from_http_response::<Executions>(&response, EXECUTIONS_API_PATH, config)
 }
 
 /// 約定情報取得APIを呼び出す。約定IDを指定してAPIを呼び出す。
 pub async fn request_executions_with_execution_id(
     http_client: &impl HttpClient,
-    execution_id: &str,
+    config: &ClientConfig,
+    credentials: &Credentials,
+    execution_id: &ExecutionId,
 ) -> Result<RestResponse<Executions>, Error> {
     let url = format!(
         "{}{}?executionId={}",
-        PRIVATE_ENDPOINT, EXECUTIONS_API_PATH, execution_id,
+        config.private_endpoint(),
+        EXECUTIONS_API_PATH,
+        execution_id,
     );
-    let headers = Headers::create_get_headers(&EXECUTIONS_API_PATH)?;
-    let response = http_client.get(url, &headers).await?;
-    parse_from_http_response::<Executions>(&response)
+    let headers = Headers::create_get_headers(credentials, EXECUTIONS_API_PATH)?;
+    let response = with_timeout(config, http_client.get(url, &headers)).await?;
+    parse_from_http_response::<Executions>(&response, EXECUTIONS_API_PATH, config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::credentials::tests::fake_credentials;
     use crate::http_client::tests::InmemClient;
     use chrono::SecondsFormat;
 
@@ -115,9 +126,14 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_executions_with_execution_id(&http_client, "execid")
-            .await
-            .unwrap();
+        let resp = request_executions_with_execution_id(
+            &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
+            &ExecutionId::new("execid"),
+        )
+        .await
+        .unwrap();
         assert_eq!(resp.http_status_code, 200);
         assert_eq!(resp.body.status, 0);
         assert_eq!(
@@ -137,9 +153,14 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_executions_with_order_id(&http_client, "orderid")
-            .await
-            .unwrap();
+        let resp = request_executions_with_order_id(
+            &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
+            &OrderId::new("orderid"),
+        )
+        .await
+        .unwrap();
         assert_eq!(resp.http_status_code, 200);
         assert_eq!(resp.body.status, 0);
         assert_eq!(
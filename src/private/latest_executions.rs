@@ -1,7 +1,8 @@
 //! 最新の約定一覧APIを実装する。
 
+use crate::client_config::ClientConfig;
+use crate::credentials::Credentials;
 use crate::dto::{get_pagination_default_value, get_vector_default_value, Execution, Pagination};
-use crate::end_point::*;
 use crate::error::Error;
 use crate::headers::Headers;
 use crate::http_client::*;
@@ -60,26 +61,29 @@ impl RestResponse<LatestExecutions> {
 /// 最新の約定一覧APIを呼び出す。
 pub async fn request_latest_executions(
     http_client: &impl HttpClient,
+    config: &ClientConfig,
+    credentials: &Credentials,
     symbol: &Symbol,
     page: i32,
     count: i32,
 ) -> Result<RestResponse<LatestExecutions>, Error> {
     let url = format!(
         "{}{}?symbol={}&page={}&count={}",
-        PRIVATE_ENDPOINT,
+        config.private_endpoint(),
         LATEST_EXECUTIONS_API_PATH,
         symbol.to_string(),
         page,
         count
     );
-    let headers = Headers::create_get_headers(&LATEST_EXECUTIONS_API_PATH)?;
-    let response = http_client.get(url, &headers).await?;
-    parse_from_http_response::<LatestExecutions>(&response)
+    let headers = Headers::create_get_headers(credentials, LATEST_EXECUTIONS_API_PATH)?;
+    let response = with_timeout(config, http_client.get(url, &headers)).await?;
+    parse_from_http_response::<LatestExecutions>(&response, LATEST_EXECUTIONS_API_PATH, config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::credentials::tests::fake_credentials;
     use crate::http_client::tests::InmemClient;
     use crate::symbol::Symbol;
     use chrono::SecondsFormat;
@@ -127,9 +131,16 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_latest_executions(&http_client, &Symbol::Btc, 1, 100)
-            .await
-            .unwrap();
+        let resp = request_latest_executions(
+            &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
+            &Symbol::Btc,
+            1,
+            100,
+        )
+        .await
+        .unwrap();
         assert_eq!(resp.http_status_code, 200);
         assert_eq!(resp.body.status, 0);
         assert_eq!(
@@ -151,9 +162,16 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_latest_executions(&http_client, &Symbol::BtcJpy, 1, 100)
-            .await
-            .unwrap();
+        let resp = request_latest_executions(
+            &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
+            &Symbol::BtcJpy,
+            1,
+            100,
+        )
+        .await
+        .unwrap();
         assert_eq!(resp.http_status_code, 200);
         assert_eq!(resp.body.status, 0);
         assert_eq!(
@@ -1,6 +1,7 @@
 //! 余力情報APIを実装する。
 
-use crate::end_point::*;
+use crate::client_config::ClientConfig;
+use crate::credentials::Credentials;
 use crate::error::Error;
 use crate::headers::Headers;
 use crate::http_client::*;
@@ -69,16 +70,21 @@ impl RestResponse<Margin> {
 }
 
 /// 余力情報APIを呼び出す。
-pub async fn request_margin(http_client: &impl HttpClient) -> Result<RestResponse<Margin>, Error> {
-    let url = format!("{}{}", PRIVATE_ENDPOINT, MARGIN_API_PATH);
-    let headers = Headers::create_get_headers(&MARGIN_API_PATH)?;
-    let response = http_client.get(url, &headers).await?;
-    parse_from_http_response::<Margin>(&response)
+pub async fn request_margin(
+    http_client: &impl HttpClient,
+    config: &ClientConfig,
+    credentials: &Credentials,
+) -> Result<RestResponse<Margin>, Error> {
+    let url = format!("{}{}", config.private_endpoint(), MARGIN_API_PATH);
+    let headers = Headers::create_get_headers(credentials, MARGIN_API_PATH)?;
+    let response = with_timeout(config, http_client.get(url, &headers)).await?;
+    parse_from_http_response::<Margin>(&response, MARGIN_API_PATH, config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::credentials::tests::fake_credentials;
     use crate::http_client::tests::InmemClient;
     use chrono::SecondsFormat;
 
@@ -103,7 +109,9 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_margin(&http_client).await.unwrap();
+        let resp = request_margin(&http_client, &ClientConfig::default(), &fake_credentials())
+            .await
+            .unwrap();
         assert_eq!(resp.http_status_code, 200);
         assert_eq!(resp.body.status, 0);
         assert_eq!(
@@ -2,14 +2,18 @@
 
 #![allow(clippy::too_many_arguments)]
 
-use crate::end_point::*;
+use crate::client_config::ClientConfig;
+use crate::credentials::Credentials;
 use crate::error::Error;
 use crate::execution_type::ExecutionType;
 use crate::headers::Headers;
 use crate::http_client::*;
+use crate::id::OrderId;
 use crate::json::*;
+use crate::price::Price;
 use crate::response::*;
 use crate::side::Side;
+use crate::size::Size;
 use crate::symbol::Symbol;
 use crate::time_in_force::TimeInForce;
 use chrono::{DateTime, Utc};
@@ -30,12 +34,12 @@ pub struct Order {
     pub responsetime: DateTime<Utc>,
 
     /// 注文ID。
-    pub data: String,
+    pub data: OrderId,
 }
 
 impl RestResponse<Order> {
     /// 注文IDを取得する。
-    pub fn order_id(&self) -> &str {
+    pub fn order_id(&self) -> &OrderId {
         &self.body.data
     }
 }
@@ -44,22 +48,22 @@ fn build_parameters(
     execution_type: &ExecutionType,
     symbol: &Symbol,
     side: &Side,
-    size: f64,
+    size: Size,
     time_in_force: &TimeInForce,
-    price: Option<i64>,
-    losscut_price: Option<i64>,
+    price: Option<Price>,
+    losscut_price: Option<Price>,
 ) -> Result<Value, Error> {
     Ok(match execution_type {
         ExecutionType::Market => {
-            build_market_parameters(&execution_type, &symbol, &side, size, &time_in_force)
+            build_market_parameters(execution_type, symbol, side, size, time_in_force)
         }
         _ => match price {
             Some(p) => build_limit_or_stop_paramters(
-                &execution_type,
-                &symbol,
-                &side,
+                execution_type,
+                symbol,
+                side,
                 size,
-                &time_in_force,
+                time_in_force,
                 p,
                 losscut_price,
             ),
@@ -72,7 +76,7 @@ fn build_market_parameters(
     execution_type: &ExecutionType,
     symbol: &Symbol,
     side: &Side,
-    size: f64,
+    size: Size,
     time_in_force: &TimeInForce,
 ) -> Value {
     json!({
@@ -88,10 +92,10 @@ fn build_limit_or_stop_paramters(
     execution_type: &ExecutionType,
     symbol: &Symbol,
     side: &Side,
-    size: f64,
+    size: Size,
     time_in_force: &TimeInForce,
-    price: i64,
-    losscut_price: Option<i64>,
+    price: Price,
+    losscut_price: Option<Price>,
 ) -> Value {
     match losscut_price {
         Some(lp) => json!({
@@ -101,7 +105,7 @@ fn build_limit_or_stop_paramters(
             "size": size.to_string(),
             "price": price.to_string(),
             "timeInForce": time_in_force.to_string(),
-            "losscutPrice": lp,
+            "losscutPrice": lp.to_string(),
         }),
         None => json!({
             "symbol": symbol.to_string(),
@@ -117,33 +121,38 @@ fn build_limit_or_stop_paramters(
 /// 新規注文APIを呼び出す。
 pub async fn request_order(
     http_client: &impl HttpClient,
+    config: &ClientConfig,
+    credentials: &Credentials,
     execution_type: &ExecutionType,
     symbol: &Symbol,
     side: &Side,
-    size: f64,
+    size: Size,
     time_in_force: &TimeInForce,
-    price: Option<i64>,
-    losscut_price: Option<i64>,
+    price: Option<Price>,
+    losscut_price: Option<Price>,
 ) -> Result<RestResponse<Order>, Error> {
-    let url = format!("{}{}", PRIVATE_ENDPOINT, ORDER_API_PATH,);
+    let url = format!("{}{}", config.private_endpoint(), ORDER_API_PATH,);
     let parameters = build_parameters(
-        &execution_type,
-        &symbol,
-        &side,
+        execution_type,
+        symbol,
+        side,
         size,
-        &time_in_force,
+        time_in_force,
         price,
         losscut_price,
     )?;
-    let headers = Headers::create_post_headers(&ORDER_API_PATH, &parameters)?;
-    let response = http_client.post(url, &headers, &parameters).await?;
-    parse_from_http_response::<Order>(&response)
+    let headers = Headers::create_post_headers(credentials, ORDER_API_PATH, &parameters)?;
+    let response = with_timeout(config, http_client.post(url, &headers, &parameters)).await?;
+    parse_from_http_response::<Order>(&response, ORDER_API_PATH, config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::credentials::tests::fake_credentials;
     use crate::http_client::tests::InmemClient;
+    use crate::price::price;
+    use crate::size::size;
     use chrono::SecondsFormat;
 
     const SAMPLE_RESPONSE: &str = r#"
@@ -164,10 +173,12 @@ mod tests {
         };
         let resp = request_order(
             &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
             &ExecutionType::Market,
             &Symbol::BtcJpy,
             &Side::Buy,
-            0.1,
+            size("0.1"),
             &TimeInForce::Fak,
             None,
             None,
@@ -195,13 +206,15 @@ mod tests {
         };
         let resp = request_order(
             &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
             &ExecutionType::Limit,
             &Symbol::BtcJpy,
             &Side::Buy,
-            0.1,
+            size("0.1"),
             &TimeInForce::Fas,
-            Some(100),
-            Some(100),
+            Some(price("100")),
+            Some(price("100")),
         )
         .await
         .unwrap();
@@ -226,12 +239,14 @@ mod tests {
         };
         let resp = request_order(
             &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
             &ExecutionType::Stop,
             &Symbol::BtcJpy,
             &Side::Buy,
-            0.1,
+            size("0.1"),
             &TimeInForce::Fas,
-            Some(100),
+            Some(price("100")),
             None,
         )
         .await
@@ -246,4 +261,69 @@ mod tests {
         );
         assert_eq!(resp.order_id(), "637000");
     }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn test_decimal_size_parameter() {
+        let parameters = build_parameters(
+            &ExecutionType::Market,
+            &Symbol::Btc,
+            &Side::Buy,
+            size("0.1") + size("0.2"),
+            &TimeInForce::Fak,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(parameters["size"], "0.3");
+    }
+
+    #[test]
+    fn test_build_parameters() {
+        let parameters = build_parameters(
+            &ExecutionType::Limit,
+            &Symbol::BtcJpy,
+            &Side::Buy,
+            size("0.1"),
+            &TimeInForce::Fas,
+            Some(price("1000000")),
+            Some(price("900000")),
+        )
+        .unwrap();
+        assert_eq!(
+            parameters,
+            json!({
+                "symbol": "BTC_JPY",
+                "side": "BUY",
+                "executionType": "LIMIT",
+                "size": "0.1",
+                "price": "1000000",
+                "timeInForce": "FAS",
+                "losscutPrice": "900000",
+            })
+        );
+
+        let parameters = build_parameters(
+            &ExecutionType::Stop,
+            &Symbol::XrpJpy,
+            &Side::Sell,
+            size("10"),
+            &TimeInForce::Fak,
+            Some(price("52.123")),
+            Some(price("55.5")),
+        )
+        .unwrap();
+        assert_eq!(
+            parameters,
+            json!({
+                "symbol": "XRP_JPY",
+                "side": "SELL",
+                "executionType": "STOP",
+                "size": "10",
+                "price": "52.123",
+                "timeInForce": "FAK",
+                "losscutPrice": "55.5",
+            })
+        );
+    }
 }
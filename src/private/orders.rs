@@ -1,10 +1,12 @@
 //! 注文情報取得APIを実装する。
 
+use crate::client_config::ClientConfig;
+use crate::credentials::Credentials;
 use crate::dto::{get_vector_default_value, Order};
-use crate::end_point::*;
 use crate::error::Error;
 use crate::headers::Headers;
 use crate::http_client::*;
+use crate::id::OrderId;
 use crate::json::*;
 use crate::response::*;
 use chrono::{DateTime, Utc};
@@ -13,6 +15,9 @@ use serde::Deserialize;
 /// 注文情報取得APIのパス。
 const ORDERS_API_PATH: &str = "/v1/orders";
 
+/// 注文情報取得APIで一度に指定できる注文IDの最大件数。
+pub(crate) const MAX_ORDER_IDS: usize = 10;
+
 /// 注文情報取得APIから返ってくるレスポンスのうち`list`の部分を格納数する構造体。
 #[derive(Deserialize)]
 pub struct Data {
@@ -40,27 +45,40 @@ impl RestResponse<Orders> {
     pub fn orders(&self) -> &Vec<Order> {
         &self.body.data.list
     }
+
+    /// 別の呼び出しで取得した注文情報を後ろにつなげる。
+    pub(crate) fn extend(&mut self, other: RestResponse<Orders>) {
+        self.body.data.list.extend(other.body.data.list);
+        self.unknown_fields.extend(other.unknown_fields);
+    }
 }
 
 /// 注文情報取得APIを呼び出す。
 pub async fn request_orders(
     http_client: &impl HttpClient,
-    order_ids: &[&str],
+    config: &ClientConfig,
+    credentials: &Credentials,
+    order_ids: &[&OrderId],
 ) -> Result<RestResponse<Orders>, Error> {
     let url = format!(
         "{}{}?orderId={}",
-        PRIVATE_ENDPOINT,
+        config.private_endpoint(),
         ORDERS_API_PATH,
-        order_ids.join(",")
+        order_ids
+            .iter()
+            .map(|id| id.as_str())
+            .collect::<Vec<&str>>()
+            .join(",")
     );
-    let headers = Headers::create_get_headers(&ORDERS_API_PATH)?;
-    let response = http_client.get(url, &headers).await?;
-    parse_from_http_response::<Orders>(&response)
+    let headers = Headers::create_get_headers(credentials, ORDERS_API_PATH)?;
+    let response = with_timeout(config, http_client.get(url, &headers)).await?;
+    parse_from_http_response::<Orders>(&response, ORDERS_API_PATH, config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::credentials::tests::fake_credentials;
     use crate::http_client::tests::InmemClient;
     use chrono::SecondsFormat;
 
@@ -124,9 +142,14 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_orders(&http_client, &Vec::<&str>::new())
-            .await
-            .unwrap();
+        let resp = request_orders(
+            &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
+            &Vec::<&OrderId>::new(),
+        )
+        .await
+        .unwrap();
         assert_eq!(resp.http_status_code, 200);
         assert_eq!(resp.body.status, 0);
         assert_eq!(
@@ -146,9 +169,14 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_orders(&http_client, &Vec::<&str>::new())
-            .await
-            .unwrap();
+        let resp = request_orders(
+            &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
+            &Vec::<&OrderId>::new(),
+        )
+        .await
+        .unwrap();
         assert_eq!(resp.http_status_code, 200);
         assert_eq!(resp.body.status, 0);
         assert_eq!(
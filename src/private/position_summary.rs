@@ -1,7 +1,8 @@
 //! 建玉サマリーAPIを実装する。
 
+use crate::client_config::ClientConfig;
+use crate::credentials::Credentials;
 use crate::dto::{get_vector_default_value, Summary};
-use crate::end_point::*;
 use crate::error::Error;
 use crate::headers::Headers;
 use crate::http_client::*;
@@ -46,22 +47,25 @@ impl RestResponse<PositionSummary> {
 /// 建玉サマリーAPIを呼び出す。
 pub async fn request_position_summary(
     http_client: &impl HttpClient,
+    config: &ClientConfig,
+    credentials: &Credentials,
     symbol: &Symbol,
 ) -> Result<RestResponse<PositionSummary>, Error> {
     let url = format!(
         "{}{}?symbol={}",
-        PRIVATE_ENDPOINT,
+        config.private_endpoint(),
         POSITION_SUMMARY_API_PATH,
         symbol.to_string(),
     );
-    let headers = Headers::create_get_headers(&POSITION_SUMMARY_API_PATH)?;
-    let response = http_client.get(url, &headers).await?;
-    parse_from_http_response::<PositionSummary>(&response)
+    let headers = Headers::create_get_headers(credentials, POSITION_SUMMARY_API_PATH)?;
+    let response = with_timeout(config, http_client.get(url, &headers)).await?;
+    parse_from_http_response::<PositionSummary>(&response, POSITION_SUMMARY_API_PATH, config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::credentials::tests::fake_credentials;
     use crate::http_client::tests::InmemClient;
     use crate::symbol::Symbol;
     use chrono::SecondsFormat;
@@ -101,9 +105,14 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_position_summary(&http_client, &Symbol::BtcJpy)
-            .await
-            .unwrap();
+        let resp = request_position_summary(
+            &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
+            &Symbol::BtcJpy,
+        )
+        .await
+        .unwrap();
         assert_eq!(resp.http_status_code, 200);
         assert_eq!(resp.body.status, 0);
         assert_eq!(
@@ -123,9 +132,14 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_position_summary(&http_client, &Symbol::BtcJpy)
-            .await
-            .unwrap();
+        let resp = request_position_summary(
+            &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
+            &Symbol::BtcJpy,
+        )
+        .await
+        .unwrap();
         assert_eq!(resp.http_status_code, 200);
         assert_eq!(resp.body.status, 0);
         assert_eq!(
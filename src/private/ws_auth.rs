@@ -0,0 +1,191 @@
+//! Private WebSocket APIのアクセストークンを取得、延長、削除するAPIを実装する。
+
+use crate::client_config::ClientConfig;
+use crate::credentials::Credentials;
+use crate::error::Error;
+use crate::headers::Headers;
+use crate::http_client::*;
+use crate::json::*;
+use crate::response::*;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// アクセストークンAPIのパス。
+const WS_AUTH_API_PATH: &str = "/v1/ws-auth";
+
+/// アクセストークン取得APIから返ってくるレスポンスを格納する構造体。
+#[derive(Deserialize)]
+pub struct WsAuth {
+    /// ステータスコード。
+    pub status: i16,
+
+    /// APIが呼び出された時間。
+    #[serde(deserialize_with = "gmo_timestamp_to_chrono_timestamp")]
+    pub responsetime: DateTime<Utc>,
+
+    /// アクセストークン。
+    pub data: String,
+}
+
+impl RestResponse<WsAuth> {
+    /// アクセストークンを取得する。
+    pub fn token(&self) -> &str {
+        &self.body.data
+    }
+}
+
+/// アクセストークン延長APIから返ってくるレスポンスを格納する構造体。
+#[derive(Deserialize)]
+pub struct ExtendWsAuth {
+    /// ステータスコード。
+    pub status: i16,
+
+    /// APIが呼び出された時間。
+    #[serde(deserialize_with = "gmo_timestamp_to_chrono_timestamp")]
+    pub responsetime: DateTime<Utc>,
+}
+
+/// アクセストークン削除APIから返ってくるレスポンスを格納する構造体。
+#[derive(Deserialize)]
+pub struct RevokeWsAuth {
+    /// ステータスコード。
+    pub status: i16,
+
+    /// APIが呼び出された時間。
+    #[serde(deserialize_with = "gmo_timestamp_to_chrono_timestamp")]
+    pub responsetime: DateTime<Utc>,
+}
+
+fn build_parameters(token: &str) -> Value {
+    json!({
+        "token": token,
+    })
+}
+
+/// アクセストークン取得APIを呼び出す。
+pub async fn request_create_ws_auth(
+    http_client: &impl HttpClient,
+    config: &ClientConfig,
+    credentials: &Credentials,
+) -> Result<RestResponse<WsAuth>, Error> {
+    let url = format!("{}{}", config.private_endpoint(), WS_AUTH_API_PATH);
+    let parameters = json!({});
+    let headers = Headers::create_post_headers(credentials, WS_AUTH_API_PATH, &parameters)?;
+    let response = with_timeout(config, http_client.post(url, &headers, &parameters)).await?;
+    parse_from_http_response::<WsAuth>(&response, WS_AUTH_API_PATH, config)
+}
+
+/// アクセストークン延長APIを呼び出す。
+pub async fn request_extend_ws_auth(
+    http_client: &impl HttpClient,
+    config: &ClientConfig,
+    credentials: &Credentials,
+    token: &str,
+) -> Result<RestResponse<ExtendWsAuth>, Error> {
+    let url = format!("{}{}", config.private_endpoint(), WS_AUTH_API_PATH);
+    let parameters = build_parameters(token);
+    let headers = Headers::create_put_headers(credentials, WS_AUTH_API_PATH, &parameters)?;
+    let response = with_timeout(config, http_client.put(url, &headers, &parameters)).await?;
+    parse_from_http_response::<ExtendWsAuth>(&response, WS_AUTH_API_PATH, config)
+}
+
+/// アクセストークン削除APIを呼び出す。
+pub async fn request_revoke_ws_auth(
+    http_client: &impl HttpClient,
+    config: &ClientConfig,
+    credentials: &Credentials,
+    token: &str,
+) -> Result<RestResponse<RevokeWsAuth>, Error> {
+    let url = format!("{}{}", config.private_endpoint(), WS_AUTH_API_PATH);
+    let parameters = build_parameters(token);
+    let headers = Headers::create_delete_headers(credentials, WS_AUTH_API_PATH, &parameters)?;
+    let response = with_timeout(config, http_client.delete(url, &headers, &parameters)).await?;
+    parse_from_http_response::<RevokeWsAuth>(&response, WS_AUTH_API_PATH, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::tests::fake_credentials;
+    use crate::http_client::tests::InmemClient;
+    use chrono::SecondsFormat;
+
+    const SAMPLE_RESPONSE: &str = r#"
+    {
+        "status": 0,
+        "data": "xxxxxxxxxxxxxxxxxxxx",
+        "responsetime": "2019-03-19T02:15:06.102Z"
+    }
+    "#;
+
+    const SAMPLE_EMPTY_RESPONSE: &str = r#"
+    {
+        "status": 0,
+        "responsetime": "2019-03-19T02:15:06.102Z"
+    }
+    "#;
+
+    #[tokio::test]
+    async fn test_create_ws_auth() {
+        let body = SAMPLE_RESPONSE;
+        let http_client = InmemClient {
+            http_status_code: 200,
+            body_text: body.to_string(),
+            return_error: false,
+        };
+        let resp =
+            request_create_ws_auth(&http_client, &ClientConfig::default(), &fake_credentials())
+                .await
+                .unwrap();
+        assert_eq!(resp.http_status_code, 200);
+        assert_eq!(resp.body.status, 0);
+        assert_eq!(
+            resp.body
+                .responsetime
+                .to_rfc3339_opts(SecondsFormat::Millis, true),
+            "2019-03-19T02:15:06.102Z"
+        );
+        assert_eq!(resp.token(), "xxxxxxxxxxxxxxxxxxxx");
+    }
+
+    #[tokio::test]
+    async fn test_extend_ws_auth() {
+        let body = SAMPLE_EMPTY_RESPONSE;
+        let http_client = InmemClient {
+            http_status_code: 200,
+            body_text: body.to_string(),
+            return_error: false,
+        };
+        let resp = request_extend_ws_auth(
+            &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
+            "xxxxxxxxxxxxxxxxxxxx",
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.http_status_code, 200);
+        assert_eq!(resp.body.status, 0);
+    }
+
+    #[tokio::test]
+    async fn test_revoke_ws_auth() {
+        let body = SAMPLE_EMPTY_RESPONSE;
+        let http_client = InmemClient {
+            http_status_code: 200,
+            body_text: body.to_string(),
+            return_error: false,
+        };
+        let resp = request_revoke_ws_auth(
+            &http_client,
+            &ClientConfig::default(),
+            &fake_credentials(),
+            "xxxxxxxxxxxxxxxxxxxx",
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.http_status_code, 200);
+        assert_eq!(resp.body.status, 0);
+    }
+}
@@ -0,0 +1,885 @@
+//! Private WebSocket APIを実装する。
+
+use crate::dto::{get_string_default_value, Execution, MaybeUnknown, Order, Position, Summary};
+use crate::error::Error;
+use crate::execution_type::ExecutionType;
+use crate::http_client::HttpClient;
+use crate::id::{ExecutionId, OrderId};
+use crate::json::*;
+use crate::order_status::OrderStatus;
+use crate::order_type::OrderType;
+use crate::price::Price;
+use crate::private::PrivateAPI;
+use crate::settle_type::SettleType;
+use crate::side::Side;
+use crate::size::Size;
+use crate::time_in_force::TimeInForce;
+use crate::web_socket::*;
+use chrono::{DateTime, Utc};
+use futures::future::{AbortHandle, Abortable};
+use futures::stream::{self, Stream, StreamExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::{delay_for, delay_until, Instant};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Private WebSocket APIのパス。
+const PRIVATE_WEB_SOCKET_API_PATH: &str = "/v1";
+
+/// アクセストークンの有効期限。GMOコインでは取得、延長してから60分。
+const TOKEN_LIFETIME: Duration = Duration::from_secs(60 * 60);
+
+/// アクセストークンを延長する間隔のデフォルト値。有効期限が切れる前に延長する。
+const DEFAULT_TOKEN_EXTENSION_INTERVAL: Duration = Duration::from_secs(50 * 60);
+
+/// アクセストークンの延長に失敗したときに再度延長を試みるまでの最大の間隔。
+const TOKEN_EXTENSION_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 再接続時に購読し直すときの間隔。GMOコインでは購読リクエストは1秒に1回まで。
+const RESUBSCRIBE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 購読できるチャンネルの候補。
+#[derive(Copy, Clone, PartialEq)]
+pub enum PrivateChannel {
+    /// 約定情報通知。
+    ExecutionEvents,
+
+    /// 注文情報通知。
+    OrderEvents,
+
+    /// 建玉情報通知。
+    PositionEvents,
+
+    /// 建玉サマリー情報通知。
+    PositionSummaryEvents,
+}
+
+/// 約定情報通知のチャンネル名。
+pub const EXECUTION_EVENTS_CHANNEL: &str = "executionEvents";
+
+/// 注文情報通知のチャンネル名。
+pub const ORDER_EVENTS_CHANNEL: &str = "orderEvents";
+
+/// 建玉情報通知のチャンネル名。
+pub const POSITION_EVENTS_CHANNEL: &str = "positionEvents";
+
+/// 建玉サマリー情報通知のチャンネル名。
+pub const POSITION_SUMMARY_EVENTS_CHANNEL: &str = "positionSummaryEvents";
+
+impl PrivateChannel {
+    /// チャンネルを文字列に変換する。
+    pub fn to_string(&self) -> &str {
+        match self {
+            PrivateChannel::ExecutionEvents => EXECUTION_EVENTS_CHANNEL,
+            PrivateChannel::OrderEvents => ORDER_EVENTS_CHANNEL,
+            PrivateChannel::PositionEvents => POSITION_EVENTS_CHANNEL,
+            PrivateChannel::PositionSummaryEvents => POSITION_SUMMARY_EVENTS_CHANNEL,
+        }
+    }
+}
+
+/// Private WebSocket APIから配信されるイベント。
+pub enum PrivateEvent {
+    /// 約定情報通知。
+    Execution {
+        /// メッセージタイプ。"ER"。
+        msg_type: String,
+
+        /// 約定情報。
+        execution: Execution,
+    },
+
+    /// 注文情報通知。
+    Order {
+        /// メッセージタイプ。"NOR", "ROR", "COR", or "ER"。
+        msg_type: String,
+
+        /// 注文情報。
+        order: Order,
+    },
+
+    /// 建玉情報通知。
+    Position {
+        /// メッセージタイプ。"OPR", "UPR", "ULR", or "CPR"。
+        msg_type: String,
+
+        /// 建玉情報。
+        position: Position,
+    },
+
+    /// 建玉サマリー情報通知。
+    PositionSummary {
+        /// メッセージタイプ。"INIT", "UPDATE", or "PERIODIC"。
+        msg_type: String,
+
+        /// 建玉サマリー。
+        summary: Summary,
+    },
+}
+
+/// 約定情報通知で配信されるメッセージを格納する構造体。
+#[derive(Deserialize)]
+struct ExecutionEventMessage {
+    #[serde(rename = "orderId")]
+    order_id: OrderId,
+
+    #[serde(rename = "executionId")]
+    execution_id: ExecutionId,
+
+    symbol: String,
+
+    #[serde(rename = "settleType")]
+    settle_type: MaybeUnknown<SettleType>,
+
+    side: MaybeUnknown<Side>,
+
+    #[serde(deserialize_with = "str_to_price", rename = "executionPrice")]
+    execution_price: Price,
+
+    #[serde(deserialize_with = "str_to_size", rename = "executionSize")]
+    execution_size: Size,
+
+    #[serde(
+        deserialize_with = "gmo_timestamp_to_chrono_timestamp",
+        rename = "executionTimestamp"
+    )]
+    execution_timestamp: DateTime<Utc>,
+
+    #[serde(deserialize_with = "str_to_i64", rename = "lossGain")]
+    loss_gain: i64,
+
+    #[serde(deserialize_with = "str_to_i64")]
+    fee: i64,
+
+    #[serde(rename = "msgType")]
+    msg_type: String,
+}
+
+/// 注文情報通知で配信されるメッセージを格納する構造体。
+#[derive(Deserialize)]
+struct OrderEventMessage {
+    #[serde(rename = "orderId")]
+    order_id: OrderId,
+
+    symbol: String,
+
+    #[serde(rename = "settleType")]
+    settle_type: MaybeUnknown<SettleType>,
+
+    #[serde(rename = "executionType")]
+    execution_type: MaybeUnknown<ExecutionType>,
+
+    side: MaybeUnknown<Side>,
+
+    #[serde(rename = "orderStatus")]
+    order_status: MaybeUnknown<OrderStatus>,
+
+    #[serde(default = "get_string_default_value", rename = "cancelType")]
+    cancel_type: String,
+
+    #[serde(
+        deserialize_with = "gmo_timestamp_to_chrono_timestamp",
+        rename = "orderTimestamp"
+    )]
+    order_timestamp: DateTime<Utc>,
+
+    #[serde(deserialize_with = "str_to_price", rename = "orderPrice")]
+    order_price: Price,
+
+    #[serde(deserialize_with = "str_to_size", rename = "orderSize")]
+    order_size: Size,
+
+    #[serde(deserialize_with = "str_to_size", rename = "orderExecutedSize")]
+    order_executed_size: Size,
+
+    #[serde(deserialize_with = "str_to_price", rename = "losscutPrice")]
+    losscut_price: Price,
+
+    #[serde(rename = "timeInForce")]
+    time_in_force: MaybeUnknown<TimeInForce>,
+
+    #[serde(rename = "msgType")]
+    msg_type: String,
+}
+
+/// 建玉情報通知で配信されるメッセージを格納する構造体。
+#[derive(Deserialize)]
+struct PositionEventMessage {
+    #[serde(flatten)]
+    position: Position,
+
+    #[serde(rename = "msgType")]
+    msg_type: String,
+}
+
+/// 建玉サマリー情報通知で配信されるメッセージを格納する構造体。
+#[derive(Deserialize)]
+struct PositionSummaryEventMessage {
+    #[serde(flatten)]
+    summary: Summary,
+
+    #[serde(rename = "msgType")]
+    msg_type: String,
+}
+
+/// Private WebSocket APIから配信されるメッセージ。
+#[derive(Deserialize)]
+#[serde(tag = "channel")]
+enum EventMessage {
+    #[serde(rename = "executionEvents")]
+    Execution(ExecutionEventMessage),
+
+    #[serde(rename = "orderEvents")]
+    Order(OrderEventMessage),
+
+    #[serde(rename = "positionEvents")]
+    Position(PositionEventMessage),
+
+    #[serde(rename = "positionSummaryEvents")]
+    PositionSummary(PositionSummaryEventMessage),
+}
+
+impl From<EventMessage> for PrivateEvent {
+    fn from(message: EventMessage) -> Self {
+        match message {
+            EventMessage::Execution(m) => PrivateEvent::Execution {
+                msg_type: m.msg_type,
+                execution: Execution {
+                    execution_id: m.execution_id,
+                    order_id: m.order_id,
+                    symbol: m.symbol,
+                    side: m.side,
+                    settle_type: m.settle_type,
+                    size: m.execution_size,
+                    price: m.execution_price,
+                    loss_gain: m.loss_gain,
+                    fee: m.fee,
+                    timestamp: m.execution_timestamp,
+                },
+            },
+            EventMessage::Order(m) => PrivateEvent::Order {
+                msg_type: m.msg_type,
+                order: Order {
+                    // 注文情報通知では親注文IDと取引区分は配信されないので、注文IDと通常の注文で埋める。
+                    root_order_id: m.order_id.clone(),
+                    order_id: m.order_id,
+                    symbol: m.symbol,
+                    side: m.side,
+                    order_type: MaybeUnknown::Known(OrderType::Normal),
+                    execution_type: m.execution_type,
+                    settle_type: m.settle_type,
+                    size: m.order_size,
+                    executed_size: m.order_executed_size,
+                    price: m.order_price,
+                    losscut_price: m.losscut_price,
+                    status: m.order_status,
+                    cancel_type: m.cancel_type,
+                    time_in_force: m.time_in_force,
+                    timestamp: m.order_timestamp,
+                },
+            },
+            EventMessage::Position(m) => PrivateEvent::Position {
+                msg_type: m.msg_type,
+                position: m.position,
+            },
+            EventMessage::PositionSummary(m) => PrivateEvent::PositionSummary {
+                msg_type: m.msg_type,
+                summary: m.summary,
+            },
+        }
+    }
+}
+
+/// Private WebSocket API。
+/// アクセストークンは有効期限が切れる前にバックグラウンドのタスクで自動で延長する。
+/// イベントを受け取っていない間も延長するので、`next_event`を長い間呼び出さなくてもアクセストークンは失効しない。
+/// 接続が切れた場合は、次にイベントを受け取るときに再接続して購読中のチャンネルを購読し直す。
+pub struct PrivateWebSocketAPI<'a, T: HttpClient + std::marker::Sync + std::marker::Send> {
+    private_api: &'a PrivateAPI<T>,
+    url: String,
+    token: Arc<Mutex<TokenState>>,
+    token_extension: AbortHandle,
+    connection: Option<Connection>,
+    channels: Vec<PrivateChannel>,
+}
+
+/// アクセストークンと延長の状態。バックグラウンドのタスクと共有する。
+struct TokenState {
+    /// 現在のアクセストークン。
+    token: String,
+
+    /// 最後にアクセストークンを取得または延長した時刻。
+    extended_at: Instant,
+
+    /// バックグラウンドでの延長に失敗したときのエラー。次の`next_event`で返す。
+    extension_error: Option<Error>,
+}
+
+/// アクセストークンを`interval`ごとに延長し続けるタスクを起動する。返り値で止められる。
+fn spawn_token_extension<T>(
+    private_api: PrivateAPI<T>,
+    state: Arc<Mutex<TokenState>>,
+    interval: Duration,
+) -> AbortHandle
+where
+    T: HttpClient + Clone + std::marker::Sync + std::marker::Send + 'static,
+{
+    let (handle, registration) = AbortHandle::new_pair();
+    let task = async move {
+        let mut due = Instant::now() + interval;
+        loop {
+            delay_until(due).await;
+            let token = state.lock().unwrap().token.clone();
+            let result = private_api.extend_ws_auth(&token).await;
+            let mut state = state.lock().unwrap();
+            due = match result {
+                Ok(_) => {
+                    // 延長中に再接続でアクセストークンが作り直された場合は、新しいアクセストークンの時刻を残す。
+                    if state.token == token {
+                        state.extended_at = Instant::now();
+                    }
+                    Instant::now() + interval
+                }
+                Err(e) => {
+                    state.extension_error = Some(e);
+                    Instant::now() + interval.min(TOKEN_EXTENSION_RETRY_INTERVAL)
+                }
+            };
+        }
+    };
+    tokio::spawn(Abortable::new(task, registration));
+    handle
+}
+
+impl<'a, T> PrivateWebSocketAPI<'a, T>
+where
+    T: HttpClient + Clone + std::marker::Sync + std::marker::Send + 'static,
+{
+    /// `private_api`に設定されたPrivate WebSocket APIに接続する。アクセストークンは`private_api`を使って取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `private_api` - アクセストークンの取得、延長、削除に使うPrivate API。
+    ///
+    pub async fn connect(
+        private_api: &'a PrivateAPI<T>,
+    ) -> Result<PrivateWebSocketAPI<'a, T>, Error> {
+        let url = format!(
+            "{}{}",
+            private_api.config().private_web_socket_endpoint(),
+            PRIVATE_WEB_SOCKET_API_PATH
+        );
+        PrivateWebSocketAPI::connect_with_url(private_api, &url).await
+    }
+
+    /// 指定したURLのWebSocketサーバーに接続する。接続先は`url`の後ろにアクセストークンを付けたものになる。
+    ///
+    /// # Arguments
+    ///
+    /// * `private_api` - アクセストークンの取得、延長、削除に使うPrivate API。
+    /// * `url` - 接続先のURL。
+    ///
+    pub async fn connect_with_url(
+        private_api: &'a PrivateAPI<T>,
+        url: &str,
+    ) -> Result<PrivateWebSocketAPI<'a, T>, Error> {
+        let token = private_api.create_ws_auth().await?.token().to_string();
+        let connection = connect(&build_url(url, &token)).await?;
+        let state = Arc::new(Mutex::new(TokenState {
+            token,
+            extended_at: Instant::now(),
+            extension_error: None,
+        }));
+        let token_extension = spawn_token_extension(
+            private_api.clone(),
+            state.clone(),
+            DEFAULT_TOKEN_EXTENSION_INTERVAL,
+        );
+        Ok(PrivateWebSocketAPI {
+            private_api,
+            url: url.to_string(),
+            token: state,
+            token_extension,
+            connection: Some(connection),
+            channels: Vec::new(),
+        })
+    }
+
+    /// アクセストークンを延長する間隔を設定する。デフォルトは50分。
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - 延長する間隔。有効期限の60分より短くする。
+    ///
+    pub fn set_token_extension_interval(&mut self, interval: Duration) {
+        self.token_extension.abort();
+        self.token_extension =
+            spawn_token_extension(self.private_api.clone(), self.token.clone(), interval);
+    }
+
+    /// 現在のアクセストークンを取得する。
+    pub fn token(&self) -> String {
+        self.token.lock().unwrap().token.clone()
+    }
+
+    /// チャンネルの購読を開始する。
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - チャンネル。
+    ///
+    pub async fn subscribe(&mut self, channel: &PrivateChannel) -> Result<(), Error> {
+        if let Some(connection) = self.connection.as_mut() {
+            send_json(connection, &build_command(SUBSCRIBE_COMMAND, channel)).await?;
+        }
+        if !self.channels.contains(channel) {
+            self.channels.push(*channel);
+        }
+        Ok(())
+    }
+
+    /// チャンネルの購読を解除する。
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - チャンネル。
+    ///
+    pub async fn unsubscribe(&mut self, channel: &PrivateChannel) -> Result<(), Error> {
+        if let Some(connection) = self.connection.as_mut() {
+            send_json(connection, &build_command(UNSUBSCRIBE_COMMAND, channel)).await?;
+        }
+        self.channels.retain(|c| c != channel);
+        Ok(())
+    }
+
+    /// 次のイベントを受け取る。
+    /// バックグラウンドでのアクセストークンの延長に失敗していた場合はそのエラーを返す。
+    /// 接続が切れていた場合は再接続して購読し直す。
+    pub async fn next_event(&mut self) -> Result<PrivateEvent, Error> {
+        loop {
+            if let Some(e) = self.token.lock().unwrap().extension_error.take() {
+                return Err(e);
+            }
+            if self.connection.is_none() {
+                self.reconnect().await?;
+            }
+            let received = match self.connection.as_mut() {
+                Some(connection) => connection.next().await,
+                None => continue,
+            };
+
+            match received {
+                Some(Ok(Message::Text(text))) => {
+                    let message = parse_from_web_socket_message::<EventMessage>(&text)?;
+                    return Ok(message.into());
+                }
+                Some(Ok(Message::Close(_))) | None => {
+                    self.connection = None;
+                }
+                Some(Err(e)) => {
+                    self.connection = None;
+                    return Err(e.into());
+                }
+                // Ping, Pongなどはtungsteniteが処理するので読み飛ばす。
+                Some(Ok(_)) => {}
+            }
+        }
+    }
+
+    /// イベントを受け取り続ける`Stream`に変換する。
+    pub fn into_stream(self) -> impl Stream<Item = Result<PrivateEvent, Error>> + 'a {
+        stream::unfold(self, |mut api| async move {
+            let event = api.next_event().await;
+            Some((event, api))
+        })
+    }
+
+    /// 接続を閉じてアクセストークンを削除する。
+    pub async fn close(mut self) -> Result<(), Error> {
+        self.token_extension.abort();
+        if let Some(mut connection) = self.connection.take() {
+            connection.close(None).await?;
+        }
+        self.private_api.revoke_ws_auth(&self.token()).await?;
+        Ok(())
+    }
+
+    async fn reconnect(&mut self) -> Result<(), Error> {
+        let expired = self.token.lock().unwrap().extended_at.elapsed() >= TOKEN_LIFETIME;
+        if expired {
+            let token = self.private_api.create_ws_auth().await?.token().to_string();
+            let mut state = self.token.lock().unwrap();
+            state.token = token;
+            state.extended_at = Instant::now();
+        }
+
+        let mut connection = connect(&build_url(&self.url, &self.token())).await?;
+        for (i, channel) in self.channels.iter().enumerate() {
+            if i > 0 {
+                delay_for(RESUBSCRIBE_INTERVAL).await;
+            }
+            send_json(&mut connection, &build_command(SUBSCRIBE_COMMAND, channel)).await?;
+        }
+        self.connection = Some(connection);
+        Ok(())
+    }
+}
+
+/// 破棄されたらアクセストークンの延長を止める。
+impl<'a, T: HttpClient + std::marker::Sync + std::marker::Send> Drop
+    for PrivateWebSocketAPI<'a, T>
+{
+    fn drop(&mut self) {
+        self.token_extension.abort();
+    }
+}
+
+fn build_url(url: &str, token: &str) -> String {
+    format!("{}/{}", url, token)
+}
+
+fn build_command(command: &str, channel: &PrivateChannel) -> Value {
+    json!({
+        "command": command,
+        "channel": channel.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::tests::fake_credentials;
+    use crate::headers::Headers;
+    use crate::http_client::tests::InmemClient;
+    use crate::price::price;
+    use crate::response::RawResponse;
+    use crate::size::size;
+    use crate::web_socket::tests::InmemServer;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const WS_AUTH_RESPONSE_SAMPLE: &str = r#"
+    {
+        "status": 0,
+        "data": "xxxxxxxxxxxxxxxxxxxx",
+        "responsetime": "2019-03-19T02:15:06.102Z"
+    }
+    "#;
+
+    const EXECUTION_EVENT_SAMPLE: &str = r#"{
+        "channel":"executionEvents",
+        "orderId":123456789,
+        "executionId":72123911,
+        "symbol":"BTC",
+        "settleType":"OPEN",
+        "executionType":"LIMIT",
+        "side":"BUY",
+        "executionPrice":"877404",
+        "executionSize":"0.5",
+        "positionId":123456789,
+        "orderTimestamp":"2019-03-19T02:15:06.081Z",
+        "executionTimestamp":"2019-03-19T02:15:06.081Z",
+        "lossGain":"0",
+        "fee":"323",
+        "orderPrice":"877200",
+        "orderSize":"0.8",
+        "orderExecutedSize":"0.5",
+        "timeInForce":"FAS",
+        "msgType":"ER"
+    }"#;
+
+    const ORDER_EVENT_SAMPLE: &str = r#"{
+        "channel":"orderEvents",
+        "orderId":123456789,
+        "symbol":"BTC",
+        "settleType":"OPEN",
+        "executionType":"LIMIT",
+        "side":"BUY",
+        "orderStatus":"ORDERED",
+        "orderTimestamp":"2019-03-19T02:15:06.081Z",
+        "orderPrice":"876045",
+        "orderSize":"0.8",
+        "orderExecutedSize":"0",
+        "losscutPrice":"0",
+        "timeInForce":"FAS",
+        "msgType":"NOR"
+    }"#;
+
+    const POSITION_EVENT_SAMPLE: &str = r#"{
+        "channel":"positionEvents",
+        "positionId":1234567,
+        "symbol":"BTC_JPY",
+        "side":"BUY",
+        "size":"0.22",
+        "orderdSize":"0",
+        "price":"876045",
+        "lossGain":"14",
+        "leverage":"4",
+        "losscutPrice":"766540",
+        "timestamp":"2019-03-19T02:15:06.094Z",
+        "msgType":"OPR"
+    }"#;
+
+    const POSITION_SUMMARY_EVENT_SAMPLE: &str = r#"{
+        "channel":"positionSummaryEvents",
+        "symbol":"BTC_JPY",
+        "side":"BUY",
+        "averagePositionRate":"715656",
+        "positionLossGain":"250675",
+        "sumOrderQuantity":"2",
+        "sumPositionQuantity":"11.6999",
+        "timestamp":"2019-03-19T02:15:06.102Z",
+        "msgType":"PERIODIC"
+    }"#;
+
+    fn private_api() -> PrivateAPI<InmemClient> {
+        let http_client = InmemClient {
+            http_status_code: 200,
+            body_text: WS_AUTH_RESPONSE_SAMPLE.to_string(),
+            return_error: false,
+        };
+        PrivateAPI::new(http_client).with_credentials(fake_credentials())
+    }
+
+    #[tokio::test]
+    async fn test_subscribe() {
+        let replies = vec![
+            EXECUTION_EVENT_SAMPLE.to_string(),
+            ORDER_EVENT_SAMPLE.to_string(),
+            POSITION_EVENT_SAMPLE.to_string(),
+            POSITION_SUMMARY_EVENT_SAMPLE.to_string(),
+        ];
+        let mut server = InmemServer::start(replies).await;
+        let private_api = private_api();
+        let mut api = PrivateWebSocketAPI::connect_with_url(&private_api, &server.url)
+            .await
+            .unwrap();
+        assert_eq!(api.token(), "xxxxxxxxxxxxxxxxxxxx");
+
+        api.subscribe(&PrivateChannel::ExecutionEvents)
+            .await
+            .unwrap();
+        let command: Value = serde_json::from_str(&server.received.recv().await.unwrap()).unwrap();
+        assert_eq!(command["command"], "subscribe");
+        assert_eq!(command["channel"], "executionEvents");
+        match api.next_event().await.unwrap() {
+            PrivateEvent::Execution {
+                msg_type,
+                execution,
+            } => {
+                assert_eq!(msg_type, "ER");
+                assert_eq!(execution.order_id, "123456789");
+                assert_eq!(execution.execution_id, "72123911");
+                assert_eq!(execution.price, price("877404"));
+                assert_eq!(execution.size, size("0.5"));
+                assert_eq!(execution.fee, 323);
+            }
+            _ => panic!("約定情報通知以外のイベントを受け取った"),
+        }
+
+        api.subscribe(&PrivateChannel::OrderEvents).await.unwrap();
+        match api.next_event().await.unwrap() {
+            PrivateEvent::Order { msg_type, order } => {
+                assert_eq!(msg_type, "NOR");
+                assert_eq!(order.order_id, "123456789");
+                assert_eq!(order.root_order_id, "123456789");
+                assert_eq!(order.status, OrderStatus::Ordered);
+                assert_eq!(order.cancel_type, "NONE");
+                assert_eq!(order.price, price("876045"));
+                assert_eq!(order.size, size("0.8"));
+            }
+            _ => panic!("注文情報通知以外のイベントを受け取った"),
+        }
+
+        api.subscribe(&PrivateChannel::PositionEvents)
+            .await
+            .unwrap();
+        match api.next_event().await.unwrap() {
+            PrivateEvent::Position { msg_type, position } => {
+                assert_eq!(msg_type, "OPR");
+                assert_eq!(position.position_id, "1234567");
+                assert_eq!(position.size, size("0.22"));
+                assert_eq!(position.losscut_price, price("766540"));
+            }
+            _ => panic!("建玉情報通知以外のイベントを受け取った"),
+        }
+
+        api.subscribe(&PrivateChannel::PositionSummaryEvents)
+            .await
+            .unwrap();
+        match api.next_event().await.unwrap() {
+            PrivateEvent::PositionSummary { msg_type, summary } => {
+                assert_eq!(msg_type, "PERIODIC");
+                assert_eq!(summary.average_position_rate, price("715656"));
+                assert_eq!(summary.sum_position_quantity, size("11.6999"));
+            }
+            _ => panic!("建玉サマリー情報通知以外のイベントを受け取った"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unsubscribe() {
+        let mut server = InmemServer::start(vec![]).await;
+        let private_api = private_api();
+        let mut api = PrivateWebSocketAPI::connect_with_url(&private_api, &server.url)
+            .await
+            .unwrap();
+        api.subscribe(&PrivateChannel::OrderEvents).await.unwrap();
+        api.unsubscribe(&PrivateChannel::OrderEvents).await.unwrap();
+        let _ = server.received.recv().await.unwrap();
+        let command: Value = serde_json::from_str(&server.received.recv().await.unwrap()).unwrap();
+        assert_eq!(command["command"], "unsubscribe");
+        assert_eq!(command["channel"], "orderEvents");
+        assert!(api.channels.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resubscribe_after_reconnect() {
+        let connections = vec![
+            vec![
+                EXECUTION_EVENT_SAMPLE.to_string(),
+                POSITION_EVENT_SAMPLE.to_string(),
+            ],
+            vec![ORDER_EVENT_SAMPLE.to_string()],
+        ];
+        let mut server = InmemServer::start_with_connections(connections).await;
+        let private_api = private_api();
+        let mut api = PrivateWebSocketAPI::connect_with_url(&private_api, &server.url)
+            .await
+            .unwrap();
+        api.subscribe(&PrivateChannel::ExecutionEvents)
+            .await
+            .unwrap();
+        api.subscribe(&PrivateChannel::PositionEvents)
+            .await
+            .unwrap();
+
+        let mut events = Box::pin(api.into_stream());
+        match events.next().await.unwrap().unwrap() {
+            PrivateEvent::Execution { .. } => {}
+            _ => panic!("約定情報通知以外のイベントを受け取った"),
+        }
+        match events.next().await.unwrap().unwrap() {
+            PrivateEvent::Position { .. } => {}
+            _ => panic!("建玉情報通知以外のイベントを受け取った"),
+        }
+        // サーバーから接続が切られたので、再接続して購読し直してからイベントを受け取る。
+        match events.next().await.unwrap().unwrap() {
+            PrivateEvent::Order { .. } => {}
+            _ => panic!("注文情報通知以外のイベントを受け取った"),
+        }
+
+        let mut commands = Vec::new();
+        for _ in 0..4 {
+            let command: Value =
+                serde_json::from_str(&server.received.recv().await.unwrap()).unwrap();
+            commands.push(format!("{} {}", command["command"], command["channel"]));
+        }
+        assert_eq!(
+            commands,
+            vec![
+                r#""subscribe" "executionEvents""#,
+                r#""subscribe" "positionEvents""#,
+                r#""subscribe" "executionEvents""#,
+                r#""subscribe" "positionEvents""#,
+            ]
+        );
+    }
+
+    /// アクセストークンの延長回数を数える単体テスト用のHttpクライアント。
+    #[derive(Clone)]
+    struct CountingClient {
+        put_count: Arc<AtomicUsize>,
+    }
+
+    impl CountingClient {
+        fn response() -> Result<RawResponse, Error> {
+            Ok(RawResponse {
+                http_status_code: 200,
+                body_text: WS_AUTH_RESPONSE_SAMPLE.to_string(),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl HttpClient for CountingClient {
+        async fn get(&self, _url: String, _headers: &Headers) -> Result<RawResponse, Error> {
+            CountingClient::response()
+        }
+
+        async fn post(
+            &self,
+            _url: String,
+            _headers: &Headers,
+            _parameters: &Value,
+        ) -> Result<RawResponse, Error> {
+            CountingClient::response()
+        }
+
+        async fn put(
+            &self,
+            _url: String,
+            _headers: &Headers,
+            _parameters: &Value,
+        ) -> Result<RawResponse, Error> {
+            self.put_count.fetch_add(1, Ordering::SeqCst);
+            CountingClient::response()
+        }
+
+        async fn delete(
+            &self,
+            _url: String,
+            _headers: &Headers,
+            _parameters: &Value,
+        ) -> Result<RawResponse, Error> {
+            CountingClient::response()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_token_extension() {
+        let server = InmemServer::start(vec![]).await;
+        let private_api = PrivateAPI::new(CountingClient {
+            put_count: Arc::new(AtomicUsize::new(0)),
+        })
+        .with_credentials(fake_credentials());
+        let mut api = PrivateWebSocketAPI::connect_with_url(&private_api, &server.url)
+            .await
+            .unwrap();
+        api.set_token_extension_interval(Duration::from_millis(10));
+
+        // イベントは配信されないので、待っている間にアクセストークンが延長される。
+        let result = tokio::time::timeout(Duration::from_millis(100), api.next_event()).await;
+        assert!(result.is_err());
+        assert!(private_api.http_client.put_count.load(Ordering::SeqCst) >= 2);
+    }
+
+    #[tokio::test]
+    async fn test_token_extension_without_polling() {
+        let server = InmemServer::start(vec![]).await;
+        let private_api = PrivateAPI::new(CountingClient {
+            put_count: Arc::new(AtomicUsize::new(0)),
+        })
+        .with_credentials(fake_credentials());
+        let mut api = PrivateWebSocketAPI::connect_with_url(&private_api, &server.url)
+            .await
+            .unwrap();
+        api.set_token_extension_interval(Duration::from_millis(10));
+
+        // `next_event`を呼び出していなくてもアクセストークンが延長される。
+        delay_for(Duration::from_millis(100)).await;
+        let count = private_api.http_client.put_count.load(Ordering::SeqCst);
+        assert!(count >= 2);
+
+        // 破棄したら延長は止まる。
+        drop(api);
+        let count = private_api.http_client.put_count.load(Ordering::SeqCst);
+        delay_for(Duration::from_millis(50)).await;
+        assert_eq!(
+            private_api.http_client.put_count.load(Ordering::SeqCst),
+            count
+        );
+    }
+}
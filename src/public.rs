@@ -1,32 +1,86 @@
 //! Public APIを実装する。
 
+pub mod klines;
 pub mod orderbooks;
 pub mod status;
+pub mod symbols;
 pub mod ticker;
 pub mod trades;
 
-use crate::dto::{DEFAULT_COUNT, DEFAULT_PAGE};
+use crate::client_config::ClientConfig;
+use crate::dto::{self, DEFAULT_COUNT, DEFAULT_PAGE};
 use crate::error::Error;
 use crate::http_client::HttpClient;
+use crate::kline_interval::KlineInterval;
+use crate::pagination::{paginate, PageOptions};
+use crate::public::klines::{request_klines, request_klines_range, Kline, Klines};
 use crate::public::orderbooks::{request_orderbooks, Orderbooks};
 use crate::public::status::{request_status, Status};
+use crate::public::symbols::{request_symbols, Symbols};
 use crate::public::ticker::{request_ticker, Ticker};
 use crate::public::trades::{request_trades, Trades};
 use crate::response::RestResponse;
 use crate::symbol::Symbol;
+use chrono::NaiveDate;
+use futures::{Stream, TryStreamExt};
+use std::sync::Arc;
 
+/// Public API。
+#[derive(Clone)]
 pub struct PublicAPI<T: HttpClient + std::marker::Sync + std::marker::Send> {
     pub http_client: T,
+
+    /// 接続先のURLやタイムアウトの設定。複製しても共有する。
+    config: Arc<ClientConfig>,
 }
 
 impl<T: HttpClient + std::marker::Sync + std::marker::Send> PublicAPI<T> {
+    /// Public APIを作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `http_client` - HTTPクライアント。
+    ///
+    pub fn new(http_client: T) -> PublicAPI<T> {
+        PublicAPI {
+            http_client,
+            config: Arc::new(ClientConfig::default()),
+        }
+    }
+
+    /// 接続先のURLやタイムアウトを指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - クライアントの設定。
+    ///
+    pub fn with_config(mut self, config: ClientConfig) -> PublicAPI<T> {
+        self.config = Arc::new(config);
+        self
+    }
+
+    /// 接続先のURLやタイムアウトの設定を取得する。
+    pub fn config(&self) -> &ClientConfig {
+        &self.config
+    }
+
     /// 取引所ステータスAPIを呼び出す。
     ///
     /// # Arguments
     ///
     ///
     pub async fn status(&self) -> Result<RestResponse<Status>, Error> {
-        let response = request_status(&self.http_client).await?;
+        let response = request_status(&self.http_client, &self.config).await?;
+        Ok(response)
+    }
+
+    /// 取引ルールAPIを呼び出す。
+    ///
+    /// # Arguments
+    ///
+    ///
+    pub async fn symbols(&self) -> Result<RestResponse<Symbols>, Error> {
+        let response = request_symbols(&self.http_client, &self.config).await?;
         Ok(response)
     }
 
@@ -37,7 +91,7 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PublicAPI<T> {
     /// * `symbol` - 銘柄
     ///
     pub async fn ticker(&self, symbol: &Symbol) -> Result<RestResponse<Ticker>, Error> {
-        let response = request_ticker(&self.http_client, &symbol).await?;
+        let response = request_ticker(&self.http_client, &self.config, symbol).await?;
         Ok(response)
     }
 
@@ -48,7 +102,7 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PublicAPI<T> {
     /// * `symbol` - 銘柄
     ///
     pub async fn orderbooks(&self, symbol: &Symbol) -> Result<RestResponse<Orderbooks>, Error> {
-        let response = request_orderbooks(&self.http_client, &symbol).await?;
+        let response = request_orderbooks(&self.http_client, &self.config, symbol).await?;
         Ok(response)
     }
 
@@ -59,8 +113,14 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PublicAPI<T> {
     /// * `symbol` - 銘柄
     ///
     pub async fn trades(&self, symbol: &Symbol) -> Result<RestResponse<Trades>, Error> {
-        let response =
-            request_trades(&self.http_client, &symbol, DEFAULT_PAGE, DEFAULT_COUNT).await?;
+        let response = request_trades(
+            &self.http_client,
+            &self.config,
+            symbol,
+            DEFAULT_PAGE,
+            DEFAULT_COUNT,
+        )
+        .await?;
         Ok(response)
     }
 
@@ -78,7 +138,82 @@ impl<T: HttpClient + std::marker::Sync + std::marker::Send> PublicAPI<T> {
         page: i32,
         count: i32,
     ) -> Result<RestResponse<Trades>, Error> {
-        let response = request_trades(&self.http_client, &symbol, page, count).await?;
+        let response = request_trades(&self.http_client, &self.config, symbol, page, count).await?;
         Ok(response)
     }
+
+    /// 取引履歴APIのページを自動でめくり、約定を1件ずつ返すストリームを作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄
+    /// * `options` - 1ページ当たりの取得件数、返す件数の上限、時刻の範囲。
+    ///
+    pub fn trades_stream<'a>(
+        &'a self,
+        symbol: &'a Symbol,
+        options: PageOptions,
+    ) -> impl Stream<Item = Result<dto::Trade, Error>> + 'a {
+        paginate(options, move |page, count| async move {
+            let response = self.trades_with_options(symbol, page, count).await?;
+            Ok(response.body.data.list)
+        })
+    }
+
+    /// 取引履歴APIのページを自動でめくり、約定をすべて取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄
+    /// * `options` - 1ページ当たりの取得件数、返す件数の上限、時刻の範囲。
+    ///
+    pub async fn trades_all(
+        &self,
+        symbol: &Symbol,
+        options: PageOptions,
+    ) -> Result<Vec<dto::Trade>, Error> {
+        self.trades_stream(symbol, options).try_collect().await
+    }
+
+    /// KLine情報の取得APIを呼び出す。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄
+    /// * `interval` - 時間足
+    /// * `date` - 取得する日付。4時間足以上の場合は年だけが使われる。
+    ///
+    pub async fn klines(
+        &self,
+        symbol: &Symbol,
+        interval: &KlineInterval,
+        date: &NaiveDate,
+    ) -> Result<RestResponse<Klines>, Error> {
+        let response =
+            request_klines(&self.http_client, &self.config, symbol, interval, date).await?;
+        Ok(response)
+    }
+
+    /// 期間を指定してKLine情報の取得APIを呼び出す。日付(4時間足以上の場合は年)ごとにAPIを呼び出し、ローソク足を古い順につなげて返す。
+    /// 4時間足以上の場合も、開始時刻(UTC)の日付が`from`から`to`の間のローソク足だけを返す。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄
+    /// * `interval` - 時間足
+    /// * `from` - 取得を開始する日付
+    /// * `to` - 取得を終了する日付。この日付も含む。
+    ///
+    pub async fn klines_range(
+        &self,
+        symbol: &Symbol,
+        interval: &KlineInterval,
+        from: &NaiveDate,
+        to: &NaiveDate,
+    ) -> Result<Vec<Kline>, Error> {
+        let klines =
+            request_klines_range(&self.http_client, &self.config, symbol, interval, from, to)
+                .await?;
+        Ok(klines)
+    }
 }
@@ -0,0 +1,264 @@
+//! KLine情報の取得APIを実装する。
+
+use crate::client_config::ClientConfig;
+use crate::dto::get_vector_default_value;
+use crate::error::Error;
+use crate::headers::Headers;
+use crate::http_client::*;
+use crate::json::*;
+use crate::kline_interval::KlineInterval;
+use crate::price::Price;
+use crate::response::*;
+use crate::symbol::*;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::Deserialize;
+
+/// KLine情報の取得APIのパス。
+const KLINES_API_PATH: &str = "/v1/klines";
+
+/// ローソク足1本分の情報(open_time, open, high, low, close, volume)を格納する構造体。
+#[derive(Deserialize)]
+pub struct Kline {
+    /// 開始時刻。
+    #[serde(
+        deserialize_with = "unix_millis_to_chrono_timestamp",
+        rename = "openTime"
+    )]
+    pub open_time: DateTime<Utc>,
+
+    /// 始値。
+    #[serde(deserialize_with = "str_to_price")]
+    pub open: Price,
+
+    /// 高値。
+    #[serde(deserialize_with = "str_to_price")]
+    pub high: Price,
+
+    /// 安値。
+    #[serde(deserialize_with = "str_to_price")]
+    pub low: Price,
+
+    /// 終値。
+    #[serde(deserialize_with = "str_to_price")]
+    pub close: Price,
+
+    /// 取引量。
+    #[serde(deserialize_with = "str_to_f64")]
+    pub volume: f64,
+}
+
+/// KLine情報の取得APIから返ってくるレスポンスを格納する構造体。
+#[derive(Deserialize)]
+pub struct Klines {
+    /// ステータスコード。
+    pub status: i16,
+
+    /// APIが呼び出された時間。
+    #[serde(deserialize_with = "gmo_timestamp_to_chrono_timestamp")]
+    pub responsetime: DateTime<Utc>,
+
+    /// レスポンスの`data`の部分。
+    #[serde(default = "get_vector_default_value::<Kline>")]
+    pub data: Vec<Kline>,
+}
+
+impl RestResponse<Klines> {
+    /// ローソク足の配列を取得する。
+    pub fn klines(&self) -> &Vec<Kline> {
+        &self.body.data
+    }
+}
+
+/// 時間足に応じてAPIに渡す日付の文字列を作る。
+/// 1時間足以下はYYYYMMDD、4時間足以上はYYYYの形式になる。
+fn format_date(interval: &KlineInterval, date: &NaiveDate) -> String {
+    if interval.is_daily() {
+        date.format("%Y%m%d").to_string()
+    } else {
+        date.format("%Y").to_string()
+    }
+}
+
+/// KLine情報の取得APIを呼び出す。
+pub async fn request_klines(
+    http_client: &impl HttpClient,
+    config: &ClientConfig,
+    symbol: &Symbol,
+    interval: &KlineInterval,
+    date: &NaiveDate,
+) -> Result<RestResponse<Klines>, Error> {
+    let url = format!(
+        "{}{}?symbol={}&interval={}&date={}",
+        config.public_endpoint(),
+        KLINES_API_PATH,
+        symbol.to_string(),
+        interval.to_string(),
+        format_date(interval, date),
+    );
+    let headers = Headers::create_empty_headers();
+    let response = with_timeout(config, http_client.get(url, &headers)).await?;
+    parse_from_http_response::<Klines>(&response, KLINES_API_PATH, config)
+}
+
+/// 期間内の日付(4時間足以上の場合は年)ごとにKLine情報の取得APIを呼び出し、ローソク足を古い順につなげて返す。
+/// `from`, `to`の日付を含む。4時間足以上はAPIが1年分を返すので、開始時刻(UTC)の日付が期間内のものだけを返す。
+pub async fn request_klines_range(
+    http_client: &impl HttpClient,
+    config: &ClientConfig,
+    symbol: &Symbol,
+    interval: &KlineInterval,
+    from: &NaiveDate,
+    to: &NaiveDate,
+) -> Result<Vec<Kline>, Error> {
+    let mut klines = Vec::<Kline>::new();
+    let mut date = *from;
+    while date <= *to {
+        let response = request_klines(http_client, config, symbol, interval, &date).await?;
+        klines.extend(response.body.data);
+        let next_date = if interval.is_daily() {
+            date.checked_add_signed(Duration::days(1))
+        } else {
+            NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)
+        };
+        date = match next_date {
+            Some(d) => d,
+            None => break,
+        };
+    }
+    if !interval.is_daily() {
+        klines.retain(|k| {
+            let open_date = k.open_time.date_naive();
+            *from <= open_date && open_date <= *to
+        });
+    }
+    klines.sort_by_key(|k| k.open_time);
+    Ok(klines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_client::tests::InmemClient;
+    use crate::price::price;
+    use crate::symbol::Symbol;
+    use chrono::SecondsFormat;
+
+    const KLINES_RESPONSE_SAMPLE: &str = r#"
+    {
+        "status": 0,
+        "data": [
+          {
+            "openTime":"1618588800000",
+            "open":"6418255",
+            "high":"6518250",
+            "low":"6318250",
+            "close":"6418253",
+            "volume":"0.0001"
+          },
+          {
+            "openTime":"1618588860000",
+            "open":"6418251",
+            "high":"6418252",
+            "low":"6415250",
+            "close":"6418245",
+            "volume":"0.0001"
+          }
+        ],
+        "responsetime": "2019-03-28T09:28:07.980Z"
+    }
+    "#;
+
+    #[tokio::test]
+    async fn test_klines() {
+        let body = KLINES_RESPONSE_SAMPLE;
+        let http_client = InmemClient {
+            http_status_code: 200,
+            body_text: body.to_string(),
+            return_error: false,
+        };
+        let resp = request_klines(
+            &http_client,
+            &ClientConfig::default(),
+            &Symbol::Btc,
+            &KlineInterval::Min1,
+            &NaiveDate::from_ymd_opt(2021, 4, 17).unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.http_status_code, 200);
+        assert_eq!(resp.body.status, 0);
+        assert_eq!(
+            resp.body
+                .responsetime
+                .to_rfc3339_opts(SecondsFormat::Millis, true),
+            "2019-03-28T09:28:07.980Z"
+        );
+        let klines = resp.klines();
+        assert_eq!(klines.len(), 2);
+        assert_eq!(
+            klines[0]
+                .open_time
+                .to_rfc3339_opts(SecondsFormat::Millis, true),
+            "2021-04-16T16:00:00.000Z"
+        );
+        assert_eq!(klines[0].open, price("6418255"));
+        assert_eq!(klines[0].high, price("6518250"));
+        assert_eq!(klines[0].low, price("6318250"));
+        assert_eq!(klines[0].close, price("6418253"));
+        assert_eq!(klines[0].volume, 0.0001);
+    }
+
+    #[tokio::test]
+    async fn test_klines_range() {
+        let body = KLINES_RESPONSE_SAMPLE;
+        let http_client = InmemClient {
+            http_status_code: 200,
+            body_text: body.to_string(),
+            return_error: false,
+        };
+        let klines = request_klines_range(
+            &http_client,
+            &ClientConfig::default(),
+            &Symbol::Btc,
+            &KlineInterval::Min1,
+            &NaiveDate::from_ymd_opt(2021, 4, 17).unwrap(),
+            &NaiveDate::from_ymd_opt(2021, 4, 19).unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(klines.len(), 6);
+        assert!(klines.windows(2).all(|w| w[0].open_time <= w[1].open_time));
+
+        let klines = request_klines_range(
+            &http_client,
+            &ClientConfig::default(),
+            &Symbol::Btc,
+            &KlineInterval::Day1,
+            &NaiveDate::from_ymd_opt(2020, 12, 31).unwrap(),
+            &NaiveDate::from_ymd_opt(2021, 4, 16).unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(klines.len(), 4);
+
+        // 4時間足以上は1年分が返ってくるので、期間外のローソク足を除く。
+        let klines = request_klines_range(
+            &http_client,
+            &ClientConfig::default(),
+            &Symbol::Btc,
+            &KlineInterval::Day1,
+            &NaiveDate::from_ymd_opt(2021, 4, 17).unwrap(),
+            &NaiveDate::from_ymd_opt(2021, 12, 31).unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(klines.len(), 0);
+    }
+
+    #[test]
+    fn test_format_date() {
+        let date = NaiveDate::from_ymd_opt(2021, 4, 7).unwrap();
+        assert_eq!(format_date(&KlineInterval::Hour1, &date), "20210407");
+        assert_eq!(format_date(&KlineInterval::Hour4, &date), "2021");
+    }
+}
@@ -1,11 +1,13 @@
 //! 板情報APIを実装する。
 
-use crate::end_point::*;
+use crate::client_config::ClientConfig;
 use crate::error::Error;
 use crate::headers::Headers;
 use crate::http_client::*;
 use crate::json::*;
+use crate::price::Price;
 use crate::response::*;
+use crate::size::Size;
 use crate::symbol::*;
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
@@ -17,12 +19,12 @@ const ORDERBOOKS_API_PATH: &str = "/v1/orderbooks";
 #[derive(Deserialize)]
 pub struct PriceAndSize {
     /// 価格。
-    #[serde(deserialize_with = "str_to_i64")]
-    pub price: i64,
+    #[serde(deserialize_with = "str_to_price")]
+    pub price: Price,
 
     /// 注文数量。
-    #[serde(deserialize_with = "str_to_f64")]
-    pub size: f64,
+    #[serde(deserialize_with = "str_to_size")]
+    pub size: Size,
 }
 
 /// 板情報APIから返ってくるレスポンスのうち`data`の部分を格納する構造体。
@@ -72,17 +74,18 @@ impl RestResponse<Orderbooks> {
 /// 板情報APIを呼び出す。
 pub async fn request_orderbooks(
     http_client: &impl HttpClient,
+    config: &ClientConfig,
     symbol: &Symbol,
 ) -> Result<RestResponse<Orderbooks>, Error> {
     let url = format!(
         "{}{}?symbol={}",
-        PUBLIC_ENDPOINT,
+        config.public_endpoint(),
         ORDERBOOKS_API_PATH,
         symbol.to_string(),
     );
     let headers = Headers::create_empty_headers();
-    let response = http_client.get(url, &headers).await?;
-    parse_from_http_response::<Orderbooks>(&response)
+    let response = with_timeout(config, http_client.get(url, &headers)).await?;
+    parse_from_http_response::<Orderbooks>(&response, ORDERBOOKS_API_PATH, config)
 }
 
 #[cfg(test)]
@@ -124,7 +127,7 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_orderbooks(&http_client, &Symbol::Btc)
+        let resp = request_orderbooks(&http_client, &ClientConfig::default(), &Symbol::Btc)
             .await
             .unwrap();
         assert_eq!(resp.http_status_code, 200);
@@ -1,6 +1,6 @@
 //! 取引所ステータスAPIを実装する。
 
-use crate::end_point::*;
+use crate::client_config::ClientConfig;
 use crate::error::Error;
 use crate::headers::Headers;
 use crate::http_client::*;
@@ -67,15 +67,19 @@ impl RestResponse<Status> {
 }
 
 /// 取引所ステータスAPIを呼び出す。
-pub async fn request_status(http_client: &impl HttpClient) -> Result<RestResponse<Status>, Error> {
-    let url = format!("{}{}", PUBLIC_ENDPOINT, STATUS_API_PATH,);
+pub async fn request_status(
+    http_client: &impl HttpClient,
+    config: &ClientConfig,
+) -> Result<RestResponse<Status>, Error> {
+    let url = format!("{}{}", config.public_endpoint(), STATUS_API_PATH,);
     let headers = Headers::create_empty_headers();
-    let response = http_client.get(url, &headers).await?;
-    parse_from_http_response::<Status>(&response)
+    let response = with_timeout(config, http_client.get(url, &headers)).await?;
+    parse_from_http_response::<Status>(&response, STATUS_API_PATH, config)
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::api_error_code::ApiErrorCode;
     use crate::http_client::tests::InmemClient;
     use crate::public::status::*;
     use chrono::SecondsFormat;
@@ -96,7 +100,9 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_status(&http_client).await.unwrap();
+        let resp = request_status(&http_client, &ClientConfig::default())
+            .await
+            .unwrap();
         assert_eq!(resp.http_status_code, 200);
         assert_eq!(resp.body.status, 0);
         assert_eq!(
@@ -106,7 +112,7 @@ mod tests {
             "2019-03-19T02:15:06.001Z"
         );
         assert_eq!(resp.status(), "OPEN");
-        assert_eq!(resp.is_open(), true);
+        assert!(resp.is_open());
     }
 
     #[tokio::test]
@@ -117,8 +123,78 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_status(&http_client).await;
-        assert_eq!(resp.is_err(), true);
+        let resp = request_status(&http_client, &ClientConfig::default()).await;
+        assert!(resp.is_err());
+        match resp {
+            Err(Error::ResponseParseError {
+                http_status_code,
+                body_text,
+                path,
+                ..
+            }) => {
+                assert_eq!(http_status_code, 200);
+                assert_eq!(body_text, body);
+                assert_eq!(path, STATUS_API_PATH);
+            }
+            _ => panic!("ResponseParseErrorが返ってこなかった"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_status_with_collect_unknown_fields() {
+        let body = r#"{
+            "status": 0,
+            "data": {
+              "status": "OPEN",
+              "reason": "new field"
+            },
+            "responsetime": "2019-03-19T02:15:06.001Z"
+          }"#;
+        let http_client = InmemClient {
+            http_status_code: 200,
+            body_text: body.to_string(),
+            return_error: false,
+        };
+        let resp = request_status(&http_client, &ClientConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(resp.unknown_fields.len(), 0);
+
+        let config = ClientConfig::default().with_collect_unknown_fields(true);
+        let resp = request_status(&http_client, &config).await.unwrap();
+        assert_eq!(resp.status(), "OPEN");
+        assert_eq!(resp.unknown_fields.len(), 1);
+        assert_eq!(resp.unknown_fields["/data/reason"], "new field");
+    }
+
+    #[tokio::test]
+    async fn test_status_when_api_error_returned() {
+        let body = r#"{
+            "status": 5,
+            "messages": [
+              {
+                "message_code": "ERR-5201",
+                "message_string": "MAINTENANCE. Please wait for a while"
+              }
+            ],
+            "responsetime": "2019-03-19T02:15:06.001Z"
+          }"#;
+        let http_client = InmemClient {
+            http_status_code: 200,
+            body_text: body.to_string(),
+            return_error: false,
+        };
+        let err = match request_status(&http_client, &ClientConfig::default()).await {
+            Err(e) => e,
+            Ok(_) => panic!("エラーレスポンスを受け取れなかった"),
+        };
+        assert_eq!(err.api_error_code(), Some(ApiErrorCode::Maintenance.into()));
+        assert!(err
+            .api_error_code()
+            .unwrap()
+            .known()
+            .unwrap()
+            .is_maintenance());
     }
 
     #[tokio::test]
@@ -129,7 +205,7 @@ mod tests {
             body_text: body.to_string(),
             return_error: true,
         };
-        let resp = request_status(&http_client).await;
-        assert_eq!(resp.is_err(), true);
+        let resp = request_status(&http_client, &ClientConfig::default()).await;
+        assert!(resp.is_err());
     }
 }
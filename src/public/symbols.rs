@@ -0,0 +1,147 @@
+//! 取引ルールAPIを実装する。
+
+use crate::client_config::ClientConfig;
+use crate::dto::get_vector_default_value;
+use crate::error::Error;
+use crate::headers::Headers;
+use crate::http_client::*;
+use crate::json::*;
+use crate::price::Price;
+use crate::response::*;
+use crate::size::Size;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+/// 取引ルールAPIのパス。
+const SYMBOLS_API_PATH: &str = "/v1/symbols";
+
+/// 銘柄ごとの取引ルールを格納する構造体。
+#[derive(Deserialize, Clone)]
+pub struct SymbolRule {
+    /// 銘柄名。
+    pub symbol: String,
+
+    /// 最小注文数量/回。
+    #[serde(deserialize_with = "str_to_size", rename = "minOrderSize")]
+    pub min_order_size: Size,
+
+    /// 最大注文数量/回。
+    #[serde(deserialize_with = "str_to_size", rename = "maxOrderSize")]
+    pub max_order_size: Size,
+
+    /// 最小注文単位/回。注文数量はこの値の倍数でなければならない。
+    #[serde(deserialize_with = "str_to_size", rename = "sizeStep")]
+    pub size_step: Size,
+
+    /// 注文価格の呼値。注文価格はこの値の倍数でなければならない。
+    #[serde(deserialize_with = "str_to_price", rename = "tickSize")]
+    pub tick_size: Price,
+
+    /// Taker手数料。
+    #[serde(deserialize_with = "str_to_f64", rename = "takerFee")]
+    pub taker_fee: f64,
+
+    /// Maker手数料。
+    #[serde(deserialize_with = "str_to_f64", rename = "makerFee")]
+    pub maker_fee: f64,
+}
+
+/// 取引ルールAPIから返ってくるレスポンスを格納する構造体。
+#[derive(Deserialize)]
+pub struct Symbols {
+    /// ステータスコード。
+    pub status: i16,
+
+    /// APIが呼び出された時間。
+    #[serde(deserialize_with = "gmo_timestamp_to_chrono_timestamp")]
+    pub responsetime: DateTime<Utc>,
+
+    /// レスポンスの`data`の部分。
+    #[serde(default = "get_vector_default_value::<SymbolRule>")]
+    pub data: Vec<SymbolRule>,
+}
+
+impl RestResponse<Symbols> {
+    /// 取引ルールの配列を取得する。
+    pub fn symbols(&self) -> &Vec<SymbolRule> {
+        &self.body.data
+    }
+}
+
+/// 取引ルールAPIを呼び出す。
+pub async fn request_symbols(
+    http_client: &impl HttpClient,
+    config: &ClientConfig,
+) -> Result<RestResponse<Symbols>, Error> {
+    let url = format!("{}{}", config.public_endpoint(), SYMBOLS_API_PATH);
+    let headers = Headers::create_empty_headers();
+    let response = with_timeout(config, http_client.get(url, &headers)).await?;
+    parse_from_http_response::<Symbols>(&response, SYMBOLS_API_PATH, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_client::tests::InmemClient;
+    use crate::price::price;
+    use crate::size::size;
+    use chrono::SecondsFormat;
+
+    const SYMBOLS_RESPONSE_SAMPLE: &str = r#"
+    {
+        "status": 0,
+        "data": [
+          {
+            "symbol": "BTC",
+            "minOrderSize": "0.0001",
+            "maxOrderSize": "5",
+            "sizeStep": "0.0001",
+            "tickSize": "1",
+            "takerFee": "0.0005",
+            "makerFee": "-0.0001"
+          },
+          {
+            "symbol": "BTC_JPY",
+            "minOrderSize": "0.01",
+            "maxOrderSize": "5",
+            "sizeStep": "0.01",
+            "tickSize": "1",
+            "takerFee": "0",
+            "makerFee": "0"
+          }
+        ],
+        "responsetime": "2022-12-15T19:22:23.792Z"
+    }
+    "#;
+
+    #[tokio::test]
+    async fn test_symbols() {
+        let body = SYMBOLS_RESPONSE_SAMPLE;
+        let http_client = InmemClient {
+            http_status_code: 200,
+            body_text: body.to_string(),
+            return_error: false,
+        };
+        let resp = request_symbols(&http_client, &ClientConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(resp.http_status_code, 200);
+        assert_eq!(resp.body.status, 0);
+        assert_eq!(
+            resp.body
+                .responsetime
+                .to_rfc3339_opts(SecondsFormat::Millis, true),
+            "2022-12-15T19:22:23.792Z"
+        );
+        let symbols = resp.symbols();
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[0].symbol, "BTC");
+        assert_eq!(symbols[0].min_order_size, size("0.0001"));
+        assert_eq!(symbols[0].max_order_size, size("5.0"));
+        assert_eq!(symbols[0].size_step, size("0.0001"));
+        assert_eq!(symbols[0].tick_size, price("1"));
+        assert_eq!(symbols[0].taker_fee, 0.0005);
+        assert_eq!(symbols[0].maker_fee, -0.0001);
+        assert_eq!(symbols[1].symbol, "BTC_JPY");
+    }
+}
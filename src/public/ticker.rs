@@ -1,10 +1,11 @@
 //! 最新レートAPIを実装する。
 
-use crate::end_point::*;
+use crate::client_config::ClientConfig;
 use crate::error::Error;
 use crate::headers::Headers;
 use crate::http_client::*;
 use crate::json::*;
+use crate::price::Price;
 use crate::response::*;
 use crate::symbol::*;
 use chrono::{DateTime, Utc};
@@ -17,24 +18,24 @@ const TICKER_API_PATH: &str = "/v1/ticker";
 #[derive(Deserialize)]
 pub struct Data {
     /// ASK。
-    #[serde(deserialize_with = "str_to_i64")]
-    pub ask: i64,
+    #[serde(deserialize_with = "str_to_price")]
+    pub ask: Price,
 
     /// BID。
-    #[serde(deserialize_with = "str_to_i64")]
-    pub bid: i64,
+    #[serde(deserialize_with = "str_to_price")]
+    pub bid: Price,
 
     /// 高値。
-    #[serde(deserialize_with = "str_to_i64")]
-    pub high: i64,
+    #[serde(deserialize_with = "str_to_price")]
+    pub high: Price,
 
     /// 終値。
-    #[serde(deserialize_with = "str_to_i64")]
-    pub last: i64,
+    #[serde(deserialize_with = "str_to_price")]
+    pub last: Price,
 
     /// 安値。
-    #[serde(deserialize_with = "str_to_i64")]
-    pub low: i64,
+    #[serde(deserialize_with = "str_to_price")]
+    pub low: Price,
 
     /// 銘柄名。
     pub symbol: String,
@@ -64,50 +65,50 @@ pub struct Ticker {
 
 impl RestResponse<Ticker> {
     /// ASKを取得する。
-    pub fn ask(&self) -> Result<i64, Error> {
-        let d = self.body.data.get(0).ok_or(Error::EmptyResponseError {})?;
+    pub fn ask(&self) -> Result<Price, Error> {
+        let d = self.body.data.first().ok_or(Error::EmptyResponseError {})?;
         Ok(d.ask)
     }
 
     /// BIDを取得する。
-    pub fn bid(&self) -> Result<i64, Error> {
-        let d = self.body.data.get(0).ok_or(Error::EmptyResponseError {})?;
+    pub fn bid(&self) -> Result<Price, Error> {
+        let d = self.body.data.first().ok_or(Error::EmptyResponseError {})?;
         Ok(d.bid)
     }
 
     /// 高値を取得する。
-    pub fn high(&self) -> Result<i64, Error> {
-        let d = self.body.data.get(0).ok_or(Error::EmptyResponseError {})?;
+    pub fn high(&self) -> Result<Price, Error> {
+        let d = self.body.data.first().ok_or(Error::EmptyResponseError {})?;
         Ok(d.high)
     }
 
     /// 終値を取得する。
-    pub fn last(&self) -> Result<i64, Error> {
-        let d = self.body.data.get(0).ok_or(Error::EmptyResponseError {})?;
+    pub fn last(&self) -> Result<Price, Error> {
+        let d = self.body.data.first().ok_or(Error::EmptyResponseError {})?;
         Ok(d.last)
     }
 
     /// 安値を取得する。
-    pub fn low(&self) -> Result<i64, Error> {
-        let d = self.body.data.get(0).ok_or(Error::EmptyResponseError {})?;
+    pub fn low(&self) -> Result<Price, Error> {
+        let d = self.body.data.first().ok_or(Error::EmptyResponseError {})?;
         Ok(d.low)
     }
 
     /// 銘柄名を取得する。
     pub fn symbol(&self) -> Result<&String, Error> {
-        let d = self.body.data.get(0).ok_or(Error::EmptyResponseError {})?;
+        let d = self.body.data.first().ok_or(Error::EmptyResponseError {})?;
         Ok(&d.symbol)
     }
 
     /// 時刻を取得する。
     pub fn timestamp(&self) -> Result<&DateTime<Utc>, Error> {
-        let d = self.body.data.get(0).ok_or(Error::EmptyResponseError {})?;
+        let d = self.body.data.first().ok_or(Error::EmptyResponseError {})?;
         Ok(&d.timestamp)
     }
 
     /// 取引量を取得する。
     pub fn volume(&self) -> Result<f64, Error> {
-        let d = self.body.data.get(0).ok_or(Error::EmptyResponseError {})?;
+        let d = self.body.data.first().ok_or(Error::EmptyResponseError {})?;
         Ok(d.volume)
     }
 }
@@ -115,22 +116,24 @@ impl RestResponse<Ticker> {
 /// 最新レートAPIを呼び出す。
 pub async fn request_ticker(
     http_client: &impl HttpClient,
+    config: &ClientConfig,
     symbol: &Symbol,
 ) -> Result<RestResponse<Ticker>, Error> {
     let url = format!(
         "{}{}?symbol={}",
-        PUBLIC_ENDPOINT,
+        config.public_endpoint(),
         TICKER_API_PATH,
         symbol.to_string(),
     );
     let headers = Headers::create_empty_headers();
-    let response = http_client.get(url, &headers).await?;
-    parse_from_http_response::<Ticker>(&response)
+    let response = with_timeout(config, http_client.get(url, &headers)).await?;
+    parse_from_http_response::<Ticker>(&response, TICKER_API_PATH, config)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::http_client::tests::InmemClient;
+    use crate::price::price;
     use crate::public::ticker::*;
     use crate::symbol::Symbol;
     use chrono::SecondsFormat;
@@ -160,7 +163,9 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_ticker(&http_client, &Symbol::Btc).await.unwrap();
+        let resp = request_ticker(&http_client, &ClientConfig::default(), &Symbol::Btc)
+            .await
+            .unwrap();
         assert_eq!(resp.http_status_code, 200);
         assert_eq!(resp.body.status, 0);
         assert_eq!(
@@ -169,11 +174,11 @@ mod tests {
                 .to_rfc3339_opts(SecondsFormat::Millis, true),
             "2019-03-19T02:15:06.014Z"
         );
-        assert_eq!(resp.ask().unwrap(), 750760);
-        assert_eq!(resp.bid().unwrap(), 750600);
-        assert_eq!(resp.high().unwrap(), 762302);
-        assert_eq!(resp.last().unwrap(), 756662);
-        assert_eq!(resp.low().unwrap(), 704874);
+        assert_eq!(resp.ask().unwrap(), price("750760"));
+        assert_eq!(resp.bid().unwrap(), price("750600"));
+        assert_eq!(resp.high().unwrap(), price("762302"));
+        assert_eq!(resp.last().unwrap(), price("756662"));
+        assert_eq!(resp.low().unwrap(), price("704874"));
         assert_eq!(resp.symbol().unwrap(), "BTC");
         assert_eq!(
             resp.timestamp()
@@ -183,4 +188,22 @@ mod tests {
         );
         assert_eq!(resp.volume().unwrap(), 194785.8484);
     }
+
+    #[tokio::test]
+    async fn test_ticker_with_fractional_prices() {
+        let body = TICKER_RESPONSE_SAMPLE
+            .replace("750760", "52.123")
+            .replace("750600", "52.101")
+            .replace("\"BTC\"", "\"XRP_JPY\"");
+        let http_client = InmemClient {
+            http_status_code: 200,
+            body_text: body,
+            return_error: false,
+        };
+        let resp = request_ticker(&http_client, &ClientConfig::default(), &Symbol::XrpJpy)
+            .await
+            .unwrap();
+        assert_eq!(resp.ask().unwrap(), price("52.123"));
+        assert_eq!(resp.bid().unwrap(), price("52.101"));
+    }
 }
@@ -1,7 +1,7 @@
 //! 取引履歴APIを実装する。
 
+use crate::client_config::ClientConfig;
 use crate::dto::{Pagination, Trade};
-use crate::end_point::*;
 use crate::error::Error;
 use crate::headers::Headers;
 use crate::http_client::*;
@@ -58,21 +58,22 @@ impl RestResponse<Trades> {
 /// 取引履歴APIを呼び出す。
 pub async fn request_trades(
     http_client: &impl HttpClient,
+    config: &ClientConfig,
     symbol: &Symbol,
     page: i32,
     count: i32,
 ) -> Result<RestResponse<Trades>, Error> {
     let url = format!(
         "{}{}?symbol={}&page={}&count={}",
-        PUBLIC_ENDPOINT,
+        config.public_endpoint(),
         TRADES_API_PATH,
         symbol.to_string(),
         page,
         count,
     );
     let headers = Headers::create_empty_headers();
-    let response = http_client.get(url, &headers).await?;
-    parse_from_http_response::<Trades>(&response)
+    let response = with_timeout(config, http_client.get(url, &headers)).await?;
+    parse_from_http_response::<Trades>(&response, TRADES_API_PATH, config)
 }
 
 #[cfg(test)]
@@ -117,7 +118,7 @@ mod tests {
             body_text: body.to_string(),
             return_error: false,
         };
-        let resp = request_trades(&http_client, &Symbol::Btc, 1, 100)
+        let resp = request_trades(&http_client, &ClientConfig::default(), &Symbol::Btc, 1, 100)
             .await
             .unwrap();
         assert_eq!(resp.http_status_code, 200);
@@ -0,0 +1,337 @@
+//! Public WebSocket APIを実装する。
+
+use crate::client_config::ClientConfig;
+use crate::dto::Trade;
+use crate::error::Error;
+use crate::json::*;
+use crate::public::orderbooks::PriceAndSize;
+use crate::public::ticker;
+use crate::symbol::Symbol;
+use crate::web_socket::*;
+use chrono::{DateTime, Utc};
+use futures::stream::Stream;
+use futures::task::{Context, Poll};
+use serde::Deserialize;
+use serde_json::json;
+use std::pin::Pin;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Public WebSocket APIのパス。
+const PUBLIC_WEB_SOCKET_API_PATH: &str = "/v1";
+
+/// 購読できるチャンネルの候補。
+pub enum Channel {
+    /// 最新レート。
+    Ticker,
+
+    /// 板情報。
+    Orderbooks,
+
+    /// 取引履歴。
+    Trades,
+}
+
+/// 最新レートのチャンネル名。
+pub const TICKER_CHANNEL: &str = "ticker";
+
+/// 板情報のチャンネル名。
+pub const ORDERBOOKS_CHANNEL: &str = "orderbooks";
+
+/// 取引履歴のチャンネル名。
+pub const TRADES_CHANNEL: &str = "trades";
+
+impl Channel {
+    /// チャンネルを文字列に変換する。
+    pub fn to_string(&self) -> &str {
+        match self {
+            Channel::Ticker => TICKER_CHANNEL,
+            Channel::Orderbooks => ORDERBOOKS_CHANNEL,
+            Channel::Trades => TRADES_CHANNEL,
+        }
+    }
+}
+
+/// 板情報チャンネルから配信されるメッセージを格納する構造体。
+#[derive(Deserialize)]
+pub struct Orderbooks {
+    /// 売り注文の情報。
+    pub asks: Vec<PriceAndSize>,
+
+    /// 買い注文の情報。
+    pub bids: Vec<PriceAndSize>,
+
+    /// 銘柄名。
+    pub symbol: String,
+
+    /// 時刻。
+    #[serde(deserialize_with = "gmo_timestamp_to_chrono_timestamp")]
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 取引履歴チャンネルから配信されるメッセージを格納する構造体。
+#[derive(Deserialize)]
+pub struct Trades {
+    /// 取引データ。
+    #[serde(flatten)]
+    pub trade: Trade,
+
+    /// 銘柄名。
+    pub symbol: String,
+}
+
+/// Public WebSocket APIから配信されるメッセージ。
+#[derive(Deserialize)]
+#[serde(tag = "channel")]
+pub enum PublicMessage {
+    /// 最新レート。
+    #[serde(rename = "ticker")]
+    Ticker(ticker::Data),
+
+    /// 板情報。
+    #[serde(rename = "orderbooks")]
+    Orderbooks(Orderbooks),
+
+    /// 取引履歴。
+    #[serde(rename = "trades")]
+    Trades(Trades),
+}
+
+/// Public WebSocket API。
+/// `futures::stream::Stream`を実装しているので、購読したチャンネルのメッセージは`next()`などで受け取る。
+pub struct PublicWebSocketAPI {
+    connection: Connection,
+}
+
+impl PublicWebSocketAPI {
+    /// GMOコインのPublic WebSocket APIに接続する。
+    pub async fn connect() -> Result<PublicWebSocketAPI, Error> {
+        PublicWebSocketAPI::connect_with_config(&ClientConfig::default()).await
+    }
+
+    /// `config`に設定されたPublic WebSocket APIに接続する。
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - クライアントの設定。
+    ///
+    pub async fn connect_with_config(config: &ClientConfig) -> Result<PublicWebSocketAPI, Error> {
+        let url = format!(
+            "{}{}",
+            config.public_web_socket_endpoint(),
+            PUBLIC_WEB_SOCKET_API_PATH
+        );
+        PublicWebSocketAPI::connect_with_url(&url).await
+    }
+
+    /// 指定したURLのWebSocketサーバーに接続する。
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - 接続先のURL。
+    ///
+    pub async fn connect_with_url(url: &str) -> Result<PublicWebSocketAPI, Error> {
+        let connection = connect(url).await?;
+        Ok(PublicWebSocketAPI { connection })
+    }
+
+    /// チャンネルの購読を開始する。
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - チャンネル。
+    /// * `symbol` - 銘柄。
+    ///
+    pub async fn subscribe(&mut self, channel: &Channel, symbol: &Symbol) -> Result<(), Error> {
+        let message = build_command(SUBSCRIBE_COMMAND, channel, symbol);
+        send_json(&mut self.connection, &message).await
+    }
+
+    /// チャンネルの購読を解除する。
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - チャンネル。
+    /// * `symbol` - 銘柄。
+    ///
+    pub async fn unsubscribe(&mut self, channel: &Channel, symbol: &Symbol) -> Result<(), Error> {
+        let message = build_command(UNSUBSCRIBE_COMMAND, channel, symbol);
+        send_json(&mut self.connection, &message).await
+    }
+}
+
+fn build_command(command: &str, channel: &Channel, symbol: &Symbol) -> serde_json::Value {
+    json!({
+        "command": command,
+        "channel": channel.to_string(),
+        "symbol": symbol.to_string(),
+    })
+}
+
+impl Stream for PublicWebSocketAPI {
+    type Item = Result<PublicMessage, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let message = match Pin::new(&mut self.connection).poll_next(cx) {
+                Poll::Ready(Some(Ok(m))) => m,
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e.into()))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            };
+            match message {
+                Message::Text(text) => {
+                    return Poll::Ready(Some(parse_from_web_socket_message::<PublicMessage>(&text)))
+                }
+                Message::Close(_) => return Poll::Ready(None),
+                // Ping, Pongなどはtungsteniteが処理するので読み飛ばす。
+                _ => continue,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price::price;
+    use crate::side::Side;
+    use crate::size::size;
+    use crate::web_socket::tests::InmemServer;
+    use chrono::SecondsFormat;
+    use futures::StreamExt;
+
+    const TICKER_MESSAGE_SAMPLE: &str = r#"{
+        "channel":"ticker",
+        "ask": "750760",
+        "bid": "750600",
+        "high": "762302",
+        "last": "756662",
+        "low": "704874",
+        "symbol": "BTC",
+        "timestamp": "2018-03-30T12:34:56.789Z",
+        "volume": "194785.8484"
+    }"#;
+
+    const ORDERBOOKS_MESSAGE_SAMPLE: &str = r#"{
+        "channel":"orderbooks",
+        "asks": [
+            {"price": "455659","size": "0.1"},
+            {"price": "455658","size": "0.2"}
+        ],
+        "bids": [
+            {"price": "455665","size": "0.1"}
+        ],
+        "symbol": "BTC",
+        "timestamp": "2018-03-30T12:34:56.789Z"
+    }"#;
+
+    const TRADES_MESSAGE_SAMPLE: &str = r#"{
+        "channel":"trades",
+        "price": "750760",
+        "side": "BUY",
+        "size": "0.1",
+        "timestamp": "2018-03-30T12:34:56.789Z",
+        "symbol": "BTC"
+    }"#;
+
+    const ERROR_MESSAGE_SAMPLE: &str = r#"{"error":"ERR-5003 Request too many."}"#;
+
+    #[tokio::test]
+    async fn test_subscribe() {
+        let replies = vec![
+            TICKER_MESSAGE_SAMPLE.to_string(),
+            ORDERBOOKS_MESSAGE_SAMPLE.to_string(),
+            TRADES_MESSAGE_SAMPLE.to_string(),
+        ];
+        let mut server = InmemServer::start(replies).await;
+        let mut api = PublicWebSocketAPI::connect_with_url(&server.url)
+            .await
+            .unwrap();
+
+        api.subscribe(&Channel::Ticker, &Symbol::Btc).await.unwrap();
+        let command: serde_json::Value =
+            serde_json::from_str(&server.received.recv().await.unwrap()).unwrap();
+        assert_eq!(command["command"], "subscribe");
+        assert_eq!(command["channel"], "ticker");
+        assert_eq!(command["symbol"], "BTC");
+        match api.next().await.unwrap().unwrap() {
+            PublicMessage::Ticker(d) => {
+                assert_eq!(d.ask, price("750760"));
+                assert_eq!(d.bid, price("750600"));
+                assert_eq!(d.symbol, "BTC");
+                assert_eq!(d.volume, 194785.8484);
+            }
+            _ => panic!("ticker以外のメッセージを受け取った"),
+        }
+
+        api.subscribe(&Channel::Orderbooks, &Symbol::Btc)
+            .await
+            .unwrap();
+        match api.next().await.unwrap().unwrap() {
+            PublicMessage::Orderbooks(d) => {
+                assert_eq!(d.asks.len(), 2);
+                assert_eq!(d.bids.len(), 1);
+                assert_eq!(d.bids[0].price, price("455665"));
+                assert_eq!(
+                    d.timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+                    "2018-03-30T12:34:56.789Z"
+                );
+            }
+            _ => panic!("orderbooks以外のメッセージを受け取った"),
+        }
+
+        api.subscribe(&Channel::Trades, &Symbol::Btc).await.unwrap();
+        match api.next().await.unwrap().unwrap() {
+            PublicMessage::Trades(d) => {
+                assert_eq!(d.trade.price, price("750760"));
+                assert_eq!(d.trade.side, Side::Buy);
+                assert_eq!(d.trade.size, size("0.1"));
+                assert_eq!(d.symbol, "BTC");
+            }
+            _ => panic!("trades以外のメッセージを受け取った"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unsubscribe() {
+        let mut server = InmemServer::start(vec![]).await;
+        let mut api = PublicWebSocketAPI::connect_with_url(&server.url)
+            .await
+            .unwrap();
+        api.unsubscribe(&Channel::Orderbooks, &Symbol::EthJpy)
+            .await
+            .unwrap();
+        let command: serde_json::Value =
+            serde_json::from_str(&server.received.recv().await.unwrap()).unwrap();
+        assert_eq!(command["command"], "unsubscribe");
+        assert_eq!(command["channel"], "orderbooks");
+        assert_eq!(command["symbol"], "ETH_JPY");
+    }
+
+    #[tokio::test]
+    async fn test_connect_with_config() {
+        let mut server = InmemServer::start(vec![]).await;
+        let config = ClientConfig::default().with_public_web_socket_endpoint(&server.url);
+        let mut api = PublicWebSocketAPI::connect_with_config(&config)
+            .await
+            .unwrap();
+        api.subscribe(&Channel::Trades, &Symbol::Btc).await.unwrap();
+        let command: serde_json::Value =
+            serde_json::from_str(&server.received.recv().await.unwrap()).unwrap();
+        assert_eq!(command["command"], "subscribe");
+        assert_eq!(command["channel"], "trades");
+    }
+
+    #[tokio::test]
+    async fn test_error_message() {
+        let server = InmemServer::start(vec![ERROR_MESSAGE_SAMPLE.to_string()]).await;
+        let mut api = PublicWebSocketAPI::connect_with_url(&server.url)
+            .await
+            .unwrap();
+        api.subscribe(&Channel::Ticker, &Symbol::Btc).await.unwrap();
+        match api.next().await.unwrap() {
+            Err(Error::WebSocketAPIError(m)) => assert_eq!(m, "ERR-5003 Request too many."),
+            _ => panic!("エラーメッセージを受け取れなかった"),
+        }
+    }
+}
@@ -0,0 +1,483 @@
+//! APIの呼び出し回数を制限するレートリミッターと、それを使うHTTPクライアントを実装する。
+
+use crate::error::Error;
+use crate::headers::Headers;
+use crate::http_client::HttpClient;
+use crate::response::RawResponse;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 1秒あたりに呼び出せる回数。
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RateLimit {
+    /// GETリクエストの回数。
+    pub get_per_second: u32,
+
+    /// POSTリクエストの回数。PUT, DELETEリクエストもこちらで数える。
+    pub post_per_second: u32,
+}
+
+impl RateLimit {
+    /// 回数を指定して作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `get_per_second` - 1秒あたりのGETリクエストの回数。1以上を指定する。
+    /// * `post_per_second` - 1秒あたりのPOST, PUT, DELETEリクエストの回数。1以上を指定する。
+    ///
+    pub fn new(get_per_second: u32, post_per_second: u32) -> RateLimit {
+        RateLimit {
+            get_per_second,
+            post_per_second,
+        }
+    }
+
+    /// Tier1の制限。GET, POSTそれぞれ1秒に20回まで。
+    pub fn tier1() -> RateLimit {
+        RateLimit::new(20, 20)
+    }
+
+    /// Tier2の制限。GET, POSTそれぞれ1秒に30回まで。
+    pub fn tier2() -> RateLimit {
+        RateLimit::new(30, 30)
+    }
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        RateLimit::tier1()
+    }
+}
+
+/// レートリミッターが使う時計。テストでは時間を進めるだけの時計に差し替える。
+#[async_trait]
+pub trait Clock: Send + Sync {
+    /// 現在時刻を返す。
+    fn now(&self) -> Instant;
+
+    /// 指定した時間だけ待つ。
+    async fn sleep(&self, duration: Duration);
+}
+
+/// tokioのタイマーを使う時計。
+pub struct TokioClock;
+
+#[async_trait]
+impl Clock for TokioClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::delay_for(duration).await;
+    }
+}
+
+/// トークンバケット。1秒あたりの回数と同じ数までトークンを貯められる。
+/// 浮動小数点の誤差が出ないように、トークンの残り数の代わりにバケットが空になる時刻で状態を持つ。
+struct TokenBucket {
+    /// トークン1つが補充されるのにかかる時間。
+    interval: Duration,
+
+    /// バケットが満杯のときに、空になるまでの時間。
+    burst: Duration,
+
+    /// 取り出したトークンがすべて補充される時刻。
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(per_second: u32, now: Instant) -> TokenBucket {
+        let per_second = per_second.max(1);
+        let interval = Duration::from_secs(1) / per_second;
+        TokenBucket {
+            interval,
+            burst: interval * per_second,
+            refilled_at: now,
+        }
+    }
+
+    /// トークンを1つ取り出す。足りない場合は取り出せるようになるまでの時間を返す。
+    fn try_take(&mut self, now: Instant) -> Result<(), Duration> {
+        let refilled_at = self.refilled_at.max(now);
+        let next = refilled_at + self.interval;
+        let wait = next.saturating_duration_since(now + self.burst);
+        if wait > Duration::from_secs(0) {
+            return Err(wait);
+        }
+        self.refilled_at = next;
+        Ok(())
+    }
+}
+
+struct Inner {
+    clock: Box<dyn Clock>,
+    get_bucket: Mutex<TokenBucket>,
+    post_bucket: Mutex<TokenBucket>,
+
+    /// APIのパス(例: `/v1/order`)ごとのトークンバケット。
+    endpoint_buckets: Mutex<Vec<(String, TokenBucket)>>,
+}
+
+/// GET, POSTで別々のトークンバケットを持つレートリミッター。
+/// `with_endpoint_limit`でAPIのパスごとの制限も追加できる。
+/// `clone()`したものは同じバケットを共有するので、`PublicAPI`と`PrivateAPI`で1つの制限を共有できる。
+#[derive(Clone)]
+pub struct RateLimiter {
+    inner: Arc<Inner>,
+}
+
+impl RateLimiter {
+    /// レートリミッターを作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - 1秒あたりに呼び出せる回数。
+    ///
+    pub fn new(limit: RateLimit) -> RateLimiter {
+        RateLimiter::with_clock(limit, TokioClock)
+    }
+
+    /// 時計を指定してレートリミッターを作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - 1秒あたりに呼び出せる回数。
+    /// * `clock` - 時計。
+    ///
+    pub fn with_clock(limit: RateLimit, clock: impl Clock + 'static) -> RateLimiter {
+        let now = clock.now();
+        RateLimiter {
+            inner: Arc::new(Inner {
+                clock: Box::new(clock),
+                get_bucket: Mutex::new(TokenBucket::new(limit.get_per_second, now)),
+                post_bucket: Mutex::new(TokenBucket::new(limit.post_per_second, now)),
+                endpoint_buckets: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// APIのパスごとの制限を追加する。GET, POSTの制限に加えて、このパスへのリクエストは1秒あたり`per_second`回までになる。
+    /// 同じパスを指定した場合は制限を置き換える。`clone()`したレートリミッターにも反映される。
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - APIのパス。例: `/v1/order`。URLのパスがこの文字列で終わるリクエストを制限する。
+    /// * `per_second` - 1秒あたりに呼び出せる回数。1以上を指定する。
+    ///
+    pub fn with_endpoint_limit(self, path: &str, per_second: u32) -> RateLimiter {
+        let bucket = TokenBucket::new(per_second, self.inner.clock.now());
+        {
+            let mut buckets = self.inner.endpoint_buckets.lock().unwrap();
+            buckets.retain(|(p, _)| p != path);
+            buckets.push((path.to_string(), bucket));
+        }
+        self
+    }
+
+    /// GETリクエストを送れるようになるまで待つ。待った場合は`true`を返す。
+    pub async fn acquire_get(&self) -> bool {
+        self.acquire(|now| self.inner.get_bucket.lock().unwrap().try_take(now))
+            .await
+    }
+
+    /// POST, PUT, DELETEリクエストを送れるようになるまで待つ。待った場合は`true`を返す。
+    pub async fn acquire_post(&self) -> bool {
+        self.acquire(|now| self.inner.post_bucket.lock().unwrap().try_take(now))
+            .await
+    }
+
+    /// `url`のパスに制限があれば、リクエストを送れるようになるまで待つ。待った場合は`true`を返す。
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - リクエスト先のURL。
+    ///
+    pub async fn acquire_endpoint(&self, url: &str) -> bool {
+        let path = url.split('?').next().unwrap_or_default();
+        self.acquire(|now| {
+            let mut buckets = self.inner.endpoint_buckets.lock().unwrap();
+            match buckets.iter_mut().find(|(p, _)| path.ends_with(p.as_str())) {
+                Some((_, bucket)) => bucket.try_take(now),
+                None => Ok(()),
+            }
+        })
+        .await
+    }
+
+    async fn acquire(&self, try_take: impl Fn(Instant) -> Result<(), Duration>) -> bool {
+        let mut waited = false;
+        loop {
+            match try_take(self.inner.clock.now()) {
+                Ok(()) => return waited,
+                Err(wait) => {
+                    self.inner.clock.sleep(wait).await;
+                    waited = true;
+                }
+            }
+        }
+    }
+}
+
+/// レートリミッターで呼び出し回数を制限するHTTPクライアント。任意の`HttpClient`を包んで使う。
+#[derive(Clone)]
+pub struct RateLimitedClient<T: HttpClient + std::marker::Sync + std::marker::Send> {
+    inner: T,
+    limiter: RateLimiter,
+}
+
+impl<T: HttpClient + std::marker::Sync + std::marker::Send> RateLimitedClient<T> {
+    /// レートリミッターを使うHTTPクライアントを作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - 実際にリクエストを送るHTTPクライアント。
+    /// * `limiter` - レートリミッター。
+    ///
+    pub fn new(inner: T, limiter: RateLimiter) -> RateLimitedClient<T> {
+        RateLimitedClient { inner, limiter }
+    }
+
+    /// レートリミッターを取得する。
+    pub fn limiter(&self) -> &RateLimiter {
+        &self.limiter
+    }
+
+    /// リクエストを送れるようになるまで待つ。待った場合は`true`を返す。
+    async fn acquire(&self, url: &str, is_get: bool) -> bool {
+        let waited = if is_get {
+            self.limiter.acquire_get().await
+        } else {
+            self.limiter.acquire_post().await
+        };
+        self.limiter.acquire_endpoint(url).await || waited
+    }
+}
+
+/// 待った場合は、API-TIMESTAMPとAPI-SIGNが古くなって認証に失敗しないように署名し直す。
+fn fresh_headers(headers: &Headers, waited: bool) -> Option<Headers> {
+    if waited {
+        Some(headers.resign())
+    } else {
+        None
+    }
+}
+
+#[async_trait]
+impl<T: HttpClient + std::marker::Sync + std::marker::Send> HttpClient for RateLimitedClient<T> {
+    async fn get(&self, url: String, headers: &Headers) -> Result<RawResponse, Error> {
+        let resigned = fresh_headers(headers, self.acquire(&url, true).await);
+        self.inner
+            .get(url, resigned.as_ref().unwrap_or(headers))
+            .await
+    }
+
+    async fn post(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        let resigned = fresh_headers(headers, self.acquire(&url, false).await);
+        self.inner
+            .post(url, resigned.as_ref().unwrap_or(headers), parameters)
+            .await
+    }
+
+    async fn put(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        let resigned = fresh_headers(headers, self.acquire(&url, false).await);
+        self.inner
+            .put(url, resigned.as_ref().unwrap_or(headers), parameters)
+            .await
+    }
+
+    async fn delete(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        let resigned = fresh_headers(headers, self.acquire(&url, false).await);
+        self.inner
+            .delete(url, resigned.as_ref().unwrap_or(headers), parameters)
+            .await
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::credentials::tests::fake_credentials;
+    use crate::http_client::tests::InmemClient;
+    use crate::mock::MockHttpClient;
+
+    /// 単体テスト用の時計。待つ代わりに時間を進める。
+    pub(crate) struct MockClock {
+        start: Instant,
+        elapsed: Arc<Mutex<Duration>>,
+    }
+
+    impl MockClock {
+        pub(crate) fn new() -> (MockClock, Arc<Mutex<Duration>>) {
+            let elapsed = Arc::new(Mutex::new(Duration::from_secs(0)));
+            let clock = MockClock {
+                start: Instant::now(),
+                elapsed: elapsed.clone(),
+            };
+            (clock, elapsed)
+        }
+    }
+
+    #[async_trait]
+    impl Clock for MockClock {
+        fn now(&self) -> Instant {
+            self.start + *self.elapsed.lock().unwrap()
+        }
+
+        async fn sleep(&self, duration: Duration) {
+            *self.elapsed.lock().unwrap() += duration;
+        }
+    }
+
+    pub(crate) fn elapsed_millis(elapsed: &Arc<Mutex<Duration>>) -> u128 {
+        elapsed.lock().unwrap().as_millis()
+    }
+
+    #[tokio::test]
+    async fn test_burst_then_wait() {
+        let (clock, elapsed) = MockClock::new();
+        let limiter = RateLimiter::with_clock(RateLimit::tier1(), clock);
+        for _ in 0..20 {
+            limiter.acquire_get().await;
+        }
+        assert_eq!(elapsed_millis(&elapsed), 0);
+
+        limiter.acquire_get().await;
+        assert_eq!(elapsed_millis(&elapsed), 50);
+
+        limiter.acquire_get().await;
+        assert_eq!(elapsed_millis(&elapsed), 100);
+    }
+
+    #[tokio::test]
+    async fn test_get_and_post_buckets_are_separate() {
+        let (clock, elapsed) = MockClock::new();
+        let limiter = RateLimiter::with_clock(RateLimit::new(1, 2), clock);
+        limiter.acquire_get().await;
+        limiter.acquire_post().await;
+        limiter.acquire_post().await;
+        assert_eq!(elapsed_millis(&elapsed), 0);
+
+        limiter.acquire_post().await;
+        assert_eq!(elapsed_millis(&elapsed), 500);
+
+        // GETのバケットは0.5秒分だけ補充されているので、あと0.5秒待つ。
+        limiter.acquire_get().await;
+        assert_eq!(elapsed_millis(&elapsed), 1000);
+    }
+
+    #[tokio::test]
+    async fn test_cloned_limiter_shares_buckets() {
+        let (clock, elapsed) = MockClock::new();
+        let limiter = RateLimiter::with_clock(RateLimit::new(2, 2), clock);
+        let public_client = RateLimitedClient::new(
+            InmemClient {
+                http_status_code: 200,
+                body_text: "{}".to_string(),
+                return_error: false,
+            },
+            limiter.clone(),
+        );
+        let private_client = RateLimitedClient::new(
+            InmemClient {
+                http_status_code: 200,
+                body_text: "{}".to_string(),
+                return_error: false,
+            },
+            limiter,
+        );
+        let headers = Headers::create_empty_headers();
+        public_client.get("".to_string(), &headers).await.unwrap();
+        private_client.get("".to_string(), &headers).await.unwrap();
+        assert_eq!(elapsed_millis(&elapsed), 0);
+
+        let resp = public_client.get("".to_string(), &headers).await.unwrap();
+        assert_eq!(resp.http_status_code, 200);
+        assert_eq!(elapsed_millis(&elapsed), 500);
+
+        private_client
+            .delete("".to_string(), &headers, &Value::Null)
+            .await
+            .unwrap();
+        assert_eq!(elapsed_millis(&elapsed), 500);
+    }
+
+    fn timestamp(client: &RateLimitedClient<MockHttpClient>, index: usize) -> String {
+        client.inner.calls()[index].headers["API-TIMESTAMP"].clone()
+    }
+
+    #[tokio::test]
+    async fn test_resign_after_wait() {
+        let (clock, elapsed) = MockClock::new();
+        let client = RateLimitedClient::new(
+            MockHttpClient::new().on("GET", "/v1/*", 200, "{}"),
+            RateLimiter::with_clock(RateLimit::new(1, 1), clock),
+        );
+        let url = "https://api.coin.z.com/private/v1/assets".to_string();
+        let headers = Headers::create_get_headers(&fake_credentials(), "/v1/assets").unwrap();
+        let signed_at = headers
+            .into_iter()
+            .find(|(k, _)| k.as_str() == "API-TIMESTAMP")
+            .map(|(_, v)| v.clone())
+            .unwrap();
+
+        // 待たなかったリクエストは受け取ったヘッダーをそのまま送る。
+        client.get(url.clone(), &headers).await.unwrap();
+        assert_eq!(elapsed_millis(&elapsed), 0);
+        assert_eq!(timestamp(&client, 0), signed_at);
+
+        // 待ったリクエストは待ったあとの時刻で署名し直す。
+        std::thread::sleep(Duration::from_millis(2));
+        client.get(url, &headers).await.unwrap();
+        assert_eq!(elapsed_millis(&elapsed), 1000);
+        let resigned_at: u64 = timestamp(&client, 1).parse().unwrap();
+        assert!(resigned_at > signed_at.parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_limit() {
+        let (clock, elapsed) = MockClock::new();
+        let limiter = RateLimiter::with_clock(RateLimit::new(10, 10), clock)
+            .with_endpoint_limit("/v1/order", 2);
+        let client = RateLimitedClient::new(
+            MockHttpClient::new().on("POST", "/v1/*", 200, "{}"),
+            limiter,
+        );
+        let headers = Headers::create_empty_headers();
+        let post = |path: &str| {
+            client.post(
+                format!("https://api.coin.z.com/private{}", path),
+                &headers,
+                &Value::Null,
+            )
+        };
+        post("/v1/order").await.unwrap();
+        post("/v1/order").await.unwrap();
+        post("/v1/cancelOrder").await.unwrap();
+        assert_eq!(elapsed_millis(&elapsed), 0);
+
+        post("/v1/order").await.unwrap();
+        assert_eq!(elapsed_millis(&elapsed), 500);
+
+        // パスの制限がないAPIはGET, POSTの制限だけで待つ。
+        post("/v1/cancelOrder").await.unwrap();
+        assert_eq!(elapsed_millis(&elapsed), 500);
+    }
+}
@@ -1,6 +1,10 @@
 //! APIのレスポンスを定義する。
 
+use crate::api_error_code::ApiErrorCode;
+use crate::dto::MaybeUnknown;
 use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
 
 /// HTTPクライアントから返ってくるそのままのレスポンスを持つ構造体。
 #[derive(Debug)]
@@ -17,12 +21,16 @@ pub struct RestResponse<T> {
 
     /// GMOコインからのレスポンスのボディ部分。
     pub body: T,
+
+    /// `ClientConfig::with_collect_unknown_fields`を有効にしたときに、ボディのうち構造体が知らないフィールド。
+    /// キーはJSON Pointer(例: `/data/list/0/foo`)。無効の場合は常に空。
+    pub unknown_fields: HashMap<String, Value>,
 }
 
 /// APIの呼び出しが不正なときにGMOコインから返ってくるレスポンスのうちメッセージの部分。
 #[derive(Deserialize, Debug)]
 pub struct ErrorMessage {
-    pub message_code: String,
+    pub message_code: MaybeUnknown<ApiErrorCode>,
     pub message_string: String,
 }
 
@@ -32,3 +40,22 @@ pub struct ErrorResponse {
     pub status: i16,
     pub messages: Vec<ErrorMessage>,
 }
+
+impl ErrorResponse {
+    /// 最初のメッセージのエラーコードを取得する。
+    /// ライブラリが知らないエラーコードの場合は`MaybeUnknown::Unknown`に元の文字列を格納して返す。
+    /// メッセージがない場合は`None`を返す。
+    pub fn code(&self) -> Option<MaybeUnknown<ApiErrorCode>> {
+        self.messages.first().map(|m| m.message_code.clone())
+    }
+
+    /// 指定したエラーコードのメッセージを含んでいるか？
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - エラーコード。
+    ///
+    pub fn has_code(&self, code: ApiErrorCode) -> bool {
+        self.messages.iter().any(|m| m.message_code == code)
+    }
+}
@@ -0,0 +1,550 @@
+//! 失敗したリクエストを指数バックオフで再試行するHTTPクライアントを実装する。
+
+use crate::api_error_code::ApiErrorCode;
+use crate::error::Error;
+use crate::headers::Headers;
+use crate::http_client::HttpClient;
+use crate::rate_limiter::{Clock, TokioClock};
+use crate::response::{ErrorResponse, RawResponse};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 再試行の方針。
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// 最大の再試行回数。最初の呼び出しは含まない。
+    pub max_retries: u32,
+
+    /// 1回目の再試行までに待つ時間。再試行するたびに2倍にする。
+    pub base_delay: Duration,
+
+    /// 再試行までに待つ時間の上限。
+    pub max_delay: Duration,
+
+    /// 待つ時間をランダムに短くするか？ 有効にすると待つ時間は元の半分から元の長さまでの間になる。
+    pub jitter: bool,
+
+    /// 再試行するエラーコード。これらのエラーはGMOコインがリクエストを処理せずに返すので、POSTリクエストでも再試行する。
+    pub retryable_codes: Vec<ApiErrorCode>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+            jitter: true,
+            retryable_codes: vec![
+                ApiErrorCode::TooManyRequests,
+                ApiErrorCode::Maintenance,
+                ApiErrorCode::EmergencyMaintenance,
+            ],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// `attempt`回目の試行が失敗したあと、再試行までに待つ時間を返す。
+    fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self
+            .base_delay
+            .checked_mul(1 << exponent)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+        if !self.jitter {
+            return delay;
+        }
+        delay / 2 + (delay / 2).mul_f64(random_ratio())
+    }
+
+    /// 再試行するエラーコードを含むエラーレスポンスか？
+    fn is_retryable_error_response(&self, error_response: &ErrorResponse) -> bool {
+        self.retryable_codes
+            .iter()
+            .any(|code| error_response.has_code(*code))
+    }
+}
+
+/// 0以上1未満の乱数を返す。
+fn random_ratio() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0);
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// 1回の試行の結果。`RetryClient::with_on_attempt`で登録した関数に渡す。
+pub struct Attempt<'a> {
+    /// HTTPメソッド。
+    pub method: &'static str,
+
+    /// リクエスト先のURL。
+    pub url: &'a str,
+
+    /// 何回目の試行か。最初の呼び出しが1。
+    pub attempt: u32,
+
+    /// 試行の結果。
+    pub result: &'a Result<RawResponse, Error>,
+
+    /// 再試行するまでに待つ時間。再試行しない場合は`None`。
+    pub retry_after: Option<Duration>,
+}
+
+/// 試行するたびに呼び出す関数。
+type OnAttempt = Arc<dyn Fn(&Attempt) + Send + Sync>;
+
+/// 失敗したリクエストを指数バックオフで再試行するHTTPクライアント。任意の`HttpClient`を包んで使う。
+///
+/// GETリクエストは通信エラー、HTTPステータスコード5xx、再試行するエラーコードのときに再試行する。
+/// POST, PUT, DELETEリクエストは、注文が二重に出されないように再試行するエラーコードのときだけ再試行する。
+#[derive(Clone)]
+pub struct RetryClient<T: HttpClient + std::marker::Sync + std::marker::Send> {
+    inner: T,
+    policy: RetryPolicy,
+    clock: Arc<dyn Clock>,
+    on_attempt: Option<OnAttempt>,
+}
+
+impl<T: HttpClient + std::marker::Sync + std::marker::Send> RetryClient<T> {
+    /// 再試行するHTTPクライアントを作成する。
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - 実際にリクエストを送るHTTPクライアント。
+    /// * `policy` - 再試行の方針。
+    ///
+    pub fn new(inner: T, policy: RetryPolicy) -> RetryClient<T> {
+        RetryClient {
+            inner,
+            policy,
+            clock: Arc::new(TokioClock),
+            on_attempt: None,
+        }
+    }
+
+    /// 再試行までに待つときに使う時計を指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - 時計。
+    ///
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> RetryClient<T> {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// 試行するたびに呼び出す関数を指定する。ログ出力やメトリクスの収集に使う。
+    ///
+    /// # Arguments
+    ///
+    /// * `on_attempt` - 試行の結果を受け取る関数。
+    ///
+    pub fn with_on_attempt(
+        mut self,
+        on_attempt: impl Fn(&Attempt) + Send + Sync + 'static,
+    ) -> RetryClient<T> {
+        self.on_attempt = Some(Arc::new(on_attempt));
+        self
+    }
+
+    /// 試行の結果から再試行するかを判断する。
+    fn should_retry(&self, method: &str, result: &Result<RawResponse, Error>) -> bool {
+        let is_get = method == "GET";
+        match result {
+            Ok(response) => {
+                if let Ok(error_response) =
+                    serde_json::from_str::<ErrorResponse>(&response.body_text)
+                {
+                    if self.policy.is_retryable_error_response(&error_response) {
+                        return true;
+                    }
+                }
+                is_get && response.http_status_code >= 500
+            }
+            Err(Error::ReqwestError(_)) => is_get,
+            Err(_) => false,
+        }
+    }
+
+    /// 再試行しながらリクエストを送る。
+    /// 再試行するときはヘッダーを現在のタイムスタンプで署名し直すので、待っている間に署名が古くならない。
+    async fn send<F, Fut>(
+        &self,
+        method: &'static str,
+        url: &str,
+        headers: &Headers,
+        request: F,
+    ) -> Result<RawResponse, Error>
+    where
+        F: Fn(Headers) -> Fut,
+        Fut: Future<Output = Result<RawResponse, Error>>,
+    {
+        let mut attempt = 1;
+        loop {
+            let headers = if attempt == 1 {
+                headers.clone()
+            } else {
+                headers.resign()
+            };
+            let result = request(headers).await;
+            let retry_after =
+                if attempt <= self.policy.max_retries && self.should_retry(method, &result) {
+                    Some(self.policy.delay(attempt))
+                } else {
+                    None
+                };
+            if let Some(on_attempt) = &self.on_attempt {
+                on_attempt(&Attempt {
+                    method,
+                    url,
+                    attempt,
+                    result: &result,
+                    retry_after,
+                });
+            }
+            match retry_after {
+                Some(delay) => {
+                    self.clock.sleep(delay).await;
+                    attempt += 1;
+                }
+                None => return result,
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<T: HttpClient + std::marker::Sync + std::marker::Send> HttpClient for RetryClient<T> {
+    async fn get(&self, url: String, headers: &Headers) -> Result<RawResponse, Error> {
+        self.send("GET", &url, headers, |headers| {
+            let url = url.clone();
+            async move { self.inner.get(url, &headers).await }
+        })
+        .await
+    }
+
+    async fn post(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        self.send("POST", &url, headers, |headers| {
+            let url = url.clone();
+            async move { self.inner.post(url, &headers, parameters).await }
+        })
+        .await
+    }
+
+    async fn put(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        self.send("PUT", &url, headers, |headers| {
+            let url = url.clone();
+            async move { self.inner.put(url, &headers, parameters).await }
+        })
+        .await
+    }
+
+    async fn delete(
+        &self,
+        url: String,
+        headers: &Headers,
+        parameters: &Value,
+    ) -> Result<RawResponse, Error> {
+        self.send("DELETE", &url, headers, |headers| {
+            let url = url.clone();
+            async move { self.inner.delete(url, &headers, parameters).await }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::tests::fake_credentials;
+    use crate::rate_limiter::tests::{elapsed_millis, MockClock};
+    use std::sync::{Arc, Mutex};
+
+    const RATE_LIMIT_RESPONSE_SAMPLE: &str = r#"{
+        "status": 4,
+        "messages": [
+          {
+            "message_code": "ERR-5003",
+            "message_string": "Requests are too many."
+          }
+        ],
+        "responsetime": "2019-03-19T02:15:06.059Z"
+      }"#;
+
+    const INSUFFICIENT_FUNDS_RESPONSE_SAMPLE: &str = r#"{
+        "status": 1,
+        "messages": [
+          {
+            "message_code": "ERR-201",
+            "message_string": "Insufficient funds."
+          }
+        ],
+        "responsetime": "2019-03-19T02:15:06.059Z"
+      }"#;
+
+    const SUCCESS_RESPONSE_SAMPLE: &str = r#"{
+        "status": 0,
+        "data": {},
+        "responsetime": "2019-03-19T02:15:06.059Z"
+      }"#;
+
+    /// 単体テスト用のHttpクライアント。呼び出されるたびに用意したレスポンスを順番に返す。
+    struct SequenceClient {
+        responses: Mutex<Vec<(u16, &'static str)>>,
+        transport_error: bool,
+        calls: Mutex<u32>,
+        sent_headers: Mutex<Vec<(String, String)>>,
+    }
+
+    impl SequenceClient {
+        fn new(responses: Vec<(u16, &'static str)>) -> SequenceClient {
+            SequenceClient {
+                responses: Mutex::new(responses),
+                transport_error: false,
+                calls: Mutex::new(0),
+                sent_headers: Mutex::new(Vec::new()),
+            }
+        }
+
+        async fn next(&self, headers: &Headers) -> Result<RawResponse, Error> {
+            *self.calls.lock().unwrap() += 1;
+            let header = |name: &str| {
+                headers
+                    .into_iter()
+                    .find(|(k, _)| k.as_str() == name)
+                    .map(|(_, v)| v.clone())
+                    .unwrap_or_default()
+            };
+            self.sent_headers
+                .lock()
+                .unwrap()
+                .push((header("API-TIMESTAMP"), header("API-SIGN")));
+            if self.transport_error {
+                let error = reqwest::Client::new().get("http://").send().await;
+                return Err(Error::ReqwestError(error.unwrap_err()));
+            }
+            let (http_status_code, body_text) = self.responses.lock().unwrap().remove(0);
+            Ok(RawResponse {
+                http_status_code,
+                body_text: body_text.to_string(),
+            })
+        }
+
+        fn calls(&self) -> u32 {
+            *self.calls.lock().unwrap()
+        }
+    }
+
+    #[async_trait]
+    impl HttpClient for SequenceClient {
+        async fn get(&self, _url: String, headers: &Headers) -> Result<RawResponse, Error> {
+            self.next(headers).await
+        }
+
+        async fn post(
+            &self,
+            _url: String,
+            headers: &Headers,
+            _parameters: &Value,
+        ) -> Result<RawResponse, Error> {
+            self.next(headers).await
+        }
+
+        async fn put(
+            &self,
+            _url: String,
+            headers: &Headers,
+            _parameters: &Value,
+        ) -> Result<RawResponse, Error> {
+            self.next(headers).await
+        }
+
+        async fn delete(
+            &self,
+            _url: String,
+            headers: &Headers,
+            _parameters: &Value,
+        ) -> Result<RawResponse, Error> {
+            self.next(headers).await
+        }
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            jitter: false,
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_on_rate_limit() {
+        let (clock, elapsed) = MockClock::new();
+        let attempts = Arc::new(Mutex::new(Vec::new()));
+        let recorded = attempts.clone();
+        let client = RetryClient::new(
+            SequenceClient::new(vec![
+                (200, RATE_LIMIT_RESPONSE_SAMPLE),
+                (200, RATE_LIMIT_RESPONSE_SAMPLE),
+                (200, SUCCESS_RESPONSE_SAMPLE),
+            ]),
+            policy(),
+        )
+        .with_clock(clock)
+        .with_on_attempt(move |a| {
+            recorded
+                .lock()
+                .unwrap()
+                .push((a.method, a.attempt, a.retry_after));
+        });
+        let headers = Headers::create_empty_headers();
+        let resp = client
+            .post("url".to_string(), &headers, &Value::Null)
+            .await
+            .unwrap();
+        assert_eq!(resp.body_text, SUCCESS_RESPONSE_SAMPLE);
+        assert_eq!(client.inner.calls(), 3);
+        assert_eq!(elapsed_millis(&elapsed), 600);
+        assert_eq!(
+            *attempts.lock().unwrap(),
+            vec![
+                ("POST", 1, Some(Duration::from_millis(200))),
+                ("POST", 2, Some(Duration::from_millis(400))),
+                ("POST", 3, None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resign_on_each_attempt() {
+        let client = RetryClient::new(
+            SequenceClient::new(vec![
+                (200, RATE_LIMIT_RESPONSE_SAMPLE),
+                (200, RATE_LIMIT_RESPONSE_SAMPLE),
+                (200, SUCCESS_RESPONSE_SAMPLE),
+            ]),
+            RetryPolicy {
+                base_delay: Duration::from_millis(5),
+                ..policy()
+            },
+        );
+        let parameters = serde_json::json!({"symbol": "BTC"});
+        let headers =
+            Headers::create_post_headers(&fake_credentials(), "/v1/order", &parameters).unwrap();
+        client
+            .post("url".to_string(), &headers, &parameters)
+            .await
+            .unwrap();
+
+        // 再試行するたびに新しいタイムスタンプで署名し直している。
+        let sent = client.inner.sent_headers.lock().unwrap().clone();
+        assert_eq!(sent.len(), 3);
+        for pair in sent.windows(2) {
+            let before: u64 = pair[0].0.parse().unwrap();
+            let after: u64 = pair[1].0.parse().unwrap();
+            assert!(before < after);
+            assert_ne!(pair[0].1, pair[1].1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_no_retry_on_insufficient_funds() {
+        let (clock, elapsed) = MockClock::new();
+        let client = RetryClient::new(
+            SequenceClient::new(vec![(200, INSUFFICIENT_FUNDS_RESPONSE_SAMPLE)]),
+            policy(),
+        )
+        .with_clock(clock);
+        let headers = Headers::create_empty_headers();
+        let resp = client.get("url".to_string(), &headers).await.unwrap();
+        assert_eq!(resp.body_text, INSUFFICIENT_FUNDS_RESPONSE_SAMPLE);
+        assert_eq!(client.inner.calls(), 1);
+        assert_eq!(elapsed_millis(&elapsed), 0);
+    }
+
+    #[tokio::test]
+    async fn test_retry_server_error_only_for_get() {
+        let (clock, _) = MockClock::new();
+        let client = RetryClient::new(
+            SequenceClient::new(vec![(503, ""), (200, SUCCESS_RESPONSE_SAMPLE)]),
+            policy(),
+        )
+        .with_clock(clock);
+        let headers = Headers::create_empty_headers();
+        let resp = client.get("url".to_string(), &headers).await.unwrap();
+        assert_eq!(resp.http_status_code, 200);
+        assert_eq!(client.inner.calls(), 2);
+
+        let (clock, _) = MockClock::new();
+        let client = RetryClient::new(
+            SequenceClient::new(vec![(503, ""), (200, SUCCESS_RESPONSE_SAMPLE)]),
+            policy(),
+        )
+        .with_clock(clock);
+        let resp = client
+            .post("url".to_string(), &headers, &Value::Null)
+            .await
+            .unwrap();
+        assert_eq!(resp.http_status_code, 503);
+        assert_eq!(client.inner.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_transport_error() {
+        let (clock, elapsed) = MockClock::new();
+        let mut inner = SequenceClient::new(vec![]);
+        inner.transport_error = true;
+        let client = RetryClient::new(inner, policy()).with_clock(clock);
+        let headers = Headers::create_empty_headers();
+        let resp = client.get("url".to_string(), &headers).await;
+        assert!(matches!(resp, Err(Error::ReqwestError(_))));
+        assert_eq!(client.inner.calls(), 4);
+        assert_eq!(elapsed_millis(&elapsed), 1400);
+
+        let (clock, _) = MockClock::new();
+        let mut inner = SequenceClient::new(vec![]);
+        inner.transport_error = true;
+        let client = RetryClient::new(inner, policy()).with_clock(clock);
+        let resp = client.post("url".to_string(), &headers, &Value::Null).await;
+        assert!(matches!(resp, Err(Error::ReqwestError(_))));
+        assert_eq!(client.inner.calls(), 1);
+    }
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy {
+            max_delay: Duration::from_millis(500),
+            ..policy()
+        };
+        assert_eq!(policy.delay(1), Duration::from_millis(200));
+        assert_eq!(policy.delay(2), Duration::from_millis(400));
+        assert_eq!(policy.delay(3), Duration::from_millis(500));
+        assert_eq!(policy.delay(100), Duration::from_millis(500));
+
+        let policy = RetryPolicy {
+            jitter: true,
+            ..policy
+        };
+        for attempt in 1..5 {
+            let delay = policy.delay(attempt);
+            assert!(delay >= Duration::from_millis(100));
+            assert!(delay <= Duration::from_millis(500));
+        }
+    }
+}
@@ -1,5 +1,9 @@
 //! 決済区分を定義する。
 
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
 pub enum SettleType {
     Open,
     Close,
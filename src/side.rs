@@ -1,6 +1,10 @@
 //! 売買区分を定義する。
 
+use serde::{Deserialize, Serialize};
+
 /// 売買区分。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
 pub enum Side {
     Buy,
     Sell,
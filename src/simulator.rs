@@ -0,0 +1,538 @@
+//! GMOコインのREST APIを真似る模擬取引所サーバーを実装する。`simulator`フィーチャーを有効にすると使える。
+//! 本番環境に触れずに、注文から約定、変更、取消、決済、ロスカットまでを結合テストで確かめるために使う。
+
+use crate::api_error_code::ApiErrorCode;
+use crate::client_config::ClientConfig;
+use crate::credentials::Credentials;
+use crate::error::Error;
+use crate::exchange::engine::{Engine, Quote};
+use crate::exchange::{self, query_symbol, timestamp, RouteResult};
+use crate::price::Price;
+use crate::size::Size;
+use crate::symbol::Symbol;
+use chrono::Utc;
+use hyper::header::HeaderMap;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use ring::hmac;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// 価格を1つ進める制御用APIのパス。
+const ADVANCE_PATH: &str = "/simulator/advance";
+
+/// API-TIMESTAMPとサーバーの時刻のずれの許容範囲(ミリ秒)。
+const TIMESTAMP_TOLERANCE_MILLIS: i64 = 60_000;
+
+/// 模擬取引所サーバー。`start`で起動する。
+///
+/// 価格は`with_price_path`で指定した筋書きどおりに動く。`SimulatorHandle::advance`か`POST /simulator/advance`を呼ぶか、
+/// `with_tick_interval`で指定した間隔ごとに1つ進み、そのたびに指値/逆指値注文の約定とロスカットを判定する。
+/// 指値注文は価格が1回動くあいだに`with_liquidity`で指定した数量までしか約定しないので、部分約定も再現できる。
+/// Private APIは`API-KEY`, `API-TIMESTAMP`, `API-SIGN`ヘッダーを本番と同じ方法で検証する。
+pub struct Simulator {
+    credentials: Credentials,
+    market: Market,
+    tick_interval: Option<Duration>,
+}
+
+impl Simulator {
+    /// 模擬取引所を作成する。円残高は100万円、レバレッジは2倍、指値注文が1回に約定できる数量は1。
+    ///
+    /// # Arguments
+    ///
+    /// * `credentials` - Private APIの呼び出しに使うことを認めるAPIキー, APIシークレット。
+    ///
+    pub fn new(credentials: Credentials) -> Simulator {
+        let mut engine = Engine::new(1_000_000, 2);
+        engine.set_liquidity(Some(Size::from(1)));
+        Simulator {
+            credentials,
+            market: Market {
+                engine,
+                paths: BTreeMap::new(),
+            },
+            tick_interval: None,
+        }
+    }
+
+    /// 銘柄の価格の推移を指定する。最後の価格まで進んだらそのまま動かない。
+    /// 価格の推移を指定していない銘柄は注文できない。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    /// * `prices` - 価格の推移。空の場合はパニックする。
+    ///
+    pub fn with_price_path(mut self, symbol: &Symbol, prices: Vec<Price>) -> Simulator {
+        assert!(!prices.is_empty(), "価格の推移が空");
+        self.market.add_price_path(symbol.to_string(), prices);
+        self
+    }
+
+    /// 口座の円残高を指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `balance` - 円残高。
+    ///
+    pub fn with_balance(mut self, balance: i64) -> Simulator {
+        self.market.engine.set_balance(balance);
+        self
+    }
+
+    /// レバレッジを指定する。証拠金とロスカットレートの初期値の計算に使う。
+    ///
+    /// # Arguments
+    ///
+    /// * `leverage` - レバレッジ。
+    ///
+    pub fn with_leverage(mut self, leverage: i64) -> Simulator {
+        self.market.engine.set_leverage(leverage);
+        self
+    }
+
+    /// 価格が1回動くあいだに1つの指値注文が約定できる数量を指定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `liquidity` - 約定できる数量。
+    ///
+    pub fn with_liquidity(mut self, liquidity: Size) -> Simulator {
+        self.market.engine.set_liquidity(Some(liquidity));
+        self
+    }
+
+    /// 価格を自動で進める間隔を指定する。指定しない場合は`advance`を呼んだときだけ進む。
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - 価格を1つ進める間隔。
+    ///
+    pub fn with_tick_interval(mut self, interval: Duration) -> Simulator {
+        self.tick_interval = Some(interval);
+        self
+    }
+
+    /// サーバーを起動する。`SimulatorHandle`をドロップすると停止する。
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - 待ち受けるアドレス。ポート番号に0を指定すると空いているポートを使う。
+    ///
+    pub async fn start(self, addr: SocketAddr) -> Result<SimulatorHandle, Error> {
+        let state = Arc::new(State {
+            credentials: self.credentials,
+            market: Mutex::new(self.market),
+        });
+        let service_state = state.clone();
+        let make_service = make_service_fn(move |_| {
+            let state = service_state.clone();
+            async move { Ok::<_, Infallible>(service_fn(move |request| handle(state.clone(), request))) }
+        });
+        let server = Server::try_bind(&addr)
+            .map_err(|e| Error::SimulatorServerError(Box::new(e)))?
+            .serve(make_service);
+        let local_addr = server.local_addr();
+        let (shutdown, shutdown_receiver) = oneshot::channel::<()>();
+        tokio::spawn(server.with_graceful_shutdown(async {
+            shutdown_receiver.await.ok();
+        }));
+        if let Some(interval) = self.tick_interval {
+            tokio::spawn(tick(Arc::downgrade(&state), interval));
+        }
+        Ok(SimulatorHandle {
+            local_addr,
+            state,
+            shutdown: Some(shutdown),
+        })
+    }
+}
+
+/// 起動した模擬取引所サーバー。ドロップすると停止する。
+pub struct SimulatorHandle {
+    local_addr: SocketAddr,
+    state: Arc<State>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl SimulatorHandle {
+    /// サーバーが待ち受けているアドレスを取得する。
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Public API, Private APIの接続先をこのサーバーに向けた設定を作成する。
+    pub fn config(&self) -> ClientConfig {
+        ClientConfig::default()
+            .with_public_endpoint(format!("http://{}/public", self.local_addr))
+            .with_private_endpoint(format!("http://{}/private", self.local_addr))
+    }
+
+    /// すべての銘柄の価格を1つ進め、ロスカットと注文の約定を判定する。
+    pub fn advance(&self) {
+        self.state.market.lock().unwrap().advance();
+    }
+
+    /// 銘柄の現在の価格を取得する。価格の推移を指定していない銘柄の場合は`None`を返す。
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - 銘柄。
+    ///
+    pub fn price(&self, symbol: &Symbol) -> Option<Price> {
+        self.state
+            .market
+            .lock()
+            .unwrap()
+            .paths
+            .get(symbol.to_string())
+            .map(|p| p.price())
+    }
+
+    /// 確定した損益を含む口座の円残高を取得する。
+    pub fn balance(&self) -> i64 {
+        self.state.market.lock().unwrap().engine.balance()
+    }
+}
+
+impl Drop for SimulatorHandle {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.send(()).ok();
+        }
+    }
+}
+
+/// リクエストの処理で共有する状態。
+struct State {
+    credentials: Credentials,
+    market: Mutex<Market>,
+}
+
+/// 銘柄ごとの筋書きどおりの価格の推移。最後まで進んだら最後の価格のまま動かない。
+struct PricePath {
+    prices: Vec<Price>,
+    index: usize,
+    high: Price,
+    low: Price,
+}
+
+impl PricePath {
+    fn new(prices: Vec<Price>) -> PricePath {
+        PricePath {
+            high: prices[0],
+            low: prices[0],
+            prices,
+            index: 0,
+        }
+    }
+
+    /// 現在の価格。
+    fn price(&self) -> Price {
+        self.prices[self.index]
+    }
+
+    fn advance(&mut self) {
+        if self.index + 1 < self.prices.len() {
+            self.index += 1;
+        }
+        self.high = self.high.max(self.price());
+        self.low = self.low.min(self.price());
+    }
+}
+
+/// 約定エンジンと、エンジンに気配を与える価格の推移。
+struct Market {
+    engine: Engine,
+    paths: BTreeMap<String, PricePath>,
+}
+
+impl Market {
+    fn add_price_path(&mut self, symbol: &str, prices: Vec<Price>) {
+        let path = PricePath::new(prices);
+        self.engine.set_quote(symbol, Quote::at(path.price()));
+        self.paths.insert(symbol.to_string(), path);
+    }
+
+    /// すべての銘柄の価格を1つ進め、ロスカットと注文の約定を判定する。
+    fn advance(&mut self) {
+        for (symbol, path) in self.paths.iter_mut() {
+            path.advance();
+            self.engine.set_quote(symbol, Quote::at(path.price()));
+        }
+        self.engine.update();
+    }
+}
+
+/// サーバーが止まるまで`interval`ごとに価格を進める。
+async fn tick(state: Weak<State>, interval: Duration) {
+    loop {
+        tokio::time::delay_for(interval).await;
+        match state.upgrade() {
+            Some(s) => s.market.lock().unwrap().advance(),
+            None => return,
+        }
+    }
+}
+
+/// リクエストをパスで振り分け、GMOコインと同じ形式のレスポンスを返す。
+async fn handle(state: Arc<State>, request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let (parts, body) = request.into_parts();
+    let body = hyper::body::to_bytes(body).await.unwrap_or_default();
+    let body = String::from_utf8_lossy(&body).to_string();
+    let query = exchange::parse_query(parts.uri.query());
+    let method = parts.method.as_str();
+    let path = parts.uri.path();
+    let result = if let Some(path) = path.strip_prefix("/public") {
+        public_route(&state.market.lock().unwrap(), method, path, &query)
+    } else if let Some(path) = path.strip_prefix("/private") {
+        authenticate(&state.credentials, &parts.headers, method, path, &body).and_then(|_| {
+            exchange::private_route(
+                &mut state.market.lock().unwrap().engine,
+                method,
+                path,
+                &query,
+                &body,
+            )
+        })
+    } else if method == "POST" && path == ADVANCE_PATH {
+        let mut market = state.market.lock().unwrap();
+        market.advance();
+        Ok(Some(prices_json(&market)))
+    } else {
+        Err(ApiErrorCode::InvalidPath)
+    };
+    let (http_status_code, body) = exchange::to_response(result);
+    Ok(Response::builder()
+        .status(http_status_code)
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap())
+}
+
+/// Private APIのヘッダーを検証する。署名は本番と同じく`API-TIMESTAMP`, メソッド, パス, ボディをつなげた文字列のHMAC-SHA256。
+fn authenticate(
+    credentials: &Credentials,
+    headers: &HeaderMap,
+    method: &str,
+    path: &str,
+    body: &str,
+) -> Result<(), ApiErrorCode> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let api_key = header("api-key").ok_or(ApiErrorCode::ApiKeyNotSet)?;
+    if api_key != credentials.api_key() {
+        return Err(ApiErrorCode::AuthenticationFailed);
+    }
+    let api_timestamp = header("api-timestamp").ok_or(ApiErrorCode::InvalidSignature)?;
+    let millis: i64 = api_timestamp
+        .parse()
+        .map_err(|_| ApiErrorCode::InvalidSignature)?;
+    let now = Utc::now().timestamp_millis();
+    if millis < now - TIMESTAMP_TOLERANCE_MILLIS {
+        return Err(ApiErrorCode::TimestampTooLate);
+    }
+    if millis > now + TIMESTAMP_TOLERANCE_MILLIS {
+        return Err(ApiErrorCode::TimestampTooEarly);
+    }
+    let sign = header("api-sign")
+        .and_then(|s| hex::decode(s).ok())
+        .ok_or(ApiErrorCode::InvalidSignature)?;
+    let text = format!("{}{}{}{}", api_timestamp, method, path, body);
+    let key = hmac::Key::new(hmac::HMAC_SHA256, credentials.api_secret().as_bytes());
+    hmac::verify(&key, text.as_bytes(), &sign).map_err(|_| ApiErrorCode::InvalidSignature)
+}
+
+/// Public APIを処理する。
+fn public_route(
+    market: &Market,
+    method: &str,
+    path: &str,
+    query: &HashMap<String, String>,
+) -> RouteResult {
+    match (method, path) {
+        ("GET", "/v1/status") => Ok(Some(json!({"status": "OPEN"}))),
+        ("GET", "/v1/ticker") => {
+            let symbol = query.get("symbol");
+            let tickers: Vec<Value> = market
+                .paths
+                .iter()
+                .filter(|(s, _)| symbol.is_none() || symbol == Some(*s))
+                .map(|(symbol, path)| {
+                    json!({
+                        "ask": path.price().to_string(),
+                        "bid": path.price().to_string(),
+                        "high": path.high.to_string(),
+                        "last": path.price().to_string(),
+                        "low": path.low.to_string(),
+                        "symbol": symbol,
+                        "timestamp": timestamp(Utc::now()),
+                        "volume": market.engine.volume(symbol).to_string(),
+                    })
+                })
+                .collect();
+            Ok(Some(json!(tickers)))
+        }
+        ("GET", "/v1/orderbooks") => {
+            let symbol = query_symbol(query)?;
+            let price = market
+                .paths
+                .get(symbol)
+                .ok_or(ApiErrorCode::InvalidParameter)?
+                .price();
+            Ok(Some(json!({
+                "asks": [{"price": price.to_string(), "size": "1"}],
+                "bids": [{"price": price.to_string(), "size": "1"}],
+                "symbol": symbol,
+            })))
+        }
+        _ => Err(ApiErrorCode::InvalidPath),
+    }
+}
+
+/// 銘柄ごとの現在の価格。
+fn prices_json(market: &Market) -> Value {
+    let prices: Vec<Value> = market
+        .paths
+        .iter()
+        .map(|(symbol, path)| json!({"symbol": symbol, "price": path.price().to_string()}))
+        .collect();
+    json!(prices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution_type::ExecutionType;
+    use crate::http_client::Reqwest;
+    use crate::price::price;
+    use crate::private::PrivateAPI;
+    use crate::public::PublicAPI;
+    use crate::side::Side;
+    use crate::size::size;
+
+    async fn start() -> SimulatorHandle {
+        Simulator::new(Credentials::new("key", "secret"))
+            .with_price_path(
+                &Symbol::BtcJpy,
+                vec![
+                    price("1000000"),
+                    price("990000"),
+                    price("980000"),
+                    price("1020000"),
+                ],
+            )
+            .with_liquidity(size("0.3"))
+            .start("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_order_lifecycle() {
+        let simulator = start().await;
+        let private_api = PrivateAPI::new(Reqwest::new())
+            .with_config(simulator.config())
+            .with_credentials(Credentials::new("key", "secret"));
+
+        let order = private_api
+            .order(
+                &ExecutionType::Limit,
+                &Symbol::BtcJpy,
+                &Side::Buy,
+                size("0.5"),
+                Some(price("950000")),
+            )
+            .await
+            .unwrap();
+        private_api
+            .change_order(order.order_id(), price("985000"))
+            .await
+            .unwrap();
+        simulator.advance();
+        simulator.advance();
+        let active = private_api.active_orders(&Symbol::BtcJpy).await.unwrap();
+        assert_eq!(active.active_orders().len(), 1);
+        assert_eq!(active.active_orders()[0].executed_size, size("0.3"));
+        simulator.advance();
+        let active = private_api.active_orders(&Symbol::BtcJpy).await.unwrap();
+        assert_eq!(active.active_orders().len(), 1);
+        private_api.cancel_order(order.order_id()).await.unwrap();
+        let executions = private_api
+            .executions_with_order_id(order.order_id())
+            .await
+            .unwrap();
+        assert_eq!(executions.executions().len(), 1);
+        assert_eq!(executions.executions()[0].price, price("985000"));
+
+        let positions = private_api.open_positions(&Symbol::BtcJpy).await.unwrap();
+        let position = &positions.open_positions()[0];
+        assert_eq!(position.size, size("0.3"));
+        private_api
+            .close_order(
+                &ExecutionType::Market,
+                &Symbol::BtcJpy,
+                &Side::Sell,
+                None,
+                &[(&position.position_id, size("0.3"))],
+            )
+            .await
+            .unwrap();
+        let positions = private_api.open_positions(&Symbol::BtcJpy).await.unwrap();
+        assert_eq!(positions.open_positions().len(), 0);
+        assert_eq!(simulator.balance(), 1_010_500);
+        let margin = private_api.margin().await.unwrap();
+        assert_eq!(margin.actual_profit_loss(), 1_010_500);
+
+        let err = match private_api
+            .close_order(
+                &ExecutionType::Market,
+                &Symbol::BtcJpy,
+                &Side::Sell,
+                None,
+                &[(&position.position_id, size("0.3"))],
+            )
+            .await
+        {
+            Err(e) => e,
+            Ok(_) => panic!("決済済みの建玉を決済できてしまった"),
+        };
+        // 決済済みの建玉は発注前の建玉一覧の検証で弾かれる。
+        assert!(matches!(err, Error::PositionNotFoundError(_)));
+        assert_eq!(
+            private_api.margin().await.unwrap().actual_profit_loss(),
+            1_010_500
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_invalid_signature() {
+        let simulator = start().await;
+        let private_api = PrivateAPI::new(Reqwest::new())
+            .with_config(simulator.config())
+            .with_credentials(Credentials::new("key", "wrong"));
+        let err = match private_api.margin().await {
+            Err(e) => e,
+            Ok(_) => panic!("エラーレスポンスを受け取れなかった"),
+        };
+        assert_eq!(
+            err.api_error_code(),
+            Some(ApiErrorCode::InvalidSignature.into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_public_api() {
+        let simulator = start().await;
+        let public_api = PublicAPI::new(Reqwest::new()).with_config(simulator.config());
+        assert!(public_api.status().await.unwrap().is_open());
+        simulator.advance();
+        let ticker = public_api.ticker(&Symbol::BtcJpy).await.unwrap();
+        assert_eq!(ticker.ask().unwrap(), price("990000"));
+    }
+}
@@ -0,0 +1,53 @@
+//! 注文数量や残高を表す型を定義する。
+//! `decimal`フィーチャーを有効にすると、誤差の出ない十進数型(`rust_decimal::Decimal`)になる。
+
+/// 注文数量や残高を表す型。
+#[cfg(not(feature = "decimal"))]
+pub type Size = f64;
+
+/// 注文数量や残高を表す型。
+#[cfg(feature = "decimal")]
+pub type Size = rust_decimal::Decimal;
+
+/// `Size`をf64に変換する。
+#[cfg(not(feature = "decimal"))]
+pub(crate) fn size_to_f64(size: Size) -> f64 {
+    size
+}
+
+/// `Size`をf64に変換する。
+#[cfg(feature = "decimal")]
+pub(crate) fn size_to_f64(size: Size) -> f64 {
+    use rust_decimal::prelude::ToPrimitive;
+    size.to_f64().unwrap_or(f64::NAN)
+}
+
+/// `size`が`limit`を超えているか？f64の計算誤差で超えたと判定しないように、わずかな差は無視する。
+#[cfg(not(feature = "decimal"))]
+pub(crate) fn size_exceeds(size: Size, limit: Size) -> bool {
+    size - limit > 1e-8
+}
+
+/// `size`が`limit`を超えているか？十進数型なので誤差なく判定する。
+#[cfg(feature = "decimal")]
+pub(crate) fn size_exceeds(size: Size, limit: Size) -> bool {
+    size > limit
+}
+
+/// 計算で出た誤差を取り除く。f64では小数点以下8桁に丸める。
+#[cfg(not(feature = "decimal"))]
+pub(crate) fn normalize_size(size: Size) -> Size {
+    (size * 1e8).round() / 1e8
+}
+
+/// 計算で出た誤差を取り除く。十進数型では誤差が出ないので、末尾の0だけを取り除く。
+#[cfg(feature = "decimal")]
+pub(crate) fn normalize_size(size: Size) -> Size {
+    size.normalize()
+}
+
+/// 文字列から`Size`を作る。テストで使う。
+#[cfg(test)]
+pub(crate) fn size(s: &str) -> Size {
+    s.parse().unwrap()
+}
@@ -1,7 +1,10 @@
 //! 執行数量条件を定義する。
 
+use serde::{Deserialize, Serialize};
+
 /// 執行数量条件
-#[derive(Copy, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
 pub enum TimeInForce {
     /// 注文が一部約定後に未執行数量が残った場合、その残数量を失効とする条件。
     Fak,
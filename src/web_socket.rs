@@ -0,0 +1,84 @@
+//! WebSocket APIで共通して使う処理を実装する。
+
+use crate::error::Error;
+use futures::SinkExt;
+use serde_json::Value;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+/// GMOコインのWebSocket APIとの接続。
+pub(crate) type Connection = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// 購読を開始するときのコマンド。
+pub(crate) const SUBSCRIBE_COMMAND: &str = "subscribe";
+
+/// 購読を解除するときのコマンド。
+pub(crate) const UNSUBSCRIBE_COMMAND: &str = "unsubscribe";
+
+/// WebSocket APIに接続する。
+pub(crate) async fn connect(url: &str) -> Result<Connection, Error> {
+    let url = url::Url::parse(url)?;
+    let (connection, _) = connect_async(url).await?;
+    Ok(connection)
+}
+
+/// JSONをテキストメッセージとして送信する。
+pub(crate) async fn send_json(connection: &mut Connection, message: &Value) -> Result<(), Error> {
+    connection.send(Message::Text(message.to_string())).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use futures::{SinkExt, StreamExt};
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+    use tokio_tungstenite::accept_async;
+    use tokio_tungstenite::tungstenite::Message;
+
+    /// 単体テスト用のWebSocketサーバー。
+    /// クライアントから受け取ったテキストメッセージを`received`に流し、用意したメッセージを順に送り返す。
+    pub(crate) struct InmemServer {
+        pub(crate) url: String,
+        pub(crate) received: mpsc::UnboundedReceiver<String>,
+    }
+
+    impl InmemServer {
+        /// サーバーを起動する。クライアントからテキストメッセージを1件受け取るごとに`replies`を1件ずつ送り返す。
+        pub(crate) async fn start(replies: Vec<String>) -> InmemServer {
+            InmemServer::start_with_connections(vec![replies]).await
+        }
+
+        /// 複数回の接続を受け付けるサーバーを起動する。
+        /// `connections`の要素が1回の接続で送り返すメッセージに対応する。
+        /// 最後の接続以外は、メッセージを全て送り返したらサーバー側から接続を切る。
+        pub(crate) async fn start_with_connections(connections: Vec<Vec<String>>) -> InmemServer {
+            let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("ws://{}", listener.local_addr().unwrap());
+            let (tx, rx) = mpsc::unbounded_channel();
+            tokio::spawn(async move {
+                let count = connections.len();
+                for (i, replies) in connections.into_iter().enumerate() {
+                    let (stream, _) = listener.accept().await.unwrap();
+                    let mut connection = accept_async(stream).await.unwrap();
+                    let is_last = i + 1 == count;
+                    let mut replies = replies.into_iter().peekable();
+                    while let Some(Ok(message)) = connection.next().await {
+                        if let Message::Text(text) = message {
+                            let _ = tx.send(text);
+                            if let Some(reply) = replies.next() {
+                                connection.send(Message::Text(reply)).await.unwrap();
+                            }
+                            if !is_last && replies.peek().is_none() {
+                                connection.close(None).await.unwrap();
+                                break;
+                            }
+                        }
+                    }
+                }
+            });
+            InmemServer { url, received: rx }
+        }
+    }
+}